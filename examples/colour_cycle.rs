@@ -1,22 +1,19 @@
 use std::error::Error;
 
-use simple_game_engine::{self as sge, prelude::*};
+use simple_game_engine::{self as sge, color::Hsv, prelude::*};
 
-const CYCLE_SPEED: f32 = 130.0;
+// Degrees of hue per second
+const CYCLE_SPEED: f64 = 90.0;
 const SCREEN_WIDTH: u32 = 480;
 const SCREEN_HEIGHT: u32 = 360;
 
 struct App {
-    col: f32,
-    flipper: bool,
+    hue: f64,
 }
 
 impl App {
     pub fn new() -> Self {
-        Self {
-            col: 255.0,
-            flipper: true,
-        }
+        Self { hue: 0.0 }
     }
 }
 
@@ -25,25 +22,18 @@ impl sge::Application for App {
         &mut self,
         canvas: &mut WindowCanvas,
         input: &InputState,
+        _audio: &Audio,
         elapsed_time: f64,
     ) -> sge::ApplicationResult {
         // Handle keyboard input
         if input.keyboard.pressed(Scancode::Q) {
             return Ok(false);
         }
-        // If we're at the bounds for a colour value, change direction
-        if self.col <= 0.0 || self.col >= 255.0 {
-            self.flipper = !self.flipper;
-        }
         // Fill the screen with the current colour
-        canvas.set_draw_color(Color::RGB(self.col as u8, 0, 255 - self.col as u8));
+        canvas.set_draw_color(Hsv::new(self.hue, 1.0, 1.0));
         canvas.clear();
-        // Change the colour
-        if !self.flipper {
-            self.col -= CYCLE_SPEED * elapsed_time as f32;
-        } else {
-            self.col += CYCLE_SPEED * elapsed_time as f32;
-        }
+        // Move around the colour wheel
+        self.hue = (self.hue + CYCLE_SPEED * elapsed_time) % 360.0;
         Ok(true)
     }
 
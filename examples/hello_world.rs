@@ -8,6 +8,7 @@ impl sge::Application for App {
         &mut self,
         _canvas: &mut WindowCanvas,
         _input: &InputState,
+        _audio: &Audio,
     ) -> sge::ApplicationResult {
         Ok(true) // `true` indicates to continue running the application
     }
@@ -16,6 +17,7 @@ impl sge::Application for App {
         &mut self,
         canvas: &mut WindowCanvas,
         _input: &InputState,
+        _audio: &Audio,
         _elapsed_time: f64,
     ) -> sge::ApplicationResult {
         canvas.set_draw_color(Color::BLACK);
@@ -23,6 +23,7 @@ impl sge::Application for App {
         &mut self,
         canvas: &mut WindowCanvas,
         input: &InputState,
+        _audio: &Audio,
         elapsed_time: f64,
     ) -> sge::ApplicationResult {
         // Move the rectangle with the keyboard
@@ -0,0 +1,281 @@
+//! Directories of assets packed into a single archive.
+
+use std::{
+    borrow::Cow,
+    fs,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use fnv::FnvHashMap;
+
+/// Identifies a bundle, and the version of its format.
+const MAGIC: &[u8; 8] = b"sgebndl1";
+
+/// The files of an assets directory packed into a single archive, which an [`Assets`]
+/// manager can load from [instead of the directory][Assets::with_bundle].
+///
+/// A bundle is made with [`pack`][Self::pack], usually from a build script, then embedded in
+/// the binary with [`include_bytes`] and opened with [`from_static`][Self::from_static], so the
+/// game is a single file:
+/// ```no_run
+/// // build.rs
+/// use std::{env, path::Path};
+///
+/// use simple_game_engine::assets::Bundle;
+///
+/// fn main() {
+///     let out_dir = env::var("OUT_DIR").unwrap();
+///     Bundle::pack("assets", Path::new(&out_dir).join("assets.bundle")).unwrap();
+///     println!("cargo:rerun-if-changed=assets");
+/// }
+/// ```
+/// Debug builds can then load straight from the directory, with the same paths:
+/// ```no_run
+/// # use simple_game_engine::{assets::{Assets, Bundle}, prelude::*};
+/// # fn create(canvas: &mut WindowCanvas) -> Result<Assets, String> {
+/// let assets = Assets::new((**canvas).texture_creator());
+/// #[cfg(debug_assertions)]
+/// let assets = assets.with_directory("assets");
+/// #[cfg(not(debug_assertions))]
+/// let assets = assets.with_bundle(Bundle::from_static(include_bytes!(concat!(
+///     env!("OUT_DIR"),
+///     "/assets.bundle"
+/// )))?);
+/// # Ok(assets)
+/// # }
+/// ```
+///
+/// [`Assets`]: super::Assets
+/// [Assets::with_bundle]: super::Assets::with_bundle
+#[derive(Clone)]
+pub struct Bundle {
+    data: Cow<'static, [u8]>,
+    /// Where the contents of each file are in `data`, keyed by its path within the bundle.
+    files: FnvHashMap<String, Range<usize>>,
+}
+
+impl Bundle {
+    /// Pack every file in `dir` and its subdirectories into a bundle. Each file is stored under
+    /// its path relative to `dir`. Returns a `String` describing the error, starting with the
+    /// path, if a file or directory can't be read.
+    pub fn pack_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<u8>, String> {
+        let mut files = Vec::new();
+        find_files(dir.as_ref(), String::new(), &mut files)?;
+        let mut bundle = MAGIC.to_vec();
+        bundle.extend_from_slice(&(files.len() as u32).to_le_bytes());
+        for (name, path) in files {
+            let contents = fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            bundle.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            bundle.extend_from_slice(name.as_bytes());
+            bundle.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+            bundle.extend_from_slice(&contents);
+        }
+        Ok(bundle)
+    }
+
+    /// Pack every file in `dir` and its subdirectories into a bundle, and write it to `output`.
+    /// Returns a `String` describing the error, starting with the path, if a file can't be read
+    /// or written.
+    pub fn pack<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, output: Q) -> Result<(), String> {
+        let output = output.as_ref();
+        let bundle = Self::pack_dir(dir)?;
+        fs::write(output, bundle).map_err(|e| format!("{}: {}", output.display(), e))
+    }
+
+    /// Open a bundle embedded in the binary, without copying it. Returns a `String` describing
+    /// the error if it isn't a valid bundle.
+    pub fn from_static(data: &'static [u8]) -> Result<Self, String> {
+        Self::parse(Cow::Borrowed(data))
+    }
+
+    /// Open a bundle that has been read into memory. Returns a `String` describing the error
+    /// if it isn't a valid bundle.
+    pub fn from_vec(data: Vec<u8>) -> Result<Self, String> {
+        Self::parse(Cow::Owned(data))
+    }
+
+    /// Read a bundle from a file. Returns a `String` describing the error, starting with the
+    /// path, if it can't be read or isn't a valid bundle.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(Self::from_vec)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Returns the contents of the file at `path` within the bundle, or `None` if there is no
+    /// such file. `.` and `..` in the path are resolved, and `/` and `\` both separate
+    /// directories.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&[u8]> {
+        let range = self.files.get(&name(path.as_ref())?)?;
+        Some(&self.data[range.clone()])
+    }
+
+    /// Returns whether the bundle holds a file at `path`.
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.get(path).is_some()
+    }
+
+    /// Returns the paths of the files in the bundle, in no particular order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Returns the number of files in the bundle.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns whether the bundle holds no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Find where each file is in a packed bundle.
+    fn parse(data: Cow<'static, [u8]>) -> Result<Self, String> {
+        if !data.starts_with(MAGIC) {
+            return Err("Not an asset bundle".to_string());
+        }
+        let mut reader = Reader {
+            data: &data,
+            position: MAGIC.len(),
+        };
+        let count = reader.u32()?;
+        let mut files = FnvHashMap::default();
+        for _ in 0..count {
+            let len = reader.u32()? as usize;
+            let name = std::str::from_utf8(&data[reader.take(len)?])
+                .map_err(|_| "The bundle has a path that isn't valid UTF-8".to_string())?;
+            let len = reader.u64()? as usize;
+            files.insert(name.to_string(), reader.take(len)?);
+        }
+        Ok(Self { data, files })
+    }
+}
+
+/// Reads the parts of a packed bundle in order.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    /// Returns the range of the next `len` bytes, and moves past them.
+    fn take(&mut self, len: usize) -> Result<Range<usize>, String> {
+        let start = self.position;
+        match start.checked_add(len) {
+            Some(end) if end <= self.data.len() => {
+                self.position = end;
+                Ok(start..end)
+            }
+            _ => Err("The bundle is truncated".to_string()),
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let range = self.take(4)?;
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.data[range]);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        let range = self.take(8)?;
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.data[range]);
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+/// Add every file in `dir` and its subdirectories to `files`, with its name in the bundle and
+/// its path on disk, sorted so the same directory always packs into the same bundle.
+fn find_files(
+    dir: &Path,
+    prefix: String,
+    files: &mut Vec<(String, PathBuf)>,
+) -> Result<(), String> {
+    let error = |e: std::io::Error| format!("{}: {}", dir.display(), e);
+    let mut entries = fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name = file_name
+            .to_str()
+            .ok_or_else(|| format!("{}: The path isn't valid UTF-8", path.display()))?;
+        let name = format!("{}{}", prefix, file_name);
+        if path.is_dir() {
+            find_files(&path, name + "/", files)?;
+        } else {
+            files.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name a file is stored under in a bundle, or `None` if the path is absolute or
+/// leads outside the bundle.
+pub(super) fn name(path: &Path) -> Option<String> {
+    let path = path.to_str()?.replace('\\', "/");
+    let mut parts = Vec::new();
+    for component in Path::new(&path).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => (),
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_dir;
+
+    /// Test packing a directory, and reading the files back out of the bundle.
+    #[test]
+    fn test_bundle() {
+        let dir = test_dir("bundle");
+        fs::create_dir_all(dir.join("sounds")).unwrap();
+        fs::write(dir.join("a.txt"), "first").unwrap();
+        fs::write(dir.join("sounds").join("b.txt"), "second").unwrap();
+        fs::write(dir.join("empty"), "").unwrap();
+
+        let packed = Bundle::pack_dir(&dir).unwrap();
+        assert_eq!(Bundle::pack_dir(&dir).unwrap(), packed);
+        let bundle = Bundle::from_vec(packed.clone()).unwrap();
+        assert_eq!(bundle.len(), 3);
+        let mut paths: Vec<_> = bundle.paths().collect();
+        paths.sort_unstable();
+        assert_eq!(paths, ["a.txt", "empty", "sounds/b.txt"]);
+        assert_eq!(bundle.get("a.txt"), Some(&b"first"[..]));
+        assert_eq!(bundle.get("./sounds/../sounds/b.txt"), Some(&b"second"[..]));
+        assert_eq!(bundle.get("sounds\\b.txt"), Some(&b"second"[..]));
+        assert_eq!(bundle.get("empty"), Some(&b""[..]));
+        assert!(!bundle.contains("missing.txt"));
+        assert!(!bundle.contains("../a.txt"));
+        assert!(!bundle.contains("/a.txt"));
+
+        let output = dir.join("assets.bundle");
+        Bundle::pack(dir.join("sounds"), &output).unwrap();
+        let bundle = Bundle::load(&output).unwrap();
+        assert_eq!(bundle.paths().collect::<Vec<_>>(), ["b.txt"]);
+
+        assert!(Bundle::from_static(b"not a bundle").is_err());
+        assert_eq!(
+            Bundle::from_vec(packed[..packed.len() - 1].to_vec()).err(),
+            Some("The bundle is truncated".to_string())
+        );
+        let error = Bundle::load(dir.join("missing.bundle")).err().unwrap();
+        assert!(error.starts_with(&format!("{}: ", dir.join("missing.bundle").display())));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
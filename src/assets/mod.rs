@@ -0,0 +1,680 @@
+//! Loading textures, fonts, sounds and maps from files, and reloading them when they change.
+//!
+//! An [`Assets`] manager loads each file once, however many times it's asked for, and hands
+//! out typed [`Handle`]s to what it loaded. Handles don't borrow anything, so they can be kept
+//! in your application alongside the manager, and textures are owned by the manager, so their
+//! lifetimes aren't tied to the canvas's texture creator.
+//!
+//! During development, [`watch`][Assets::watch] for changes and call
+//! [`reload_changed`][Assets::reload_changed] every frame, to see changes to assets as soon as
+//! they're saved, without restarting.
+//!
+//! To ship a game as a single file, pack its assets directory into a [`Bundle`], embed that in
+//! the binary, and load assets [from the bundle][Assets::with_bundle]. Assets are loaded with
+//! the same paths from a bundle as from a [directory][Assets::with_directory], so only the line
+//! choosing where they come from needs to differ between debug and release builds.
+//! # Example
+//! ```no_run
+//! use simple_game_engine::{self as sge, assets::{Assets, TextureHandle}, prelude::*};
+//!
+//! #[derive(Default)]
+//! struct App {
+//!     assets: Option<Assets>,
+//!     player: Option<TextureHandle>,
+//! }
+//!
+//! impl sge::Application for App {
+//!     fn on_create(
+//!         &mut self,
+//!         canvas: &mut WindowCanvas,
+//!         _input: &InputState,
+//!         _audio: &Audio,
+//!     ) -> sge::ApplicationResult {
+//!         let mut assets = Assets::new((**canvas).texture_creator());
+//!         assets.watch(true);
+//!         self.player = Some(assets.load_texture("assets/player.bmp")?);
+//!         self.assets = Some(assets);
+//!         Ok(true)
+//!     }
+//!
+//!     fn on_update(
+//!         &mut self,
+//!         canvas: &mut WindowCanvas,
+//!         _input: &InputState,
+//!         _audio: &Audio,
+//!         _elapsed_time: f64,
+//!     ) -> sge::ApplicationResult {
+//!         let assets = self.assets.as_mut().unwrap();
+//!         if let Err(e) = assets.reload_changed() {
+//!             eprintln!("{}", e);
+//!         }
+//!         let player = assets.texture(self.player.unwrap()).unwrap();
+//!         canvas.copy(&player, None, Rect::new(100, 100, 32, 32))?;
+//!         Ok(true)
+//!     }
+//! }
+//! ```
+
+mod bundle;
+pub use bundle::Bundle;
+mod store;
+use store::{Files, Source, Store};
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use sdl2::{
+    render::{Texture, TextureCreator},
+    rwops::RWops,
+    surface::Surface,
+    video::WindowContext,
+};
+
+#[cfg(feature = "tiled")]
+use crate::tilemap::Tilemap;
+use crate::{
+    audio::Sound,
+    canvas::{Font, LayerTexture},
+};
+
+/// The height of a line of text outline fonts are loaded at.
+#[cfg(feature = "ttf")]
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+/// The ID of the next [`Assets`] manager to be created.
+static NEXT_MANAGER: AtomicU32 = AtomicU32::new(0);
+
+/// Identifies an asset loaded by an [`Assets`] manager. `T` is the kind of asset.
+pub struct Handle<T> {
+    /// The ID of the manager that loaded the asset.
+    manager: u32,
+    index: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(manager: u32, index: usize) -> Self {
+        Self {
+            manager,
+            index,
+            marker: PhantomData,
+        }
+    }
+}
+
+// Derived implementations would only apply to handles of assets with these traits
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.manager, self.index) == (other.manager, other.index)
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.manager, self.index).hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({}, {})", self.manager, self.index)
+    }
+}
+
+/// A handle to a texture.
+pub type TextureHandle = Handle<Texture<'static>>;
+/// A handle to a font.
+pub type FontHandle = Handle<Font>;
+/// A handle to a sound.
+pub type SoundHandle = Handle<Sound>;
+/// A handle to a map.
+#[cfg(feature = "tiled")]
+pub type MapHandle = Handle<Tilemap>;
+
+/// A texture owned by an [`Assets`] manager, borrowed for passing to the sdl2 API, such as
+/// [`copy`][sdl2::render::Canvas::copy].
+pub struct AssetTexture<'a>(ManuallyDrop<Texture<'a>>);
+
+impl<'a> Deref for AssetTexture<'a> {
+    type Target = Texture<'a>;
+
+    fn deref(&self) -> &Texture<'a> {
+        &self.0
+    }
+}
+
+/// Loads and owns textures, fonts, sounds, and with the "tiled" feature, maps, handing out
+/// [`Handle`]s to them. See the [module documentation][self] for an example.
+pub struct Assets<U = WindowContext> {
+    // Must be dropped before the texture creator, which keeps the renderer alive
+    textures: Store<LayerTexture>,
+    fonts: Store<Font>,
+    sounds: Store<Sound>,
+    #[cfg(feature = "tiled")]
+    maps: Store<Tilemap>,
+    source: Source,
+    watching: bool,
+    /// Stored in handles, so handles from other managers are never used as indices.
+    id: u32,
+    texture_creator: TextureCreator<U>,
+}
+
+impl<U> Assets<U> {
+    /// Create an asset manager, that loads textures with `texture_creator`. The texture
+    /// creator keeps the renderer alive, so textures can be drawn until the manager is
+    /// dropped.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{assets::Assets, prelude::*};
+    /// # fn create(canvas: &mut WindowCanvas) {
+    /// let assets = Assets::new((**canvas).texture_creator());
+    /// # }
+    /// ```
+    pub fn new(texture_creator: TextureCreator<U>) -> Self {
+        Self {
+            textures: Store::new(),
+            fonts: Store::new(),
+            sounds: Store::new(),
+            #[cfg(feature = "tiled")]
+            maps: Store::new(),
+            source: Source::Directory(PathBuf::new()),
+            watching: false,
+            id: NEXT_MANAGER.fetch_add(1, Ordering::Relaxed),
+            texture_creator,
+        }
+    }
+
+    /// Load assets from files in `dir`, rather than the current directory. Paths to load are
+    /// relative to it, and the paths of loaded assets are the paths they were loaded with. This
+    /// should be chosen before anything is loaded.
+    pub fn with_directory<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.source = Source::Directory(dir.into());
+        self
+    }
+
+    /// Load assets from files packed into `bundle`, rather than the current directory. Paths to
+    /// load are within the bundle, so are the same as they would be
+    /// [from the directory][Self::with_directory] that was packed. Files in a bundle never
+    /// change, so are never [reloaded][Self::reload_changed]. This should be chosen before
+    /// anything is loaded.
+    pub fn with_bundle(mut self, bundle: Bundle) -> Self {
+        self.source = Source::Bundle(bundle);
+        self
+    }
+
+    /// Load a texture from a BMP file, or with the "png" feature, a PNG file. Returns a handle
+    /// to the texture, which is the same handle every time the same file is loaded, or a
+    /// `String` describing the error, starting with the path, if it can't be loaded.
+    pub fn load_texture<P: AsRef<Path>>(&mut self, path: P) -> Result<TextureHandle, String> {
+        let creator = &self.texture_creator;
+        self.textures
+            .load(&self.source, path.as_ref(), |path, data, _| {
+                load_texture(creator, path, &data)
+            })
+            .map(|index| Handle::new(self.id, index))
+    }
+
+    /// Returns a texture, or `None` if the handle came from another asset manager.
+    pub fn texture(&self, handle: TextureHandle) -> Option<AssetTexture<'_>> {
+        let texture = self.textures.get(self.index(handle)?)?;
+        Some(AssetTexture(texture.as_texture(&self.texture_creator)))
+    }
+
+    /// Returns the file a texture was loaded from.
+    pub fn texture_path(&self, handle: TextureHandle) -> Option<&Path> {
+        self.textures.path(self.index(handle)?)
+    }
+
+    /// Load a font from a BDF or PSF file, or with the "ttf" feature, a TrueType or OpenType
+    /// file, choosing the format from the file extension. Outline fonts are loaded with lines
+    /// 16 pixels tall, which can be changed with [`font_mut`][Self::font_mut]. Returns a handle
+    /// to the font, which is the same handle every time the same file is loaded, or a `String`
+    /// describing the error, starting with the path, if it can't be loaded.
+    ///
+    /// Select the font for drawing text with a clone of it, which is cheap:
+    /// ```no_run
+    /// # use simple_game_engine::{assets::Assets, prelude::*};
+    /// # fn load(canvas: &mut WindowCanvas, assets: &mut Assets) -> Result<(), String> {
+    /// let font = assets.load_font("assets/font.bdf")?;
+    /// canvas.set_font(assets.font(font).cloned());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_font<P: AsRef<Path>>(&mut self, path: P) -> Result<FontHandle, String> {
+        self.fonts
+            .load(&self.source, path.as_ref(), load_font)
+            .map(|index| Handle::new(self.id, index))
+    }
+
+    /// Returns a font, or `None` if the handle came from another asset manager.
+    pub fn font(&self, handle: FontHandle) -> Option<&Font> {
+        self.fonts.get(self.index(handle)?)
+    }
+
+    /// Returns a font, so its size or fallback character can be changed.
+    pub fn font_mut(&mut self, handle: FontHandle) -> Option<&mut Font> {
+        self.fonts.get_mut(self.index(handle)?)
+    }
+
+    /// Returns the file a font was loaded from.
+    pub fn font_path(&self, handle: FontHandle) -> Option<&Path> {
+        self.fonts.path(self.index(handle)?)
+    }
+
+    /// Load a sound from a `.wav` file, or with the "ogg" feature, an ogg vorbis file. Returns
+    /// a handle to the sound, which is the same handle every time the same file is loaded, or a
+    /// `String` describing the error, starting with the path, if it can't be loaded.
+    pub fn load_sound<P: AsRef<Path>>(&mut self, path: P) -> Result<SoundHandle, String> {
+        self.sounds
+            .load(&self.source, path.as_ref(), load_sound)
+            .map(|index| Handle::new(self.id, index))
+    }
+
+    /// Returns a sound, or `None` if the handle came from another asset manager.
+    pub fn sound(&self, handle: SoundHandle) -> Option<&Sound> {
+        self.sounds.get(self.index(handle)?)
+    }
+
+    /// Returns the file a sound was loaded from.
+    pub fn sound_path(&self, handle: SoundHandle) -> Option<&Path> {
+        self.sounds.path(self.index(handle)?)
+    }
+
+    /// Load a map made with Tiled, in either its XML or JSON format; see
+    /// [`Tilemap::load`]. External tilesets are read from the same directory or bundle as the
+    /// map, and tileset image paths are relative to it too, so they can be loaded with
+    /// [`load_texture`][Self::load_texture]. Returns a handle to the map, which is the same
+    /// handle every time the same file is loaded, or a `String` describing the error, starting
+    /// with the path, if it can't be loaded.
+    #[cfg(feature = "tiled")]
+    pub fn load_map<P: AsRef<Path>>(&mut self, path: P) -> Result<MapHandle, String> {
+        self.maps
+            .load(&self.source, path.as_ref(), load_map)
+            .map(|index| Handle::new(self.id, index))
+    }
+
+    /// Returns a map, or `None` if the handle came from another asset manager.
+    #[cfg(feature = "tiled")]
+    pub fn map(&self, handle: MapHandle) -> Option<&Tilemap> {
+        self.maps.get(self.index(handle)?)
+    }
+
+    /// Returns a map, so it can be changed or [updated][Tilemap::update].
+    #[cfg(feature = "tiled")]
+    pub fn map_mut(&mut self, handle: MapHandle) -> Option<&mut Tilemap> {
+        self.maps.get_mut(self.index(handle)?)
+    }
+
+    /// Returns the file a map was loaded from.
+    #[cfg(feature = "tiled")]
+    pub fn map_path(&self, handle: MapHandle) -> Option<&Path> {
+        self.maps.path(self.index(handle)?)
+    }
+
+    /// Returns the index of a handle's asset, or `None` if the handle came from another asset
+    /// manager.
+    fn index<T>(&self, handle: Handle<T>) -> Option<usize> {
+        if handle.manager == self.id {
+            Some(handle.index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether [`reload_changed`][Self::reload_changed] reloads assets whose files
+    /// have changed.
+    pub fn is_watching(&self) -> bool {
+        self.watching
+    }
+
+    /// Choose whether [`reload_changed`][Self::reload_changed] reloads assets whose files have
+    /// changed. This is off by default, as checking every file takes time.
+    pub fn watch(&mut self, watching: bool) {
+        self.watching = watching;
+    }
+
+    /// If [watching][Self::watch], load every asset again if its file has changed since it was
+    /// last loaded. Maps are also reloaded when one of their external tilesets changes.
+    /// Reloaded assets keep their handles, so everything using them sees the new version
+    /// straight away, apart from copies such as fonts given to
+    /// [`Canvas::set_font`][crate::canvas::Canvas::set_font] and sounds that are already
+    /// playing. Returns the paths of the reloaded assets, or a `String` describing every asset
+    /// that failed to reload, which are kept as they were until their files change again.
+    pub fn reload_changed(&mut self) -> Result<Vec<PathBuf>, String> {
+        let (mut reloaded, mut errors) = (Vec::new(), Vec::new());
+        if !self.watching {
+            return Ok(reloaded);
+        }
+        let creator = &self.texture_creator;
+        self.textures.reload_changed(
+            &self.source,
+            |path, data, _| load_texture(creator, path, &data),
+            &mut reloaded,
+            &mut errors,
+        );
+        self.fonts
+            .reload_changed(&self.source, load_font, &mut reloaded, &mut errors);
+        self.sounds
+            .reload_changed(&self.source, load_sound, &mut reloaded, &mut errors);
+        #[cfg(feature = "tiled")]
+        self.maps
+            .reload_changed(&self.source, load_map, &mut reloaded, &mut errors);
+        if errors.is_empty() {
+            Ok(reloaded)
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+/// Returns the extension of a file, in lower case.
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+/// Create a texture from the contents of an image file.
+fn load_texture<U>(
+    creator: &TextureCreator<U>,
+    path: &Path,
+    data: &[u8],
+) -> Result<LayerTexture, String> {
+    let surface = match extension(path).as_deref() {
+        Some("png") => load_png(data)?,
+        _ => Surface::load_bmp_rw(&mut RWops::from_bytes(data)?)?,
+    };
+    let texture = creator
+        .create_texture_from_surface(&surface)
+        .map_err(|e| e.to_string())?;
+    Ok(LayerTexture::new(texture))
+}
+
+/// Decode a PNG file into a surface.
+#[cfg(feature = "png")]
+fn load_png(data: &[u8]) -> Result<Surface<'static>, String> {
+    let mut image = crate::canvas::Image::from_png(data)?;
+    let surface = image
+        .as_surface()?
+        .convert_format(sdl2::pixels::PixelFormatEnum::RGBA32)?;
+    Ok(surface)
+}
+
+#[cfg(not(feature = "png"))]
+fn load_png(_data: &[u8]) -> Result<Surface<'static>, String> {
+    Err("Loading PNG images needs the \"png\" feature".to_string())
+}
+
+/// Load a font from the contents of a font file.
+fn load_font(path: &Path, data: Vec<u8>, _files: &mut Files) -> Result<Font, String> {
+    match extension(path).as_deref() {
+        Some("bdf") => Font::from_bdf(&String::from_utf8(data).map_err(|e| e.to_string())?),
+        Some("psf") => Font::from_psf(&data),
+        #[cfg(feature = "ttf")]
+        Some("ttf") | Some("otf") => Font::from_bytes(data, DEFAULT_FONT_SIZE),
+        _ => Err("Unsupported font format".to_string()),
+    }
+}
+
+/// Decode a sound from the contents of a sound file.
+fn load_sound(path: &Path, data: Vec<u8>, _files: &mut Files) -> Result<Sound, String> {
+    match extension(path).as_deref() {
+        Some("wav") => Sound::from_wav(&data),
+        #[cfg(feature = "ogg")]
+        Some("ogg") => Sound::from_ogg(&data),
+        _ => Err("Unsupported sound format".to_string()),
+    }
+}
+
+/// Parse a map from the contents of a Tiled map file, reading its external tilesets from
+/// `files`.
+#[cfg(feature = "tiled")]
+fn load_map(path: &Path, data: Vec<u8>, files: &mut Files) -> Result<Tilemap, String> {
+    let contents = String::from_utf8(data).map_err(|e| e.to_string())?;
+    let read = &mut |path: &Path| files.read_to_string(path);
+    match extension(path).as_deref() {
+        Some("tmx") => Tilemap::from_tmx_with(&contents, path.parent(), read),
+        _ => Tilemap::from_tiled_json_with(&contents, path.parent(), read),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{self, File},
+        time::{Duration, SystemTime},
+    };
+
+    use sdl2::pixels::{Color, PixelFormatEnum};
+
+    use super::*;
+    use crate::{
+        canvas::{Image, SurfaceCanvas},
+        test_util::test_dir,
+    };
+
+    /// A BDF font with just a space, 6 pixels high.
+    const BDF: &str = "STARTFONT 2.1
+FONTBOUNDINGBOX 1 6 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 5
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 1
+STARTCHAR space
+ENCODING 32
+DWIDTH 3 0
+BBX 1 1 0 0
+BITMAP
+00
+ENDCHAR
+ENDFONT
+";
+
+    /// Returns the contents of a mono 8 kHz 16 bit `.wav` file holding `samples`.
+    fn wav_file(samples: &[i16]) -> Vec<u8> {
+        let data_len = samples.len() as u32 * 2;
+        let mut wav = b"RIFF".to_vec();
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        // PCM, 1 channel, 8000 frames and 16000 bytes per second, 2 byte frames, 16 bits
+        wav.extend_from_slice(
+            b"WAVEfmt \x10\0\0\0\x01\0\x01\0\x40\x1f\0\0\x80\x3e\0\0\x02\0\x10\0",
+        );
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+
+    fn canvas() -> SurfaceCanvas<'static> {
+        let surface = Surface::new(8, 8, PixelFormatEnum::RGBA8888).unwrap();
+        SurfaceCanvas::new(surface.into_canvas().unwrap())
+    }
+
+    /// Mark a file as changed `seconds` in the future, so it looks changed however coarse the
+    /// file system's timestamps are.
+    fn touch(path: &Path, seconds: u64) {
+        let file = File::options().write(true).open(path).unwrap();
+        let time = SystemTime::now() + Duration::from_secs(seconds);
+        file.set_modified(time).unwrap();
+    }
+
+    /// Test loading each kind of asset, only loading each file once, and reporting errors.
+    #[test]
+    fn test_load() {
+        let dir = test_dir("assets-load");
+        let image = Image {
+            width: 3,
+            height: 2,
+            pixels: [255, 0, 0, 255].repeat(6),
+        };
+        image.save_bmp(dir.join("red.bmp")).unwrap();
+        fs::write(dir.join("font.bdf"), BDF).unwrap();
+        fs::write(dir.join("beep.wav"), wav_file(&[0, 16384])).unwrap();
+        fs::write(dir.join("bad.wav"), "not a wav file").unwrap();
+
+        let mut canvas = canvas();
+        let mut assets = Assets::new((*canvas).texture_creator());
+        let red = assets.load_texture(dir.join("red.bmp")).unwrap();
+        let same = assets.load_texture(dir.join(".").join("red.bmp")).unwrap();
+        assert_eq!(red, same);
+        assert_eq!(
+            assets.texture_path(red),
+            Some(dir.join("red.bmp").as_path())
+        );
+        let texture = assets.texture(red).unwrap();
+        assert_eq!((texture.query().width, texture.query().height), (3, 2));
+        canvas.copy(&texture, None, None).unwrap();
+        assert_eq!(canvas.get_pixel((4, 4)), Ok(Color::RED));
+
+        #[cfg(feature = "png")]
+        {
+            image.save_png(dir.join("red.png")).unwrap();
+            let png = assets.load_texture(dir.join("red.png")).unwrap();
+            assert_ne!(png, red);
+            assert_eq!(assets.texture(png).unwrap().query().width, 3);
+        }
+
+        let font = assets.load_font(dir.join("font.bdf")).unwrap();
+        assert_eq!(assets.font(font).map(Font::line_height), Some(6));
+        assets.font_mut(font).unwrap().set_size(12.0);
+        assert_eq!(assets.font(font).map(Font::line_height), Some(12));
+        let beep = assets.load_sound(dir.join("beep.wav")).unwrap();
+        assert_eq!(assets.sound(beep).unwrap().samples(), &[0.0, 0.5]);
+        let other = Assets::new((*canvas).texture_creator());
+        assert!(other.sound(beep).is_none());
+        assert!(other.texture(red).is_none());
+
+        let error = assets.load_sound(dir.join("bad.wav")).unwrap_err();
+        assert!(error.starts_with(&format!("{}: ", dir.join("bad.wav").display())));
+        let error = assets.load_font(dir.join("missing.bdf")).unwrap_err();
+        assert!(error.starts_with(&format!("{}: ", dir.join("missing.bdf").display())));
+        let error = assets.load_font(dir.join("beep.wav")).unwrap_err();
+        assert!(error.ends_with("Unsupported font format"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Test loading assets relative to a directory, and from a bundle of that directory.
+    #[test]
+    fn test_sources() {
+        let dir = test_dir("assets-sources");
+        fs::create_dir(dir.join("sounds")).unwrap();
+        fs::write(dir.join("sounds/beep.wav"), wav_file(&[0, 16384])).unwrap();
+        #[cfg(feature = "tiled")]
+        write_map(&dir);
+        let canvas = canvas();
+
+        let mut assets = Assets::new((*canvas).texture_creator()).with_directory(&dir);
+        let beep = assets.load_sound("sounds/beep.wav").unwrap();
+        assert_eq!(assets.sound(beep).unwrap().samples(), &[0.0, 0.5]);
+        assert_eq!(assets.sound_path(beep), Some(Path::new("sounds/beep.wav")));
+        let error = assets.load_sound("missing.wav").unwrap_err();
+        assert!(error.starts_with(&format!("{}: ", dir.join("missing.wav").display())));
+
+        let bundle = Bundle::from_vec(Bundle::pack_dir(&dir).unwrap()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let mut assets = Assets::new((*canvas).texture_creator()).with_bundle(bundle);
+        assets.watch(true);
+        let beep = assets.load_sound("sounds/beep.wav").unwrap();
+        assert_eq!(assets.sound(beep).unwrap().samples(), &[0.0, 0.5]);
+        let same = assets.load_sound("./sounds/../sounds/beep.wav").unwrap();
+        assert_eq!(beep, same);
+        assert_eq!(assets.reload_changed(), Ok(Vec::new()));
+        assert_eq!(
+            assets.load_sound("missing.wav"),
+            Err("missing.wav: Not found in the bundle".to_string())
+        );
+        #[cfg(feature = "tiled")]
+        {
+            // The external tileset comes from the bundle too
+            let map = assets.load_map("maps/level.tmj").unwrap();
+            let tileset = &assets.map(map).unwrap().tilesets[0];
+            assert_eq!((tileset.first_gid, tileset.tile_count), (1, 4));
+        }
+    }
+
+    /// Write a map with an external tileset to `dir`.
+    #[cfg(feature = "tiled")]
+    fn write_map(dir: &Path) {
+        fs::create_dir_all(dir.join("maps")).unwrap();
+        fs::create_dir_all(dir.join("tiles")).unwrap();
+        fs::write(
+            dir.join("maps/level.tmj"),
+            r#"{"width": 1, "height": 1, "tilewidth": 8, "tileheight": 8,
+                "tilesets": [{"firstgid": 1, "source": "../tiles/terrain.tsj"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("tiles/terrain.tsj"),
+            r#"{"tilewidth": 8, "tileheight": 8, "tilecount": 4, "columns": 2}"#,
+        )
+        .unwrap();
+    }
+
+    /// Test reloading assets whose files have changed.
+    #[test]
+    fn test_reload() {
+        let dir = test_dir("assets-reload");
+        let path = dir.join("beep.wav");
+        fs::write(&path, wav_file(&[0, 16384])).unwrap();
+        let canvas = canvas();
+        let mut assets = Assets::new((*canvas).texture_creator());
+        let beep = assets.load_sound(&path).unwrap();
+
+        fs::write(&path, wav_file(&[16384])).unwrap();
+        touch(&path, 10);
+        assert_eq!(assets.reload_changed(), Ok(Vec::new()));
+        assets.watch(true);
+        assert!(assets.is_watching());
+        assert_eq!(assets.reload_changed(), Ok(vec![path.clone()]));
+        assert_eq!(assets.sound(beep).unwrap().samples(), &[0.5]);
+        assert_eq!(assets.reload_changed(), Ok(Vec::new()));
+
+        // A broken file keeps the old asset
+        fs::write(&path, "not a wav file").unwrap();
+        touch(&path, 20);
+        let error = assets.reload_changed().unwrap_err();
+        assert!(error.starts_with(&format!("{}: ", path.display())));
+        assert_eq!(assets.sound(beep).unwrap().samples(), &[0.5]);
+        assert_eq!(assets.reload_changed(), Ok(Vec::new()));
+
+        // Maps are reloaded when their tilesets change
+        #[cfg(feature = "tiled")]
+        {
+            write_map(&dir);
+            let map = assets.load_map(dir.join("maps/level.tmj")).unwrap();
+            let tileset = dir.join("tiles/terrain.tsj");
+            fs::write(
+                &tileset,
+                r#"{"tilewidth": 8, "tileheight": 8, "tilecount": 8, "columns": 2}"#,
+            )
+            .unwrap();
+            touch(&tileset, 30);
+            assert_eq!(
+                assets.reload_changed(),
+                Ok(vec![dir.join("maps/level.tmj")])
+            );
+            assert_eq!(assets.map(map).unwrap().tilesets[0].tile_count, 8);
+            assert_eq!(assets.reload_changed(), Ok(Vec::new()));
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
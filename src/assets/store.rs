@@ -0,0 +1,217 @@
+//! Loaded assets of one kind, keyed by the files they were loaded from, and where those files
+//! are read from.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use fnv::FnvHashMap;
+
+use super::{bundle, Bundle};
+
+/// Where asset files are read from.
+pub(super) enum Source {
+    /// Paths are relative to a directory.
+    Directory(PathBuf),
+    /// Paths are within a bundle.
+    Bundle(Bundle),
+}
+
+impl Source {
+    /// Returns where the file at `path` is, for error messages and for finding the files it
+    /// refers to, such as a map's tilesets.
+    fn location(&self, path: &Path) -> PathBuf {
+        match self {
+            Self::Directory(dir) => dir.join(path),
+            Self::Bundle(_) => path.to_path_buf(),
+        }
+    }
+
+    /// Returns the same key for every path to the same file.
+    fn key(&self, path: &Path) -> PathBuf {
+        let location = self.location(path);
+        match self {
+            Self::Directory(_) => fs::canonicalize(&location).unwrap_or(location),
+            Self::Bundle(_) => bundle::name(path).map_or(location, PathBuf::from),
+        }
+    }
+
+    /// Returns the contents of the file at `path`.
+    fn read(&self, path: &Path) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Directory(dir) => fs::read(dir.join(path)).map_err(|e| e.to_string()),
+            Self::Bundle(bundle) => bundle
+                .get(path)
+                .map(<[u8]>::to_vec)
+                .ok_or_else(|| "Not found in the bundle".to_string()),
+        }
+    }
+
+    /// Returns when the file at `path` was last changed, or `None` if that can't be found out.
+    /// Files in bundles never change.
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        match self {
+            Self::Directory(dir) => fs::metadata(dir.join(path))
+                .and_then(|meta| meta.modified())
+                .ok(),
+            Self::Bundle(_) => None,
+        }
+    }
+
+    /// Read a file, and load an asset from its contents with `load`, which is given the file's
+    /// path and can read the files it refers to from the same source. Returns the asset and
+    /// the other files it was loaded from, or a `String` describing the error, starting with
+    /// where the file is, if either fails.
+    fn load<T, F>(&self, path: &Path, load: F) -> Result<(T, Vec<Dependency>), String>
+    where
+        F: FnOnce(&Path, Vec<u8>, &mut Files) -> Result<T, String>,
+    {
+        let mut files = Files {
+            source: self,
+            read: Vec::new(),
+        };
+        self.read(path)
+            .and_then(|data| load(path, data, &mut files))
+            .map(|asset| (asset, files.read))
+            .map_err(|e| format!("{}: {}", self.location(path).display(), e))
+    }
+}
+
+/// Another file an asset was loaded from, and when it was last changed as of then.
+type Dependency = (PathBuf, Option<SystemTime>);
+
+/// Reads the other files an asset refers to, such as a map's external tilesets, from the same
+/// source as the asset, and remembers them so the asset is reloaded when they change.
+pub(super) struct Files<'a> {
+    source: &'a Source,
+    read: Vec<Dependency>,
+}
+
+impl Files<'_> {
+    /// Returns the contents of the text file at `path`. Returns a `String` describing the error,
+    /// starting with where the file is, if it can't be read.
+    #[cfg_attr(not(feature = "tiled"), allow(dead_code))]
+    pub(super) fn read_to_string(&mut self, path: &Path) -> Result<String, String> {
+        self.read
+            .push((path.to_path_buf(), self.source.modified(path)));
+        self.source
+            .read(path)
+            .and_then(|data| String::from_utf8(data).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", self.source.location(path).display(), e))
+    }
+}
+
+/// An asset, and the file it was loaded from.
+struct Entry<T> {
+    path: PathBuf,
+    asset: T,
+    /// When the file was last changed, as of the last time it was loaded.
+    modified: Option<SystemTime>,
+    /// The other files the asset was loaded from, such as a map's tilesets.
+    dependencies: Vec<Dependency>,
+}
+
+impl<T> Entry<T> {
+    /// Returns whether the asset's file, or any of the other files it was loaded from, has
+    /// changed since it was last loaded, remembering the new times so it isn't reported again.
+    fn changed(&mut self, source: &Source) -> bool {
+        let mut changed = false;
+        let files = std::iter::once((&self.path, &mut self.modified)).chain(
+            self.dependencies
+                .iter_mut()
+                .map(|(path, modified)| (&*path, modified)),
+        );
+        for (path, modified) in files {
+            let now = source.modified(path);
+            if now != *modified {
+                *modified = now;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Loaded assets of one kind. Each file is only loaded once, however many times it's asked for.
+pub(super) struct Store<T> {
+    entries: Vec<Entry<T>>,
+    /// The index of the asset loaded from each file, keyed by its canonical path, so the same
+    /// file reached by different paths is still only loaded once.
+    indices: FnvHashMap<PathBuf, usize>,
+}
+
+impl<T> Store<T> {
+    pub(super) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            indices: FnvHashMap::default(),
+        }
+    }
+
+    /// Returns the index of the asset loaded from `path` in `source`, loading it from the
+    /// file's contents with `load` if it hasn't been loaded yet. Returns a `String` describing
+    /// the error, starting with where the file is, if it can't be read or loaded.
+    pub(super) fn load<F>(&mut self, source: &Source, path: &Path, load: F) -> Result<usize, String>
+    where
+        F: FnOnce(&Path, Vec<u8>, &mut Files) -> Result<T, String>,
+    {
+        let key = source.key(path);
+        if let Some(&index) = self.indices.get(&key) {
+            return Ok(index);
+        }
+        let modified = source.modified(path);
+        let (asset, dependencies) = source.load(path, load)?;
+        self.entries.push(Entry {
+            path: path.to_path_buf(),
+            asset,
+            modified,
+            dependencies,
+        });
+        self.indices.insert(key, self.entries.len() - 1);
+        Ok(self.entries.len() - 1)
+    }
+
+    pub(super) fn get(&self, index: usize) -> Option<&T> {
+        self.entries.get(index).map(|entry| &entry.asset)
+    }
+
+    pub(super) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.entries.get_mut(index).map(|entry| &mut entry.asset)
+    }
+
+    /// Returns the path an asset was loaded from.
+    pub(super) fn path(&self, index: usize) -> Option<&Path> {
+        self.entries.get(index).map(|entry| entry.path.as_path())
+    }
+
+    /// Load every asset again if its file in `source`, or any of the other files it was loaded
+    /// from, has changed since it was last loaded. The new asset replaces the old one, so its
+    /// handle stays the same. The path of each reloaded asset is added to `reloaded`, and a
+    /// description of each failure to `errors`. Assets that fail to reload are kept as they
+    /// were, and aren't tried again until their file changes again.
+    pub(super) fn reload_changed<F>(
+        &mut self,
+        source: &Source,
+        mut load: F,
+        reloaded: &mut Vec<PathBuf>,
+        errors: &mut Vec<String>,
+    ) where
+        F: FnMut(&Path, Vec<u8>, &mut Files) -> Result<T, String>,
+    {
+        for entry in &mut self.entries {
+            if !entry.changed(source) {
+                continue;
+            }
+            match source.load(&entry.path, &mut load) {
+                Ok((asset, dependencies)) => {
+                    entry.asset = asset;
+                    entry.dependencies = dependencies;
+                    reloaded.push(entry.path.clone());
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Contains the [`SpriteBatch`] type, which queues up images from a texture atlas to be drawn
+//! together.
+
+use sdl2::rect::{Point, Rect};
+
+use super::AtlasRegion;
+
+/// A single image queued in a [`SpriteBatch`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Quad {
+    pub page: usize,
+    pub src: Rect,
+    pub dst: Rect,
+    pub angle: f64,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+}
+
+/// A list of atlas images to be drawn with
+/// [`Canvas::draw_batch`][crate::canvas::Canvas::draw_batch].
+///
+/// Images are drawn in the order they were pushed, so later images are drawn over earlier ones.
+/// Consecutive upright images on the same atlas page are drawn in a single call, so a batch is
+/// cheapest when the images drawn together were packed onto the same page.
+#[derive(Clone, Debug, Default)]
+pub struct SpriteBatch {
+    pub(crate) quads: Vec<Quad>,
+}
+
+impl SpriteBatch {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue an image to be drawn at its natural size, with its top-left corner at `pos`.
+    pub fn push<P: Into<Point>>(&mut self, region: AtlasRegion, pos: P) {
+        let pos = pos.into();
+        let dst = Rect::new(pos.x(), pos.y(), region.rect.width(), region.rect.height());
+        self.push_ex(region, dst, 0.0, false, false);
+    }
+
+    /// Queue an image to be stretched to fill `dst`, rotated clockwise by `angle` degrees around
+    /// its center, and optionally flipped.
+    pub fn push_ex<R: Into<Rect>>(
+        &mut self,
+        region: AtlasRegion,
+        dst: R,
+        angle: f64,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+    ) {
+        self.quads.push(Quad {
+            page: region.page,
+            src: region.rect,
+            dst: dst.into(),
+            angle,
+            flip_horizontal,
+            flip_vertical,
+        });
+    }
+
+    /// Remove every queued image, keeping the allocated memory for the next frame.
+    pub fn clear(&mut self) {
+        self.quads.clear();
+    }
+
+    /// Returns the number of queued images.
+    pub fn len(&self) -> usize {
+        self.quads.len()
+    }
+
+    /// Returns `true` if no images are queued.
+    pub fn is_empty(&self) -> bool {
+        self.quads.is_empty()
+    }
+}
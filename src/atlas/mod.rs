@@ -0,0 +1,194 @@
+//! Runtime texture atlases, for drawing lots of small images without switching textures.
+//!
+//! Use an [`AtlasBuilder`] at load time to pack many surfaces into a few large textures, then
+//! queue up sprites in a [`SpriteBatch`] each frame and draw them all together with
+//! [`Canvas::draw_batch`][crate::canvas::Canvas::draw_batch].
+//! # Example
+//! ```no_run
+//! use simple_game_engine::{
+//!     atlas::{AtlasBuilder, SpriteBatch},
+//!     prelude::*,
+//! };
+//! use sdl2::surface::Surface;
+//! # fn draw(canvas: &mut WindowCanvas) -> Result<(), String> {
+//! // A texture creator that doesn't borrow the canvas, so we can still draw to it
+//! let texture_creator = (**canvas).texture_creator();
+//! let mut builder = AtlasBuilder::new(1024, 1024);
+//! builder.add("player", Surface::load_bmp("player.bmp")?);
+//! builder.add("coin", Surface::load_bmp("coin.bmp")?);
+//! let atlas = builder.build(&texture_creator)?;
+//!
+//! let coin = atlas.region("coin").unwrap();
+//! let mut batch = SpriteBatch::new();
+//! for x in 0..100 {
+//!     batch.push(coin, (x * 8, 16));
+//! }
+//! canvas.draw_batch(&atlas, &batch)?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod batch;
+pub(crate) use batch::Quad;
+pub use batch::SpriteBatch;
+mod packer;
+pub use packer::SkylinePacker;
+
+use fnv::FnvHashMap;
+use sdl2::{
+    pixels::PixelFormatEnum,
+    rect::Rect,
+    render::{BlendMode, Texture, TextureCreator},
+    surface::Surface,
+};
+
+/// The location of a single image within a [`TextureAtlas`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasRegion {
+    /// Index of the atlas texture containing the image.
+    pub page: usize,
+    /// The area of the page containing the image.
+    pub rect: Rect,
+}
+
+/// A set of large textures ("pages"), each containing many smaller images.
+pub struct TextureAtlas<'a> {
+    pages: Vec<Texture<'a>>,
+    regions: FnvHashMap<String, AtlasRegion>,
+}
+
+impl<'a> TextureAtlas<'a> {
+    /// Look up where an image was packed.
+    pub fn region(&self, name: &str) -> Option<AtlasRegion> {
+        self.regions.get(name).copied()
+    }
+
+    /// Returns the texture for a page of the atlas.
+    pub fn page(&self, index: usize) -> Option<&Texture<'a>> {
+        self.pages.get(index)
+    }
+
+    /// Returns all pages of the atlas.
+    pub fn pages(&self) -> &[Texture<'a>] {
+        &self.pages
+    }
+
+    /// Returns an iterator over the names and regions of every image in the atlas.
+    pub fn regions(&self) -> impl Iterator<Item = (&str, AtlasRegion)> {
+        self.regions
+            .iter()
+            .map(|(name, region)| (name.as_str(), *region))
+    }
+}
+
+/// Collects surfaces and packs them into a [`TextureAtlas`].
+pub struct AtlasBuilder<'s> {
+    page_width: u32,
+    page_height: u32,
+    padding: u32,
+    surfaces: Vec<(String, Surface<'s>)>,
+}
+
+impl<'s> AtlasBuilder<'s> {
+    /// Create a builder whose pages will be `page_width` by `page_height` pixels. Most renderers
+    /// support textures of at least 2048 by 2048 pixels; check
+    /// [`RendererInfo`][sdl2::render::RendererInfo] for the actual limit.
+    pub fn new(page_width: u32, page_height: u32) -> Self {
+        Self {
+            page_width,
+            page_height,
+            padding: 1,
+            surfaces: Vec::new(),
+        }
+    }
+
+    /// Set the number of transparent pixels left between images (1 by default). This stops
+    /// neighbouring images bleeding into each other when scaled.
+    pub fn padding(&mut self, padding: u32) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Queue a surface to be packed into the atlas under the given name.
+    pub fn add<S: Into<String>>(&mut self, name: S, surface: Surface<'s>) -> &mut Self {
+        self.surfaces.push((name.into(), surface));
+        self
+    }
+
+    /// Work out where each queued surface will be placed, without creating any textures.
+    /// Returns a `String` describing the problem if a surface is too large for a page.
+    pub fn pack(&self) -> Result<Vec<AtlasRegion>, String> {
+        // Placing tall images first packs far more tightly
+        let mut order: Vec<_> = (0..self.surfaces.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.surfaces[i].1.height()));
+        let mut packers: Vec<SkylinePacker> = Vec::new();
+        let mut regions = vec![None; self.surfaces.len()];
+        for i in order {
+            let (name, surface) = &self.surfaces[i];
+            let width = surface.width() + self.padding;
+            let height = surface.height() + self.padding;
+            let mut region = None;
+            for (page, packer) in packers.iter_mut().enumerate() {
+                if let Some(rect) = packer.insert(width, height) {
+                    region = Some((page, rect));
+                    break;
+                }
+            }
+            if region.is_none() {
+                let mut packer = SkylinePacker::new(self.page_width, self.page_height);
+                let rect = packer.insert(width, height).ok_or_else(|| {
+                    format!(
+                        "Image {:?} ({}x{}) doesn't fit on a {}x{} atlas page",
+                        name,
+                        surface.width(),
+                        surface.height(),
+                        self.page_width,
+                        self.page_height
+                    )
+                })?;
+                region = Some((packers.len(), rect));
+                packers.push(packer);
+            }
+            let (page, rect) = region.unwrap();
+            regions[i] = Some(AtlasRegion {
+                page,
+                rect: Rect::new(rect.x(), rect.y(), surface.width(), surface.height()),
+            });
+        }
+        Ok(regions.into_iter().map(Option::unwrap).collect())
+    }
+
+    /// Pack the queued surfaces and upload the resulting pages as textures. Returns a `String`
+    /// describing the problem if a surface is too large for a page, or if sdl fails.
+    pub fn build<U>(
+        mut self,
+        texture_creator: &TextureCreator<U>,
+    ) -> Result<TextureAtlas<'_>, String> {
+        let regions = self.pack()?;
+        let page_count = regions.iter().map(|r| r.page + 1).max().unwrap_or(0);
+        let mut pages = (0..page_count)
+            .map(|_| Surface::new(self.page_width, self.page_height, PixelFormatEnum::RGBA8888))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut names = FnvHashMap::default();
+        for ((name, surface), region) in self.surfaces.iter_mut().zip(&regions) {
+            // Copy the pixels as is, rather than blending them onto the empty page
+            surface.set_blend_mode(BlendMode::None)?;
+            surface.blit(None, &mut pages[region.page], region.rect)?;
+            names.insert(std::mem::take(name), *region);
+        }
+        let pages = pages
+            .iter()
+            .map(|page| {
+                let mut texture = texture_creator
+                    .create_texture_from_surface(page)
+                    .map_err(|e| e.to_string())?;
+                texture.set_blend_mode(BlendMode::Blend);
+                Ok(texture)
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(TextureAtlas {
+            pages,
+            regions: names,
+        })
+    }
+}
@@ -0,0 +1,159 @@
+//! Contains the [`SkylinePacker`] type, which packs rectangles into a fixed-size area.
+
+use sdl2::rect::Rect;
+
+/// A horizontal segment of the skyline.
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+/// Packs rectangles into a fixed-size area using the skyline bottom-left heuristic. The packer
+/// tracks the top edge (the "skyline") of everything placed so far, and puts each new rectangle
+/// as low as possible, preferring the left-most position on a tie.
+#[derive(Clone, Debug)]
+pub struct SkylinePacker {
+    width: u32,
+    height: u32,
+    skyline: Vec<Segment>,
+}
+
+impl SkylinePacker {
+    /// Create an empty packer for an area of the given size.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            skyline: vec![Segment { x: 0, y: 0, width }],
+        }
+    }
+
+    /// Find room for a `width` by `height` rectangle and reserve it. Returns the position of the
+    /// rectangle, or `None` if there's no room left.
+    /// # Example
+    /// ```
+    /// use simple_game_engine::atlas::SkylinePacker;
+    ///
+    /// let mut packer = SkylinePacker::new(32, 32);
+    /// assert!(packer.insert(32, 16).is_some());
+    /// assert!(packer.insert(16, 16).is_some());
+    /// assert!(packer.insert(32, 16).is_none());
+    /// ```
+    pub fn insert(&mut self, width: u32, height: u32) -> Option<Rect> {
+        if width == 0 || height == 0 {
+            return None;
+        }
+        // Find the segment to start at which gives the lowest top edge
+        let (index, y) = (0..self.skyline.len())
+            .filter_map(|i| self.fit(i, width, height).map(|y| (i, y)))
+            .min_by_key(|&(i, y)| (y + height, self.skyline[i].x))?;
+        let x = self.skyline[index].x;
+        self.add_level(index, x, y + height, width);
+        Some(Rect::new(x as i32, y as i32, width, height))
+    }
+
+    /// Returns the lowest *y* coordinate a rectangle can be placed at, starting at segment
+    /// `index`, or `None` if it won't fit there.
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[index].x;
+        if x + width > self.width {
+            return None;
+        }
+        let mut y = 0;
+        let mut width_left = width;
+        for segment in &self.skyline[index..] {
+            y = y.max(segment.y);
+            if y + height > self.height {
+                return None;
+            }
+            width_left = width_left.saturating_sub(segment.width);
+            if width_left == 0 {
+                return Some(y);
+            }
+        }
+        None
+    }
+
+    /// Raise the skyline to `y` between `x` and `x + width`.
+    fn add_level(&mut self, index: usize, x: u32, y: u32, width: u32) {
+        self.skyline.insert(index, Segment { x, y, width });
+        // Trim or remove the segments now hidden under the new one
+        let right = x + width;
+        while let Some(next) = self.skyline.get_mut(index + 1) {
+            if next.x >= right {
+                break;
+            }
+            let overlap = right - next.x;
+            if overlap >= next.width {
+                self.skyline.remove(index + 1);
+            } else {
+                next.x += overlap;
+                next.width -= overlap;
+                break;
+            }
+        }
+        // Merge neighbouring segments at the same height
+        let mut i = 0;
+        while i + 1 < self.skyline.len() {
+            if self.skyline[i].y == self.skyline[i + 1].y {
+                self.skyline[i].width += self.skyline[i + 1].width;
+                self.skyline.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Returns the width of the packing area.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the packing area.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test packed rectangles stay in bounds and never overlap.
+    #[test]
+    fn test_no_overlap() {
+        let mut packer = SkylinePacker::new(64, 64);
+        let sizes = [
+            (20, 10),
+            (8, 30),
+            (30, 8),
+            (16, 16),
+            (10, 10),
+            (12, 5),
+            (5, 12),
+        ];
+        let mut placed: Vec<Rect> = Vec::new();
+        for &(w, h) in sizes.iter().cycle().take(20) {
+            if let Some(rect) = packer.insert(w, h) {
+                assert!(rect.right() <= 64 && rect.bottom() <= 64);
+                assert!(placed.iter().all(|other| !rect.has_intersection(*other)));
+                placed.push(rect);
+            }
+        }
+        assert!(placed.len() > 10);
+    }
+
+    /// Test the packer fills the bottom-left-most gap first.
+    #[test]
+    fn test_fill_gap() {
+        let mut packer = SkylinePacker::new(32, 32);
+        assert_eq!(packer.insert(16, 8), Some(Rect::new(0, 0, 16, 8)));
+        assert_eq!(packer.insert(8, 16), Some(Rect::new(16, 0, 8, 16)));
+        assert_eq!(packer.insert(8, 4), Some(Rect::new(24, 0, 8, 4)));
+        assert_eq!(packer.insert(16, 8), Some(Rect::new(0, 8, 16, 8)));
+        assert_eq!(packer.insert(0, 8), None);
+        assert_eq!(packer.insert(33, 1), None);
+    }
+}
@@ -0,0 +1,108 @@
+//! The software mixer that sounds and music are played on.
+
+use super::{music::MusicStream, PlayOptions, Sound, Voice};
+
+/// A sound being played.
+struct Playing {
+    voice: Voice,
+    sound: Sound,
+    options: PlayOptions,
+    /// Position of the next frame to play, in frames of the sound.
+    position: f64,
+}
+
+impl Playing {
+    /// Add the next stereo frames of the sound to `out`, resampled to `sample_rate`. Returns
+    /// `false` once the sound has finished.
+    fn mix(&mut self, out: &mut [f32], sample_rate: u32) -> bool {
+        let frames = self.sound.frames() as f64;
+        let step = self.options.pitch.max(0.0) as f64 * self.sound.sample_rate() as f64
+            / sample_rate as f64;
+        let pan = self.options.pan.clamp(-1.0, 1.0);
+        let left = self.options.volume * (1.0 - pan).min(1.0);
+        let right = self.options.volume * (1.0 + pan).min(1.0);
+        for frame in out.chunks_exact_mut(2) {
+            if self.position >= frames {
+                if !self.options.looping || frames == 0.0 {
+                    return false;
+                }
+                self.position %= frames;
+            }
+            let samples = self.sound.frame_at(self.position, self.options.looping);
+            frame[0] += samples.0 * left;
+            frame[1] += samples.1 * right;
+            self.position += step;
+        }
+        self.options.looping || self.position < frames
+    }
+}
+
+/// Mixes every sound being played, and the music, into one stereo stream.
+pub(super) struct Mixer {
+    pub(super) sample_rate: u32,
+    pub(super) master_volume: f32,
+    pub(super) music_volume: f32,
+    pub(super) music: Option<MusicStream>,
+    playing: Vec<Playing>,
+    next_voice: u64,
+}
+
+impl Mixer {
+    pub(super) fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            music: None,
+            playing: Vec::new(),
+            next_voice: 0,
+        }
+    }
+
+    /// Start playing `sound`, returning the voice playing it.
+    pub(super) fn play(&mut self, sound: &Sound, options: PlayOptions) -> Voice {
+        let voice = Voice(self.next_voice);
+        self.next_voice += 1;
+        self.playing.push(Playing {
+            voice,
+            sound: sound.clone(),
+            options,
+            position: 0.0,
+        });
+        voice
+    }
+
+    /// Returns the options of a voice that's still playing.
+    pub(super) fn options_mut(&mut self, voice: Voice) -> Option<&mut PlayOptions> {
+        self.playing
+            .iter_mut()
+            .find(|playing| playing.voice == voice)
+            .map(|playing| &mut playing.options)
+    }
+
+    /// Stop playing a voice.
+    pub(super) fn stop(&mut self, voice: Voice) {
+        self.playing.retain(|playing| playing.voice != voice);
+    }
+
+    /// Stop playing every sound, but not the music.
+    pub(super) fn stop_all(&mut self) {
+        self.playing.clear();
+    }
+
+    /// Fill `out` with the next interleaved stereo samples.
+    pub(super) fn mix(&mut self, out: &mut [f32]) {
+        out.iter_mut().for_each(|sample| *sample = 0.0);
+        let sample_rate = self.sample_rate;
+        self.playing
+            .retain_mut(|playing| playing.mix(out, sample_rate));
+        if let Some(music) = &mut self.music {
+            if !music.mix(out, sample_rate, self.music_volume) {
+                self.music = None;
+            }
+        }
+        for sample in out {
+            *sample = (*sample * self.master_volume).clamp(-1.0, 1.0);
+        }
+    }
+}
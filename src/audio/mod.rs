@@ -0,0 +1,489 @@
+//! Playing sound effects and music on a software mixer.
+//!
+//! An [`Audio`] handle mixes any number of [`Sound`]s, each played on its own [`Voice`] with
+//! its own volume, pan, pitch and looping, together with one streamed [`Music`] track. The
+//! engine passes its handle to the application's callbacks, and plays it through the default
+//! audio device while it runs if [`Engine::play_audio`][crate::Engine::play_audio] is set.
+//!
+//! Sounds can be loaded from files, or generated without any with [`Oscillator`]s,
+//! [`Envelope`]s, and retro [`SoundEffect`]s.
+//! # Example
+//! ```no_run
+//! use simple_game_engine::{self as sge, audio::{Music, Sound}, prelude::*};
+//!
+//! struct App {
+//!     jump: Sound,
+//! }
+//!
+//! impl sge::Application for App {
+//!     fn on_update(
+//!         &mut self,
+//!         canvas: &mut WindowCanvas,
+//!         input: &InputState,
+//!         audio: &Audio,
+//!         elapsed_time: f64,
+//!     ) -> sge::ApplicationResult {
+//!         if input.keyboard.pressed(Scancode::Space) {
+//!             audio.play(&self.jump);
+//!         }
+//!         Ok(true)
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut app = App {
+//!     jump: Sound::load_wav("assets/jump.wav")?,
+//! };
+//! let mut engine = sge::Engine::new(&mut app, "Window Title", 640, 480)?;
+//! engine.play_audio = true;
+//! engine
+//!     .audio()
+//!     .play_music(Music::load_wav("assets/theme.wav")?.looped());
+//! engine.start(true)?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod mixer;
+use mixer::Mixer;
+mod music;
+pub use music::Music;
+use music::MusicStream;
+mod sound;
+pub use sound::Sound;
+mod synth;
+pub use synth::{Envelope, Oscillator, Preset, SoundEffect, Waveform};
+
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+use sdl2::{
+    audio::{AudioCallback, AudioDevice, AudioSpecDesired},
+    AudioSubsystem,
+};
+
+/// The sample rate audio is mixed at, unless another is chosen with
+/// [`Audio::with_sample_rate`].
+pub const DEFAULT_SAMPLE_RATE: u32 = 44100;
+
+/// How much music is decoded ahead of what's been played, in seconds.
+const MUSIC_BUFFER: f64 = 0.5;
+
+/// How often the streaming thread started by [`Audio::open`] decodes more music.
+const STREAM_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Identifies a sound started with [`Audio::play`], to change or stop it while it plays. Once
+/// the sound has finished, the voice is ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Voice(u64);
+
+/// How a sound is played by [`Audio::play_with`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayOptions {
+    /// How loud the sound is, where 1 is its original volume.
+    pub volume: f32,
+    /// Where the sound is between the left (-1) and right (1) speakers.
+    pub pan: f32,
+    /// How fast the sound plays, where 2 is twice as fast and an octave higher.
+    pub pitch: f32,
+    /// Whether the sound starts again from the beginning when it ends, until it's stopped.
+    pub looping: bool,
+}
+
+impl Default for PlayOptions {
+    /// At its original volume and pitch, in the centre, played once.
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+            looping: false,
+        }
+    }
+}
+
+/// A handle to the software mixer. Cloning the handle is cheap, and every clone controls the
+/// same mixer, so one can be kept by the application while another is played by the engine.
+#[derive(Clone)]
+pub struct Audio {
+    mixer: Arc<Mutex<Mixer>>,
+    /// Decodes the music, outside the mixer's lock so the audio device is never kept waiting.
+    /// When both are locked, this is always locked first.
+    streamer: Arc<Mutex<Streamer>>,
+}
+
+/// The music track being decoded, until the end of it has been.
+struct Streamer {
+    music: Option<(u64, Music)>,
+    next_track: u64,
+}
+
+impl Audio {
+    /// Create a mixer running at [`DEFAULT_SAMPLE_RATE`].
+    pub fn new() -> Self {
+        Self::with_sample_rate(DEFAULT_SAMPLE_RATE)
+    }
+
+    /// Create a mixer running at `sample_rate` samples per second.
+    pub fn with_sample_rate(sample_rate: u32) -> Self {
+        Self {
+            mixer: Arc::new(Mutex::new(Mixer::new(sample_rate))),
+            streamer: Arc::new(Mutex::new(Streamer {
+                music: None,
+                next_track: 0,
+            })),
+        }
+    }
+
+    /// Returns the number of samples mixed per second, for each channel.
+    pub fn sample_rate(&self) -> u32 {
+        self.mixer().sample_rate
+    }
+
+    /// Play `sound` once, at its original volume and pitch. Returns the voice playing it.
+    pub fn play(&self, sound: &Sound) -> Voice {
+        self.play_with(sound, PlayOptions::default())
+    }
+
+    /// Play `sound` with the given options. Returns the voice playing it.
+    /// # Example
+    /// ```
+    /// # use simple_game_engine::audio::{Audio, PlayOptions, Sound};
+    /// # let audio = Audio::new();
+    /// # let engine = Sound::from_samples(vec![0.0; 100], 1, 44100).unwrap();
+    /// // A quiet hum on the left, until it's stopped
+    /// let hum = audio.play_with(&engine, PlayOptions {
+    ///     volume: 0.5,
+    ///     pan: -1.0,
+    ///     looping: true,
+    ///     ..Default::default()
+    /// });
+    /// assert!(audio.is_playing(hum));
+    /// audio.stop(hum);
+    /// assert!(!audio.is_playing(hum));
+    /// ```
+    pub fn play_with(&self, sound: &Sound, options: PlayOptions) -> Voice {
+        self.mixer().play(sound, options)
+    }
+
+    /// Returns whether a voice is still playing.
+    pub fn is_playing(&self, voice: Voice) -> bool {
+        self.mixer().options_mut(voice).is_some()
+    }
+
+    /// Change the volume of a voice while it plays.
+    pub fn set_volume(&self, voice: Voice, volume: f32) {
+        if let Some(options) = self.mixer().options_mut(voice) {
+            options.volume = volume;
+        }
+    }
+
+    /// Move a voice between the left (-1) and right (1) speakers while it plays.
+    pub fn set_pan(&self, voice: Voice, pan: f32) {
+        if let Some(options) = self.mixer().options_mut(voice) {
+            options.pan = pan;
+        }
+    }
+
+    /// Change the pitch of a voice while it plays.
+    pub fn set_pitch(&self, voice: Voice, pitch: f32) {
+        if let Some(options) = self.mixer().options_mut(voice) {
+            options.pitch = pitch;
+        }
+    }
+
+    /// Change whether a voice loops. A voice that stops looping finishes at the end of the
+    /// sound.
+    pub fn set_looping(&self, voice: Voice, looping: bool) {
+        if let Some(options) = self.mixer().options_mut(voice) {
+            options.looping = looping;
+        }
+    }
+
+    /// Stop a voice playing.
+    pub fn stop(&self, voice: Voice) {
+        self.mixer().stop(voice);
+    }
+
+    /// Stop every voice playing. The music keeps playing.
+    pub fn stop_all(&self) {
+        self.mixer().stop_all();
+    }
+
+    /// Returns the volume everything is played at.
+    pub fn master_volume(&self) -> f32 {
+        self.mixer().master_volume
+    }
+
+    /// Change the volume everything is played at, where 1 is full volume.
+    pub fn set_master_volume(&self, volume: f32) {
+        self.mixer().master_volume = volume;
+    }
+
+    /// Play `music`, replacing any music that's already playing.
+    pub fn play_music(&self, music: Music) {
+        {
+            let mut streamer = self.streamer();
+            let track = streamer.next_track;
+            streamer.next_track += 1;
+            self.mixer().music = Some(MusicStream::new(track, music.sample_rate()));
+            streamer.music = Some((track, music));
+        }
+        self.stream(MUSIC_BUFFER);
+    }
+
+    /// Stop the music.
+    pub fn stop_music(&self) {
+        let mut streamer = self.streamer();
+        streamer.music = None;
+        self.mixer().music = None;
+    }
+
+    /// Returns whether there's music playing.
+    pub fn is_music_playing(&self) -> bool {
+        self.mixer().music.is_some()
+    }
+
+    /// Returns the volume music is played at.
+    pub fn music_volume(&self) -> f32 {
+        self.mixer().music_volume
+    }
+
+    /// Change the volume music is played at, where 1 is its original volume.
+    pub fn set_music_volume(&self, volume: f32) {
+        self.mixer().music_volume = volume;
+    }
+
+    /// Fill `out` with the next interleaved stereo samples, moving every voice and the music
+    /// along. The audio device mixes this way while it's open; call it yourself to mix audio
+    /// without one, such as to save it to a file.
+    pub fn mix(&self, out: &mut [f32]) {
+        self.stream(out.len() as f64 / 2.0 / self.sample_rate() as f64);
+        self.mixer().mix(out);
+    }
+
+    /// Start playing the mixer through the default audio device, and decoding the music on
+    /// another thread. The engine does this itself when
+    /// [`Engine::play_audio`][crate::Engine::play_audio] is set. Audio plays until the returned
+    /// [`AudioOutput`] is dropped. Returns a `String` describing the error if sdl can't open
+    /// the device.
+    pub fn open(&self, audio: &AudioSubsystem) -> Result<AudioOutput, String> {
+        let desired = AudioSpecDesired {
+            freq: Some(self.sample_rate() as i32),
+            channels: Some(2),
+            samples: None,
+        };
+        let device = audio.open_playback(None, &desired, |_| Callback(self.clone()))?;
+        device.resume();
+        // Runs until the output, and so the sender, is dropped
+        let (stop, stopped) = mpsc::channel();
+        let streamer = self.clone();
+        thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(STREAM_INTERVAL) {
+                streamer.stream(MUSIC_BUFFER);
+            }
+        });
+        Ok(AudioOutput {
+            device,
+            _stop: stop,
+        })
+    }
+
+    /// Decode the music until at least `seconds` of it are ready to play, without keeping the
+    /// mixer locked while it decodes.
+    fn stream(&self, seconds: f64) {
+        let mut streamer = self.streamer();
+        let (track, music) = match &mut streamer.music {
+            Some((track, music)) => (*track, music),
+            None => return,
+        };
+        let ahead = match &self.mixer().music {
+            Some(stream) if stream.track == track => stream.frames_ahead(),
+            // The track has finished or been replaced
+            _ => {
+                streamer.music = None;
+                return;
+            }
+        };
+        // Interpolating the last frame needs the one after it too
+        let wanted = (seconds * music.sample_rate() as f64).ceil() as usize + 1;
+        let mut samples = Vec::new();
+        let mut complete = false;
+        while ahead + samples.len() / 2 < wanted {
+            if !music.decode(&mut samples) {
+                complete = true;
+                break;
+            }
+        }
+        if samples.is_empty() && !complete {
+            return;
+        }
+        if let Some(stream) = &mut self.mixer().music {
+            if stream.track == track {
+                stream.queue(&samples, complete);
+            }
+        }
+        if complete {
+            streamer.music = None;
+        }
+    }
+
+    /// Lock the mixer. A panic while it was locked can't leave it in an unsafe state, so a
+    /// poisoned lock is ignored.
+    fn mixer(&self) -> MutexGuard<'_, Mixer> {
+        self.mixer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the music being decoded, ignoring a poisoned lock like [`mixer`][Self::mixer].
+    fn streamer(&self) -> MutexGuard<'_, Streamer> {
+        self.streamer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plays a mixer through an audio device, until it's dropped. See [`Audio::open`].
+pub struct AudioOutput {
+    device: AudioDevice<Callback>,
+    /// Stops the streaming thread when dropped.
+    _stop: Sender<()>,
+}
+
+impl AudioOutput {
+    /// Returns the name of the sdl audio driver being played through.
+    pub fn driver(&self) -> &'static str {
+        self.device.subsystem().current_audio_driver()
+    }
+}
+
+/// Fills the audio device's buffer from the mixer.
+struct Callback(Audio);
+
+impl AudioCallback for Callback {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        // The music is decoded by the streaming thread, so this never waits for it
+        self.0.mixer().mix(out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    fn mix(audio: &Audio, frames: usize) -> Vec<f32> {
+        let mut out = vec![1.0; frames * 2];
+        audio.mix(&mut out);
+        out
+    }
+
+    /// Test mixing voices with different volumes, pans and pitches.
+    #[test]
+    fn test_mix() {
+        let audio = Audio::with_sample_rate(100);
+        let sound = Sound::from_samples(vec![0.5, 0.5, -0.5, -0.5], 1, 100).unwrap();
+        let voice = audio.play(&sound);
+        assert!(audio.is_playing(voice));
+        assert_eq!(mix(&audio, 2), [0.5, 0.5, 0.5, 0.5]);
+        audio.set_pan(voice, 0.5);
+        audio.set_volume(voice, 0.5);
+        assert_eq!(mix(&audio, 3), [-0.125, -0.25, -0.125, -0.25, 0.0, 0.0]);
+        assert!(!audio.is_playing(voice));
+
+        // Two voices add together, and are clipped
+        audio.play(&sound);
+        audio.play_with(
+            &sound,
+            PlayOptions {
+                pitch: 2.0,
+                pan: -1.0,
+                ..Default::default()
+            },
+        );
+        assert_eq!(mix(&audio, 3), [1.0, 0.5, 0.0, 0.5, -0.5, -0.5]);
+        audio.set_master_volume(0.5);
+        assert_eq!(mix(&audio, 1), [-0.25, -0.25]);
+        assert_eq!(mix(&audio, 1), [0.0, 0.0]);
+    }
+
+    /// Test looping voices, and stopping them.
+    #[test]
+    fn test_looping() {
+        let audio = Audio::with_sample_rate(100);
+        let sound = Sound::from_samples(vec![0.0, 1.0, 0.5, -0.5], 2, 100).unwrap();
+        let voice = audio.play_with(
+            &sound,
+            PlayOptions {
+                looping: true,
+                ..Default::default()
+            },
+        );
+        let other = audio.play(&sound);
+        assert_eq!(
+            mix(&audio, 5),
+            [0.0, 1.0, 1.0, -1.0, 0.0, 1.0, 0.5, -0.5, 0.0, 1.0]
+        );
+        assert!(audio.is_playing(voice));
+        assert!(!audio.is_playing(other));
+        audio.set_looping(voice, false);
+        assert_eq!(mix(&audio, 2), [0.5, -0.5, 0.0, 0.0]);
+        assert!(!audio.is_playing(voice));
+
+        let voice = audio.play(&sound);
+        audio.stop_all();
+        assert!(!audio.is_playing(voice));
+        let music = Music::from_sound(sound).looped();
+        audio.play_music(music);
+        audio.set_music_volume(0.5);
+        assert_eq!(mix(&audio, 2), [0.0, 0.5, 0.25, -0.25]);
+        assert!(audio.is_music_playing());
+        audio.stop_music();
+        assert!(!audio.is_music_playing());
+    }
+
+    /// Test playing through an audio device with sdl's dummy driver.
+    #[test]
+    fn test_open() {
+        // Set as a hint rather than in the environment, which other tests may be reading. SDL
+        // before 2.0.22 ignores the hint, so needs SDL_AUDIODRIVER=dummy set for the whole run
+        sdl2::hint::set("SDL_AUDIODRIVER", "dummy");
+        let sdl = sdl2::init().unwrap();
+        let audio = Audio::new();
+        let output = audio.open(&sdl.audio().unwrap()).unwrap();
+        assert_eq!(output.driver(), "dummy");
+        let sound = Sound::from_samples(vec![0.5; 441], 1, DEFAULT_SAMPLE_RATE).unwrap();
+        let voice = audio.play(&sound);
+        for _ in 0..200 {
+            if !audio.is_playing(voice) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!audio.is_playing(voice));
+
+        // A second of music, more than is decoded ahead at once, so the streaming thread has to
+        // decode the rest while the device plays it
+        let sound = Sound::from_samples(vec![0.5; 44100], 1, DEFAULT_SAMPLE_RATE).unwrap();
+        audio.play_music(Music::from_sound(sound));
+        for _ in 0..300 {
+            if !audio.is_music_playing() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!audio.is_music_playing());
+    }
+}
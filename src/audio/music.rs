@@ -0,0 +1,311 @@
+//! Music, which is decoded a little at a time while it plays.
+
+use std::path::Path;
+
+use super::Sound;
+
+/// The number of frames of a [`Sound`] copied into the music buffer at a time.
+const SOUND_CHUNK: usize = 4096;
+
+/// Something music can be decoded from.
+trait Decoder: Send {
+    /// Decode some more stereo frames onto the end of `buffer`. Returns `false` at the end of
+    /// the track.
+    fn decode(&mut self, buffer: &mut Vec<f32>) -> Result<bool, String>;
+    /// Go back to the start of the track.
+    fn rewind(&mut self) -> Result<(), String>;
+}
+
+/// Decodes music from a sound that's already in memory.
+struct SoundDecoder {
+    sound: Sound,
+    /// The next frame to decode.
+    position: usize,
+}
+
+impl Decoder for SoundDecoder {
+    fn decode(&mut self, buffer: &mut Vec<f32>) -> Result<bool, String> {
+        let end = (self.position + SOUND_CHUNK).min(self.sound.frames());
+        if self.position == end {
+            return Ok(false);
+        }
+        for frame in self.position..end {
+            let (left, right) = self.sound.frame_at(frame as f64, false);
+            buffer.extend_from_slice(&[left, right]);
+        }
+        self.position = end;
+        Ok(true)
+    }
+
+    fn rewind(&mut self) -> Result<(), String> {
+        self.position = 0;
+        Ok(())
+    }
+}
+
+/// Decodes an ogg vorbis stream.
+#[cfg(feature = "ogg")]
+pub(super) struct OggDecoder<R: std::io::Read + std::io::Seek> {
+    reader: lewton::inside_ogg::OggStreamReader<R>,
+}
+
+#[cfg(feature = "ogg")]
+impl<R: std::io::Read + std::io::Seek> OggDecoder<R> {
+    /// Read the headers at the start of the stream.
+    pub(super) fn new(source: R) -> Result<Self, String> {
+        let reader = lewton::inside_ogg::OggStreamReader::new(source).map_err(|e| e.to_string())?;
+        Ok(Self { reader })
+    }
+
+    /// Returns the number of samples played per second, for each channel.
+    fn sample_rate(&self) -> u32 {
+        self.reader.ident_hdr.audio_sample_rate
+    }
+
+    /// Decode the whole stream into a stereo sound.
+    pub(super) fn decode_all(mut self) -> Result<Sound, String> {
+        let mut samples = Vec::new();
+        while self.next_packet(&mut samples)? {}
+        Sound::from_samples(samples, 2, self.sample_rate())
+    }
+
+    /// Decode the next packet, taking the first two channels of surround sound.
+    fn next_packet(&mut self, buffer: &mut Vec<f32>) -> Result<bool, String> {
+        let channels = self.reader.ident_hdr.audio_channels as usize;
+        loop {
+            let packet = match self.reader.read_dec_packet_itl() {
+                Ok(Some(packet)) => packet,
+                Ok(None) => return Ok(false),
+                Err(e) => return Err(e.to_string()),
+            };
+            // Some packets only set up the decoder for the next one
+            if packet.is_empty() {
+                continue;
+            }
+            for frame in packet.chunks_exact(channels) {
+                let left = frame[0] as f32 / 32768.0;
+                let right = frame.get(1).map_or(left, |&s| s as f32 / 32768.0);
+                buffer.extend_from_slice(&[left, right]);
+            }
+            return Ok(true);
+        }
+    }
+}
+
+#[cfg(feature = "ogg")]
+impl<R: std::io::Read + std::io::Seek + Send> Decoder for OggDecoder<R> {
+    fn decode(&mut self, buffer: &mut Vec<f32>) -> Result<bool, String> {
+        self.next_packet(buffer)
+    }
+
+    fn rewind(&mut self) -> Result<(), String> {
+        self.reader.seek_absgp_pg(0).map_err(|e| e.to_string())
+    }
+}
+
+/// A music track, which is decoded as it plays rather than all at once, and played on its own
+/// channel with [`Audio::play_music`][super::Audio::play_music].
+pub struct Music {
+    decoder: Box<dyn Decoder>,
+    sample_rate: u32,
+    looping: bool,
+}
+
+impl Music {
+    /// Play a sound that's already been loaded as music.
+    pub fn from_sound(sound: Sound) -> Self {
+        let sample_rate = sound.sample_rate();
+        Self::new(Box::new(SoundDecoder { sound, position: 0 }), sample_rate)
+    }
+
+    /// Load a `.wav` file to play as music. Wav files are decoded all at once, so use ogg
+    /// vorbis files for long pieces of music. Returns a `String` describing the error if the
+    /// file can't be read or decoded.
+    pub fn load_wav<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        Sound::load_wav(path).map(Self::from_sound)
+    }
+
+    /// Stream music from the contents of an ogg vorbis file. Returns a `String` describing the
+    /// error if it isn't valid.
+    #[cfg(feature = "ogg")]
+    pub fn from_ogg(data: Vec<u8>) -> Result<Self, String> {
+        let decoder = OggDecoder::new(std::io::Cursor::new(data))?;
+        let sample_rate = decoder.sample_rate();
+        Ok(Self::new(Box::new(decoder), sample_rate))
+    }
+
+    /// Stream music from an ogg vorbis file, which is read as it plays. Returns a `String`
+    /// describing the error if the file can't be opened, or isn't valid.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::audio::{Audio, Music};
+    /// # fn load(audio: &Audio) -> Result<(), String> {
+    /// audio.play_music(Music::load_ogg("assets/theme.ogg")?.looped());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "ogg")]
+    pub fn load_ogg<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let decoder = std::fs::File::open(path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| OggDecoder::new(std::io::BufReader::new(file)))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let sample_rate = decoder.sample_rate();
+        Ok(Self::new(Box::new(decoder), sample_rate))
+    }
+
+    /// Start again from the beginning when the track ends, rather than stopping.
+    pub fn looped(self) -> Self {
+        Self {
+            looping: true,
+            ..self
+        }
+    }
+
+    /// Returns whether the track starts again from the beginning when it ends.
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Returns the number of frames played per second.
+    pub(super) fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn new(decoder: Box<dyn Decoder>, sample_rate: u32) -> Self {
+        Self {
+            decoder,
+            sample_rate,
+            looping: false,
+        }
+    }
+
+    /// Decode some more stereo frames of the track onto the end of `buffer`, going back to the
+    /// start at the end if it's looping. Returns `false` if there's nothing more to play.
+    /// Errors while playing end the track, as there's nowhere to report them.
+    pub(super) fn decode(&mut self, buffer: &mut Vec<f32>) -> bool {
+        match self.decoder.decode(buffer) {
+            Ok(true) => return true,
+            Ok(false) => (),
+            Err(_) => return false,
+        }
+        self.looping && self.decoder.rewind().is_ok() && self.decoder.decode(buffer) == Ok(true)
+    }
+}
+
+/// The part of the music that's been decoded but not played yet, which the mixer plays from.
+/// The music is decoded ahead of time on another thread, so the audio device never has to wait
+/// for it.
+pub(super) struct MusicStream {
+    /// Identifies the track being played, so frames decoded from a track that has since been
+    /// replaced are never played.
+    pub(super) track: u64,
+    sample_rate: u32,
+    /// Decoded stereo frames that haven't been played yet.
+    buffer: Vec<f32>,
+    /// Position of the next frame to play in `buffer`.
+    position: f64,
+    /// Whether the end of the track has been decoded.
+    complete: bool,
+}
+
+impl MusicStream {
+    pub(super) fn new(track: u64, sample_rate: u32) -> Self {
+        Self {
+            track,
+            sample_rate,
+            buffer: Vec::new(),
+            position: 0.0,
+            complete: false,
+        }
+    }
+
+    /// Returns the number of decoded frames that haven't been played yet.
+    pub(super) fn frames_ahead(&self) -> usize {
+        (self.buffer.len() / 2).saturating_sub(self.position as usize)
+    }
+
+    /// Add newly decoded stereo frames, and whether they're the end of the track.
+    pub(super) fn queue(&mut self, samples: &[f32], complete: bool) {
+        self.buffer.extend_from_slice(samples);
+        self.complete = complete;
+    }
+
+    /// Add the next stereo frames of music to `out` at `volume`, resampled to `sample_rate`.
+    /// If not enough has been decoded yet, the rest is left silent. Returns `false` once the
+    /// track has finished.
+    pub(super) fn mix(&mut self, out: &mut [f32], sample_rate: u32, volume: f32) -> bool {
+        let step = self.sample_rate as f64 / sample_rate as f64;
+        let frames = self.buffer.len() / 2;
+        let mut playing = true;
+        for frame in out.chunks_exact_mut(2) {
+            let index = self.position as usize;
+            if index >= frames {
+                playing = !self.complete;
+                break;
+            }
+            // Interpolating needs the frame after this one too
+            let next = index + 1;
+            if next >= frames && !self.complete {
+                break;
+            }
+            let next = next.min(frames - 1);
+            let t = (self.position - index as f64) as f32;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let (first, second) = (
+                    self.buffer[index * 2 + channel],
+                    self.buffer[next * 2 + channel],
+                );
+                *sample += (first + (second - first) * t) * volume;
+            }
+            self.position += step;
+        }
+        // Forget frames that have been played
+        let played = (self.position as usize).min(frames);
+        self.buffer.drain(..played * 2);
+        self.position -= played as f64;
+        playing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test playing decoded music, resampling it, and waiting for more to be decoded.
+    #[test]
+    fn test_music_stream() {
+        let mut music = Music::from_sound(Sound::from_samples(vec![0.5, 1.0], 1, 100).unwrap());
+        let mut samples = Vec::new();
+        assert!(music.decode(&mut samples));
+        assert!(!music.decode(&mut samples));
+        assert_eq!(samples, [0.5, 0.5, 1.0, 1.0]);
+
+        let mut stream = MusicStream::new(0, 100);
+        stream.queue(&samples, false);
+        assert_eq!(stream.frames_ahead(), 2);
+        // The last frame is held back until the frame after it is decoded
+        let mut out = [0.0; 6];
+        assert!(stream.mix(&mut out, 100, 0.5));
+        assert_eq!(out, [0.25, 0.25, 0.0, 0.0, 0.0, 0.0]);
+        stream.queue(&[0.0, 0.0], true);
+        let mut out = [0.0; 10];
+        assert!(!stream.mix(&mut out, 200, 1.0));
+        assert_eq!(out, [1.0, 1.0, 0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(stream.frames_ahead(), 0);
+
+        let mut music = Music::from_sound(Sound::from_samples(vec![0.5], 1, 100).unwrap()).looped();
+        assert!(music.is_looping());
+        let mut samples = Vec::new();
+        assert!(music.decode(&mut samples));
+        assert!(music.decode(&mut samples));
+        assert_eq!(samples, [0.5; 4]);
+        #[cfg(feature = "ogg")]
+        {
+            assert!(Music::from_ogg(b"not an ogg file".to_vec()).is_err());
+            let error = Music::load_ogg("missing.ogg").err().unwrap();
+            assert!(error.starts_with("missing.ogg: "));
+        }
+    }
+}
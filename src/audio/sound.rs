@@ -0,0 +1,212 @@
+//! Sounds decoded into memory.
+
+use std::{path::Path, sync::Arc};
+
+use sdl2::{
+    audio::{AudioCVT, AudioFormat, AudioSpecWAV},
+    rwops::RWops,
+};
+
+/// A sound decoded into memory, ready to be played any number of times with
+/// [`Audio::play`][super::Audio::play]. Cloning a sound is cheap, as the samples are shared.
+#[derive(Clone, Debug)]
+pub struct Sound {
+    /// Interleaved samples, between -1 and 1.
+    samples: Arc<[f32]>,
+    channels: u8,
+    sample_rate: u32,
+}
+
+impl Sound {
+    /// Create a sound from interleaved samples between -1 and 1, with 1 (mono) or 2 (stereo)
+    /// `channels`, played at `sample_rate` samples per second. Returns a `String` describing the
+    /// error if there are any other number of channels, or the samples don't divide evenly
+    /// between them.
+    pub fn from_samples(samples: Vec<f32>, channels: u8, sample_rate: u32) -> Result<Self, String> {
+        if channels != 1 && channels != 2 {
+            return Err(format!(
+                "Sounds must have 1 or 2 channels, not {}",
+                channels
+            ));
+        }
+        if !samples.len().is_multiple_of(channels as usize) {
+            return Err(format!(
+                "{} samples can't be split between {} channels",
+                samples.len(),
+                channels
+            ));
+        }
+        if sample_rate == 0 {
+            return Err("The sample rate must be greater than 0".to_string());
+        }
+        Ok(Self {
+            samples: samples.into(),
+            channels,
+            sample_rate,
+        })
+    }
+
+    /// Decode the contents of a `.wav` file. Sounds with more than 2 channels are mixed down to
+    /// stereo. Returns a `String` describing the error if sdl can't decode it.
+    pub fn from_wav(data: &[u8]) -> Result<Self, String> {
+        Self::decode_wav(&mut RWops::from_bytes(data)?)
+    }
+
+    /// Load and decode a `.wav` file. Sounds with more than 2 channels are mixed down to stereo.
+    /// Returns a `String` describing the error if the file can't be read or decoded.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::audio::{Audio, Sound};
+    /// # fn load(audio: &Audio) -> Result<(), String> {
+    /// let jump = Sound::load_wav("assets/jump.wav")?;
+    /// audio.play(&jump);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_wav<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        RWops::from_file(path, "rb")
+            .and_then(|mut file| Self::decode_wav(&mut file))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Decode an ogg vorbis file. Returns a `String` describing the error if it isn't valid.
+    #[cfg(feature = "ogg")]
+    pub fn from_ogg(data: &[u8]) -> Result<Self, String> {
+        let decoder = super::music::OggDecoder::new(std::io::Cursor::new(data))?;
+        decoder.decode_all()
+    }
+
+    /// Load and decode an ogg vorbis file. Long pieces of music take up a lot of memory once
+    /// decoded, so are better streamed with [`Music::load_ogg`][super::Music::load_ogg].
+    /// Returns a `String` describing the error if the file can't be read or decoded.
+    #[cfg(feature = "ogg")]
+    pub fn load_ogg<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        std::fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| Self::from_ogg(&data))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Returns the interleaved samples.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Returns the number of channels, which is 1 for mono sounds or 2 for stereo.
+    pub fn channels(&self) -> u8 {
+        self.channels
+    }
+
+    /// Returns the number of samples played per second, for each channel.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the number of frames, which each hold one sample for every channel.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    /// Returns how long the sound plays for, in seconds, at its original pitch.
+    pub fn duration(&self) -> f64 {
+        self.frames() as f64 / self.sample_rate as f64
+    }
+
+    /// Returns the left and right samples at `position`, measured in frames, interpolating
+    /// between neighbouring frames. If `wrap` is set, the last frame leads into the first.
+    pub(super) fn frame_at(&self, position: f64, wrap: bool) -> (f32, f32) {
+        let frames = self.frames();
+        let index = position as usize;
+        let next = match index + 1 {
+            next if next < frames => next,
+            _ if wrap => 0,
+            _ => index,
+        };
+        let t = (position - index as f64) as f32;
+        let (first, second) = (self.frame(index), self.frame(next));
+        (
+            first.0 + (second.0 - first.0) * t,
+            first.1 + (second.1 - first.1) * t,
+        )
+    }
+
+    /// Returns the left and right samples of a frame.
+    fn frame(&self, index: usize) -> (f32, f32) {
+        match self.channels {
+            1 => (self.samples[index], self.samples[index]),
+            _ => (self.samples[index * 2], self.samples[index * 2 + 1]),
+        }
+    }
+
+    /// Decode a `.wav` file, converting its samples to floats.
+    fn decode_wav(data: &mut RWops) -> Result<Self, String> {
+        let wav = AudioSpecWAV::load_wav_rw(data)?;
+        let channels = wav.channels.min(2);
+        let convert = AudioCVT::new(
+            wav.format,
+            wav.channels,
+            wav.freq,
+            AudioFormat::f32_sys(),
+            channels,
+            wav.freq,
+        )?;
+        let samples = convert
+            .convert(wav.buffer().to_vec())
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        Self::from_samples(samples, channels, wav.freq as u32)
+    }
+}
+
+/// Returns the contents of a 16 bit PCM `.wav` file holding `samples`, for tests.
+#[cfg(test)]
+fn wav_file(samples: &[i16], channels: u16, sample_rate: u32) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes());
+    wav.extend_from_slice(&(channels * 2).to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test decoding wav files and reading frames.
+    #[test]
+    fn test_wav() {
+        let sound = Sound::from_wav(&wav_file(&[0, 16384, -16384, 0], 2, 8000)).unwrap();
+        assert_eq!(sound.channels(), 2);
+        assert_eq!(sound.sample_rate(), 8000);
+        assert_eq!(sound.frames(), 2);
+        assert_eq!(sound.samples(), &[0.0, 0.5, -0.5, 0.0]);
+        assert_eq!(sound.frame_at(0.5, false), (-0.25, 0.25));
+        assert_eq!(sound.frame_at(1.5, false), (-0.5, 0.0));
+        assert_eq!(sound.frame_at(1.5, true), (-0.25, 0.25));
+        assert!(Sound::from_wav(b"not a wav file").is_err());
+        let error = Sound::load_wav("missing.wav").unwrap_err();
+        assert!(error.starts_with("missing.wav: "));
+
+        assert!(Sound::from_samples(vec![0.0; 3], 2, 8000).is_err());
+        assert!(Sound::from_samples(vec![0.0; 3], 3, 8000).is_err());
+        let sound = Sound::from_samples(vec![0.25; 4000], 1, 8000).unwrap();
+        assert_eq!(sound.duration(), 0.5);
+        assert_eq!(sound.frame_at(10.0, false), (0.25, 0.25));
+    }
+}
@@ -0,0 +1,520 @@
+//! Generating sounds from oscillators and envelopes, rather than loading them from files.
+
+use std::f32::consts::TAU;
+
+use super::Sound;
+
+/// The number of random values in each period of noise.
+const NOISE_STEPS: f32 = 32.0;
+
+/// Generates pseudo-random numbers with xorshift, so generated sounds are the same every time.
+#[derive(Clone, Debug)]
+struct Random(u32);
+
+impl Random {
+    fn new(seed: u32) -> Self {
+        // Xorshift never leaves 0
+        Self((seed ^ 0x9e37_79b9).max(1))
+    }
+
+    /// Returns a number from 0.0 to 1.0.
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32
+    }
+
+    /// Returns a number from `min` to `max`.
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next()
+    }
+
+    /// Returns `true` half of the time.
+    fn flip(&mut self) -> bool {
+        self.next() < 0.5
+    }
+}
+
+/// The shape of the wave an [`Oscillator`] produces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    /// A smooth, pure tone.
+    Sine,
+    /// A hollow, buzzy tone, which is high for `duty` (from 0.0 to 1.0) of each period and low
+    /// for the rest. A duty of 0.5 sounds the hollowest, and lower or higher values get thinner.
+    Square {
+        /// The fraction of each period the wave is high for.
+        duty: f32,
+    },
+    /// A bright, harsh tone, which rises steadily then drops.
+    Saw,
+    /// A soft tone between a sine and a square wave.
+    Triangle,
+    /// Random values, for explosions and hits. The frequency sets how often the value changes.
+    Noise,
+}
+
+/// Produces samples of a wave, at a frequency that can change while it plays. Every call to
+/// [`next`][Iterator::next] returns the next sample, from -1.0 to 1.0, so an oscillator never
+/// runs out.
+/// # Example
+/// ```
+/// # use simple_game_engine::audio::{Oscillator, Sound, Waveform};
+/// // A second of concert A
+/// let samples = Oscillator::new(Waveform::Sine, 440.0, 44100).take(44100).collect();
+/// let tone = Sound::from_samples(samples, 1, 44100)?;
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Oscillator {
+    waveform: Waveform,
+    frequency: f32,
+    sample_rate: u32,
+    /// How far through the current period the wave is, from 0.0 to 1.0.
+    phase: f32,
+    random: Random,
+    noise: f32,
+}
+
+impl Oscillator {
+    /// Create an oscillator producing `sample_rate` samples per second of a wave at `frequency`
+    /// hertz.
+    pub fn new(waveform: Waveform, frequency: f32, sample_rate: u32) -> Self {
+        let mut random = Random::new(0);
+        let noise = random.next() * 2.0 - 1.0;
+        Self {
+            waveform,
+            frequency,
+            sample_rate,
+            phase: 0.0,
+            random,
+            noise,
+        }
+    }
+
+    /// Returns the shape of the wave.
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    /// Change the shape of the wave, carrying on from the same point in the period.
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+    }
+
+    /// Returns the frequency of the wave, in hertz.
+    pub fn frequency(&self) -> f32 {
+        self.frequency
+    }
+
+    /// Change the frequency of the wave, carrying on from the same point in the period so it
+    /// doesn't click.
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+    }
+
+    /// Returns the value of the wave at the current phase.
+    fn value(&self) -> f32 {
+        let phase = self.phase;
+        match self.waveform {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square { duty } if phase < duty => 1.0,
+            Waveform::Square { .. } => -1.0,
+            Waveform::Saw => phase * 2.0 - 1.0,
+            Waveform::Triangle => 1.0 - (phase * 4.0 - 2.0).abs(),
+            Waveform::Noise => self.noise,
+        }
+    }
+}
+
+impl Iterator for Oscillator {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let value = self.value();
+        let step = (self.phase * NOISE_STEPS) as u32;
+        self.phase += self.frequency.max(0.0) / self.sample_rate as f32;
+        let wrapped = self.phase >= 1.0;
+        self.phase = self.phase.fract();
+        if wrapped || (self.phase * NOISE_STEPS) as u32 != step {
+            self.noise = self.random.next() * 2.0 - 1.0;
+        }
+        Some(value)
+    }
+}
+
+/// Shapes the volume of a note over time: it rises to full volume over the `attack`, falls to
+/// the `sustain` level over the `decay`, stays there while the note is held, then fades to
+/// silence over the `release` once it's let go. Times are in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Envelope {
+    /// Time taken to reach full volume.
+    pub attack: f32,
+    /// Time taken to fall from full volume to the sustain level.
+    pub decay: f32,
+    /// The volume held until the note is let go, from 0.0 to 1.0.
+    pub sustain: f32,
+    /// Time taken to fade to silence once the note is let go.
+    pub release: f32,
+}
+
+impl Envelope {
+    /// Create an envelope with the given times and sustain level.
+    pub fn new(attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Self {
+            attack,
+            decay,
+            sustain,
+            release,
+        }
+    }
+
+    /// Returns the volume `time` seconds after a note starts, if it's let go after `held`
+    /// seconds.
+    pub fn level(&self, time: f32, held: f32) -> f32 {
+        if time < held {
+            return self.held_level(time);
+        }
+        let released = time - held;
+        if released >= self.release {
+            return 0.0;
+        }
+        self.held_level(held) * (1.0 - released / self.release)
+    }
+
+    /// Returns how long a note lasts if it's let go after `held` seconds.
+    pub fn duration(&self, held: f32) -> f32 {
+        held + self.release.max(0.0)
+    }
+
+    /// Returns the volume `time` seconds after a note starts, while it's still held.
+    fn held_level(&self, time: f32) -> f32 {
+        if time < self.attack {
+            time / self.attack
+        } else if time < self.attack + self.decay {
+            1.0 - (1.0 - self.sustain) * (time - self.attack) / self.decay
+        } else {
+            self.sustain
+        }
+    }
+}
+
+impl Default for Envelope {
+    /// Full volume straight away, until the note is let go.
+    fn default() -> Self {
+        Self::new(0.0, 0.0, 1.0, 0.0)
+    }
+}
+
+/// The kinds of sound effect [`SoundEffect::preset`] can generate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// Collecting a coin or item.
+    Pickup,
+    /// Firing a laser or shooting.
+    Laser,
+    /// An explosion.
+    Explosion,
+    /// Collecting a power-up.
+    PowerUp,
+    /// Being hit or hurt.
+    Hit,
+    /// Jumping.
+    Jump,
+    /// A short beep, such as for selecting a menu item.
+    Blip,
+}
+
+/// A retro sound effect described by a handful of parameters, in the style of sfxr. Start from
+/// [`Default`] or a [`preset`][Self::preset], change the parameters until it sounds right, then
+/// [`render`][Self::render] it.
+/// # Example
+/// ```
+/// # use simple_game_engine::audio::{Audio, Preset, SoundEffect};
+/// # let audio = Audio::new();
+/// let coin = SoundEffect::preset(Preset::Pickup, 1).to_sound(audio.sample_rate())?;
+/// audio.play(&coin);
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SoundEffect {
+    /// The shape of the wave.
+    pub waveform: Waveform,
+    /// How the volume changes over time.
+    pub envelope: Envelope,
+    /// How long the sound is held before the envelope's release, in seconds.
+    pub hold: f32,
+    /// The starting frequency, in hertz.
+    pub frequency: f32,
+    /// How fast the frequency changes, in octaves per second.
+    pub slide: f32,
+    /// How fast the slide changes, in octaves per second per second.
+    pub delta_slide: f32,
+    /// The sound stops early if the frequency slides below this many hertz.
+    pub min_frequency: f32,
+    /// How far the vibrato moves the frequency, as a fraction of it.
+    pub vibrato_depth: f32,
+    /// How many times per second the vibrato wobbles.
+    pub vibrato_speed: f32,
+    /// The frequency is multiplied by this once [`arpeggio_time`][Self::arpeggio_time] has
+    /// passed, jumping to another note.
+    pub arpeggio: f32,
+    /// When the frequency jumps by the [`arpeggio`][Self::arpeggio], in seconds, or 0.0 to never
+    /// jump.
+    pub arpeggio_time: f32,
+    /// How fast the duty of a square wave changes, per second.
+    pub duty_sweep: f32,
+    /// How loud the sound is, from 0.0 to 1.0.
+    pub volume: f32,
+}
+
+impl SoundEffect {
+    /// Generate a random sound effect of the given kind. The same `seed` always gives the same
+    /// sound, and different seeds give variations on it.
+    pub fn preset(preset: Preset, seed: u32) -> Self {
+        let mut random = Random::new(seed);
+        let square = |random: &mut Random| Waveform::Square {
+            duty: random.range(0.2, 0.5),
+        };
+        let mut effect = Self::default();
+        match preset {
+            Preset::Pickup => {
+                effect.waveform = square(&mut random);
+                effect.frequency = random.range(600.0, 1600.0);
+                effect.hold = random.range(0.02, 0.1);
+                effect.envelope.release = random.range(0.1, 0.4);
+                effect.arpeggio = random.range(1.3, 1.8);
+                effect.arpeggio_time = random.range(0.03, 0.1);
+            }
+            Preset::Laser => {
+                effect.waveform = match random.flip() {
+                    true => square(&mut random),
+                    false => Waveform::Saw,
+                };
+                effect.frequency = random.range(500.0, 2000.0);
+                effect.slide = random.range(-6.0, -2.0);
+                effect.min_frequency = random.range(50.0, 200.0);
+                effect.hold = random.range(0.05, 0.2);
+                effect.envelope.release = random.range(0.05, 0.2);
+                effect.duty_sweep = random.range(-0.5, 0.5);
+            }
+            Preset::Explosion => {
+                effect.waveform = Waveform::Noise;
+                effect.frequency = random.range(100.0, 600.0);
+                effect.slide = random.range(-2.0, -0.5);
+                effect.hold = random.range(0.1, 0.3);
+                effect.envelope.release = random.range(0.3, 0.7);
+                effect.volume = 0.6;
+            }
+            Preset::PowerUp => {
+                effect.waveform = match random.flip() {
+                    true => square(&mut random),
+                    false => Waveform::Saw,
+                };
+                effect.frequency = random.range(200.0, 500.0);
+                effect.slide = random.range(1.0, 3.0);
+                effect.vibrato_depth = random.range(0.05, 0.2);
+                effect.vibrato_speed = random.range(10.0, 20.0);
+                effect.hold = random.range(0.2, 0.4);
+                effect.envelope.release = random.range(0.2, 0.4);
+            }
+            Preset::Hit => {
+                effect.waveform = match random.flip() {
+                    true => Waveform::Noise,
+                    false => square(&mut random),
+                };
+                effect.frequency = random.range(100.0, 500.0);
+                effect.slide = random.range(-4.0, -2.0);
+                effect.hold = random.range(0.02, 0.05);
+                effect.envelope.release = random.range(0.1, 0.2);
+            }
+            Preset::Jump => {
+                effect.waveform = square(&mut random);
+                effect.frequency = random.range(300.0, 600.0);
+                effect.slide = random.range(1.0, 3.0);
+                effect.hold = random.range(0.05, 0.15);
+                effect.envelope.release = random.range(0.1, 0.2);
+            }
+            Preset::Blip => {
+                effect.waveform = match random.flip() {
+                    true => square(&mut random),
+                    false => Waveform::Sine,
+                };
+                effect.frequency = random.range(400.0, 1200.0);
+                effect.hold = random.range(0.05, 0.1);
+                effect.envelope.release = random.range(0.01, 0.05);
+            }
+        }
+        effect
+    }
+
+    /// Returns how long the sound lasts, in seconds, unless its frequency slides below the
+    /// minimum first.
+    pub fn duration(&self) -> f32 {
+        self.envelope.duration(self.hold)
+    }
+
+    /// Generate the sound's mono samples, at `sample_rate` samples per second.
+    pub fn render(&self, sample_rate: u32) -> Vec<f32> {
+        let frames = (self.duration() * sample_rate as f32).ceil() as usize;
+        let mut oscillator = Oscillator::new(self.waveform, self.frequency, sample_rate);
+        let mut samples = Vec::with_capacity(frames);
+        for frame in 0..frames {
+            let time = frame as f32 / sample_rate as f32;
+            let octaves = self.slide * time + self.delta_slide * time * time / 2.0;
+            let mut frequency = self.frequency * octaves.exp2();
+            if self.arpeggio_time > 0.0 && time >= self.arpeggio_time {
+                frequency *= self.arpeggio;
+            }
+            if frequency < self.min_frequency {
+                break;
+            }
+            frequency *= 1.0 + self.vibrato_depth * (time * self.vibrato_speed * TAU).sin();
+            oscillator.set_frequency(frequency);
+            if let Waveform::Square { duty } = self.waveform {
+                let duty = (duty + self.duty_sweep * time).clamp(0.0, 1.0);
+                oscillator.set_waveform(Waveform::Square { duty });
+            }
+            let sample = oscillator.next().unwrap_or_default();
+            samples.push(sample * self.envelope.level(time, self.hold) * self.volume);
+        }
+        samples
+    }
+
+    /// Generate the sound at `sample_rate` samples per second, ready to be played with
+    /// [`Audio::play`][super::Audio::play]. Returns a `String` describing the error if the sample
+    /// rate is 0.
+    pub fn to_sound(&self, sample_rate: u32) -> Result<Sound, String> {
+        if sample_rate == 0 {
+            return Err("The sample rate must be greater than 0".to_string());
+        }
+        Sound::from_samples(self.render(sample_rate), 1, sample_rate)
+    }
+}
+
+impl Default for SoundEffect {
+    /// A short 440 hertz square wave beep at half volume.
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Square { duty: 0.5 },
+            envelope: Envelope::new(0.0, 0.0, 1.0, 0.1),
+            hold: 0.1,
+            frequency: 440.0,
+            slide: 0.0,
+            delta_slide: 0.0,
+            min_frequency: 0.0,
+            vibrato_depth: 0.0,
+            vibrato_speed: 0.0,
+            arpeggio: 1.0,
+            arpeggio_time: 0.0,
+            duty_sweep: 0.0,
+            volume: 0.5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(waveform: Waveform) -> Vec<f32> {
+        Oscillator::new(waveform, 1.0, 8).take(9).collect()
+    }
+
+    /// Test the shape of each waveform.
+    #[test]
+    fn test_oscillator() {
+        let sine = wave(Waveform::Sine);
+        assert_eq!(sine[0], 0.0);
+        assert_eq!(sine[2], 1.0);
+        assert!((sine[6] + 1.0).abs() < 1e-6);
+        assert_eq!(
+            wave(Waveform::Square { duty: 0.25 }),
+            [1.0, 1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, 1.0]
+        );
+        assert_eq!(
+            wave(Waveform::Saw),
+            [-1.0, -0.75, -0.5, -0.25, 0.0, 0.25, 0.5, 0.75, -1.0]
+        );
+        assert_eq!(
+            wave(Waveform::Triangle),
+            [-1.0, -0.5, 0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0]
+        );
+        let noise = wave(Waveform::Noise);
+        assert_eq!(noise, wave(Waveform::Noise));
+        assert!(noise.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+        assert!(noise.windows(2).all(|pair| pair[0] != pair[1]));
+        // Changing frequency carries on from the same phase
+        let mut oscillator = Oscillator::new(Waveform::Saw, 2.0, 8);
+        oscillator.nth(1);
+        oscillator.set_frequency(1.0);
+        assert_eq!(oscillator.next(), Some(0.0));
+        assert_eq!(oscillator.next(), Some(0.25));
+    }
+
+    /// Test the volume of an envelope over time.
+    #[test]
+    fn test_envelope() {
+        let envelope = Envelope::new(1.0, 1.0, 0.5, 2.0);
+        let levels: Vec<_> = [0.0, 0.5, 1.0, 1.5, 2.0, 3.0, 4.0, 5.0, 6.0]
+            .iter()
+            .map(|&time| envelope.level(time, 4.0))
+            .collect();
+        assert_eq!(levels, [0.0, 0.5, 1.0, 0.75, 0.5, 0.5, 0.5, 0.25, 0.0]);
+        // Let go during the attack
+        assert_eq!(envelope.level(1.5, 0.5), 0.25);
+        assert_eq!(envelope.duration(4.0), 6.0);
+        assert_eq!(Envelope::default().level(0.0, 1.0), 1.0);
+        assert_eq!(Envelope::default().level(1.0, 1.0), 0.0);
+    }
+
+    /// Test rendering sound effects.
+    #[test]
+    fn test_sound_effect() {
+        let effect = SoundEffect {
+            envelope: Envelope::default(),
+            hold: 1.0,
+            frequency: 2.0,
+            volume: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            effect.render(8),
+            [0.5, 0.5, -0.5, -0.5, 0.5, 0.5, -0.5, -0.5]
+        );
+        let sound = effect.to_sound(8).unwrap();
+        assert_eq!(sound.frames(), 8);
+        assert!(effect.to_sound(0).is_err());
+
+        // Sliding below the minimum frequency stops early
+        let effect = SoundEffect {
+            slide: -1.0,
+            min_frequency: 1.0,
+            ..effect
+        };
+        assert_eq!(effect.render(8).len(), 8);
+        let effect = SoundEffect {
+            min_frequency: 1.5,
+            ..effect
+        };
+        assert_eq!(effect.render(8).len(), 4);
+
+        for preset in [
+            Preset::Pickup,
+            Preset::Laser,
+            Preset::Explosion,
+            Preset::PowerUp,
+            Preset::Hit,
+            Preset::Jump,
+            Preset::Blip,
+        ] {
+            let effect = SoundEffect::preset(preset, 7);
+            assert_eq!(effect, SoundEffect::preset(preset, 7));
+            assert_ne!(effect, SoundEffect::preset(preset, 8));
+            let samples = effect.render(44100);
+            assert!(samples.iter().any(|sample| sample.abs() > 0.1));
+            assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+        }
+    }
+}
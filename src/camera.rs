@@ -0,0 +1,262 @@
+//! Types for drawing in world coordinates rather than screen pixels.
+//!
+//! A [`Transform`] maps points from one coordinate space to another, and a [`Camera2D`] builds
+//! the transform from world coordinates to the screen. Push a transform onto a canvas with
+//! [`Canvas::push_transform`][crate::canvas::Canvas::push_transform], and everything drawn
+//! through the canvas is transformed until it's popped again.
+//! # Example
+//! ```no_run
+//! use simple_game_engine::{camera::Camera2D, prelude::*};
+//! # fn draw(canvas: &mut WindowCanvas, input: &InputState) -> Result<(), String> {
+//! let mut camera = Camera2D::new(Rect::new(0, 0, 640, 480));
+//! camera.position = (1000.0, 200.0);
+//! camera.zoom = 2.0;
+//!
+//! canvas.push_transform(camera.transform());
+//! // Drawn 2x as big, centered on the screen
+//! canvas.fill_rect(Rect::new(990, 190, 20, 20))?;
+//! canvas.pop_transform();
+//!
+//! // Where in the world is the mouse pointing?
+//! let (x, y) = input.mouse.world_position(&camera);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::ops::Mul;
+
+use sdl2::rect::{Point, Rect};
+
+/// A combination of a uniform scale, a rotation and a translation, applied in that order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    /// Amount to move points by, after scaling and rotating them.
+    pub translation: (f64, f64),
+    /// Amount to scale points by.
+    pub scale: f64,
+    /// Clockwise rotation, in degrees.
+    pub rotation: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    /// A transform that leaves everything where it is.
+    pub const IDENTITY: Self = Self {
+        translation: (0.0, 0.0),
+        scale: 1.0,
+        rotation: 0.0,
+    };
+
+    /// A transform that moves points by `(x, y)`.
+    pub fn translate(x: f64, y: f64) -> Self {
+        Self {
+            translation: (x, y),
+            ..Self::IDENTITY
+        }
+    }
+
+    /// A transform that scales points away from the origin.
+    pub fn scale(scale: f64) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// A transform that rotates points clockwise around the origin by `degrees`.
+    pub fn rotate(degrees: f64) -> Self {
+        Self {
+            rotation: degrees,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Apply this transform to a point.
+    pub fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        (
+            (x * cos - y * sin) * self.scale + self.translation.0,
+            (x * sin + y * cos) * self.scale + self.translation.1,
+        )
+    }
+
+    /// Apply this transform to a [`Point`], rounding to the nearest pixel.
+    pub fn apply_point(&self, point: Point) -> Point {
+        let (x, y) = self.apply((point.x() as f64, point.y() as f64));
+        Point::new(x.round() as i32, y.round() as i32)
+    }
+
+    /// Returns the transform that undoes this one.
+    pub fn inverse(&self) -> Self {
+        let inverse = Self::rotate(-self.rotation) * Self::scale(self.scale.recip());
+        let (x, y) = inverse.apply(self.translation);
+        Self {
+            translation: (-x, -y),
+            ..inverse
+        }
+    }
+
+    /// Returns `true` if this transform doesn't change anything.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Returns `true` if this transform keeps horizontal lines horizontal, so rectangles can be
+    /// drawn as rectangles rather than polygons.
+    pub fn is_axis_aligned(&self) -> bool {
+        self.rotation.rem_euclid(360.0) == 0.0
+    }
+
+    /// Apply this transform to a rectangle that [`is_axis_aligned`][Self::is_axis_aligned],
+    /// rounding to whole pixels.
+    pub(crate) fn apply_rect(&self, rect: Rect) -> Rect {
+        let (left, top) = self.apply((rect.left() as f64, rect.top() as f64));
+        let (right, bottom) = self.apply((rect.right() as f64, rect.bottom() as f64));
+        let (left, right) = (left.min(right).round(), left.max(right).round());
+        let (top, bottom) = (top.min(bottom).round(), top.max(bottom).round());
+        Rect::new(
+            left as i32,
+            top as i32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        )
+    }
+}
+
+impl Mul for Transform {
+    type Output = Self;
+
+    /// Combine two transforms, so that `(a * b).apply(p) == a.apply(b.apply(p))`.
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            translation: self.apply(rhs.translation),
+            scale: self.scale * rhs.scale,
+            rotation: self.rotation + rhs.rotation,
+        }
+    }
+}
+
+/// A camera looking at a 2D world, which determines what part of the world is shown in a
+/// viewport on the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera2D {
+    /// The point in the world shown at the center of the viewport.
+    pub position: (f64, f64),
+    /// How much to magnify the world by. Values above 1 zoom in, and values below 1 zoom out.
+    pub zoom: f64,
+    /// Clockwise rotation of the camera, in degrees. The world appears to turn the other way.
+    pub rotation: f64,
+    /// The area of the screen the camera draws to.
+    pub viewport: Rect,
+}
+
+impl Camera2D {
+    /// Create a camera drawing to `viewport`, positioned so that world coordinates match
+    /// screen coordinates until it's moved.
+    pub fn new(viewport: Rect) -> Self {
+        let center = viewport.center();
+        Self {
+            position: (center.x() as f64, center.y() as f64),
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport,
+        }
+    }
+
+    /// Returns the transform from world coordinates to screen coordinates.
+    pub fn transform(&self) -> Transform {
+        let center = self.viewport.center();
+        Transform::translate(center.x() as f64, center.y() as f64)
+            * Transform::rotate(-self.rotation)
+            * Transform::scale(self.zoom)
+            * Transform::translate(-self.position.0, -self.position.1)
+    }
+
+    /// Convert a point in the world to a point on the screen.
+    pub fn world_to_screen(&self, point: (f64, f64)) -> (f64, f64) {
+        self.transform().apply(point)
+    }
+
+    /// Convert a point on the screen, such as the mouse cursor's position, to a point in the
+    /// world.
+    pub fn screen_to_world(&self, point: (f64, f64)) -> (f64, f64) {
+        self.transform().inverse().apply(point)
+    }
+
+    /// Returns the smallest axis-aligned rectangle of the world that's visible through the
+    /// viewport. This is useful for skipping anything that's off screen, such as when drawing a
+    /// [`Tilemap`][crate::tilemap::Tilemap].
+    pub fn visible_area(&self) -> Rect {
+        let inverse = self.transform().inverse();
+        let v = self.viewport;
+        let corners = [
+            inverse.apply((v.left() as f64, v.top() as f64)),
+            inverse.apply((v.right() as f64, v.top() as f64)),
+            inverse.apply((v.left() as f64, v.bottom() as f64)),
+            inverse.apply((v.right() as f64, v.bottom() as f64)),
+        ];
+        let left = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+        let right = corners
+            .iter()
+            .map(|c| c.0)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let top = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+        let bottom = corners
+            .iter()
+            .map(|c| c.1)
+            .fold(f64::NEG_INFINITY, f64::max);
+        Rect::new(
+            left.floor() as i32,
+            top.floor() as i32,
+            (right.ceil() - left.floor()) as u32,
+            (bottom.ceil() - top.floor()) as u32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    /// Test combining and inverting transforms.
+    #[test]
+    fn test_transform() {
+        let t = Transform::translate(10.0, 5.0) * Transform::rotate(90.0) * Transform::scale(2.0);
+        // (1, 0) -> (2, 0) -> (0, 2) -> (10, 7)
+        assert_close(t.apply((1.0, 0.0)), (10.0, 7.0));
+        assert_close(t.inverse().apply((10.0, 7.0)), (1.0, 0.0));
+        assert!((t * t.inverse()).translation.0.abs() < 1e-9);
+        assert!(!t.is_axis_aligned());
+        assert!(Transform::default().is_identity());
+    }
+
+    /// Test converting between screen and world coordinates.
+    #[test]
+    fn test_camera() {
+        let mut camera = Camera2D::new(Rect::new(0, 0, 200, 100));
+        assert!(camera.transform().is_identity());
+        camera.position = (1000.0, 1000.0);
+        camera.zoom = 2.0;
+        assert_close(camera.world_to_screen((1000.0, 1000.0)), (100.0, 50.0));
+        assert_close(camera.world_to_screen((1010.0, 1000.0)), (120.0, 50.0));
+        assert_close(camera.screen_to_world((0.0, 0.0)), (950.0, 975.0));
+        assert_eq!(camera.visible_area(), Rect::new(950, 975, 100, 50));
+        camera.rotation = 90.0;
+        // Turning the camera clockwise makes things to its right appear above the center
+        assert_close(camera.world_to_screen((1010.0, 1000.0)), (100.0, 30.0));
+    }
+}
@@ -0,0 +1,621 @@
+//! Bitmap fonts, loaded from BDF files, PSF console fonts, and font sheet images.
+
+use std::{cell::RefCell, rc::Rc};
+
+use fnv::FnvHashMap;
+use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+
+use super::font::{Font, FontKind, Glyph};
+
+/// Magic numbers at the start of PSF version 1 and 2 files.
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+
+/// A font made of glyphs with one bit per pixel, which is only scaled by whole numbers.
+#[derive(Clone)]
+pub(super) struct BitmapFont {
+    /// Every glyph, at its natural size, shared between clones of the font.
+    glyphs: Rc<FnvHashMap<char, Glyph>>,
+    line_height: u32,
+    baseline: u32,
+    scale: u32,
+    /// Glyphs scaled to the current size.
+    cache: RefCell<FnvHashMap<char, Rc<Glyph>>>,
+}
+
+impl BitmapFont {
+    /// Create a font from glyphs at their natural size.
+    fn new(glyphs: FnvHashMap<char, Glyph>, line_height: u32, baseline: u32) -> Self {
+        Self {
+            glyphs: Rc::new(glyphs),
+            line_height,
+            baseline,
+            scale: 1,
+            cache: RefCell::new(FnvHashMap::default()),
+        }
+    }
+
+    /// Returns the height of a line of text, in pixels.
+    pub(super) fn size(&self) -> u32 {
+        self.line_height()
+    }
+
+    /// Scale the font by the whole number that makes lines closest to `size` pixels tall,
+    /// emptying the glyph cache.
+    pub(super) fn set_size(&mut self, size: f32) {
+        self.scale = (size / self.line_height.max(1) as f32).round().max(1.0) as u32;
+        self.cache.get_mut().clear();
+    }
+
+    /// Returns the height of a line of text, in pixels.
+    pub(super) fn line_height(&self) -> u32 {
+        self.line_height * self.scale
+    }
+
+    /// Returns the distance from the top of a line to the baseline.
+    pub(super) fn baseline(&self) -> u32 {
+        self.baseline * self.scale
+    }
+
+    /// Returns the glyph for `c` at the current size, or `None` if the font has no glyph for
+    /// it.
+    pub(super) fn glyph(&self, c: char) -> Option<Rc<Glyph>> {
+        if let Some(glyph) = self.cache.borrow().get(&c) {
+            return Some(Rc::clone(glyph));
+        }
+        let glyph = Rc::new(scale_glyph(self.glyphs.get(&c)?, self.scale));
+        self.cache.borrow_mut().insert(c, Rc::clone(&glyph));
+        Some(glyph)
+    }
+
+    /// Returns the kerning between two glyphs, which bitmap fonts don't have.
+    pub(super) fn kern(&self, _first: &Glyph, _second: &Glyph) -> f32 {
+        0.0
+    }
+
+    /// Wrap the font in a [`Font`], falling back to `default`, or `?`, for missing glyphs.
+    fn into_font(self, default: Option<char>) -> Font {
+        let fallback = default
+            .filter(|c| self.glyphs.contains_key(c))
+            .or_else(|| Some('?').filter(|c| self.glyphs.contains_key(c)));
+        Font::new(FontKind::Bitmap(self), fallback)
+    }
+}
+
+impl Font {
+    /// Load a bitmap font from the contents of a BDF (Glyph Bitmap Distribution Format) file.
+    /// Each glyph's own advance width and bounding box are used, so proportional fonts are
+    /// spaced correctly. Missing characters fall back to the font's `DEFAULT_CHAR`, or `?`.
+    /// Returns a `String` describing the error if the font can't be parsed.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{canvas::Font, prelude::*};
+    /// # fn load(canvas: &mut WindowCanvas) -> Result<(), String> {
+    /// let bdf = std::fs::read_to_string("assets/tamzen.bdf").map_err(|e| e.to_string())?;
+    /// canvas.set_font(Some(Font::from_bdf(&bdf)?));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bdf(bdf: &str) -> Result<Self, String> {
+        let mut lines = bdf.lines().enumerate().map(|(i, line)| (i + 1, line));
+        match lines.next() {
+            Some((_, line)) if line.starts_with("STARTFONT") => {}
+            _ => return Err("Not a BDF font".to_string()),
+        }
+        let mut glyphs = FnvHashMap::default();
+        let mut bounding_box = None;
+        let (mut ascent, mut descent, mut default) = (None, None, None);
+        while let Some((number, line)) = lines.next() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("FONTBOUNDINGBOX") => bounding_box = Some(bdf_numbers::<4>(number, words)?),
+                Some("FONT_ASCENT") => ascent = Some(bdf_numbers::<1>(number, words)?[0]),
+                Some("FONT_DESCENT") => descent = Some(bdf_numbers::<1>(number, words)?[0]),
+                Some("DEFAULT_CHAR") => {
+                    default = char::from_u32(bdf_numbers::<1>(number, words)?[0] as u32)
+                }
+                Some("STARTCHAR") => {
+                    if let Some((c, glyph)) = bdf_glyph(&mut lines, bounding_box)? {
+                        glyphs.insert(c, glyph);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Fonts without ascent and descent properties fit in their bounding box
+        let [_, height, _, y_offset] = bounding_box.unwrap_or([0, 0, 0, 0]);
+        let ascent = ascent.unwrap_or(height + y_offset).max(0) as u32;
+        let descent = descent.unwrap_or(-y_offset).max(0) as u32;
+        Ok(BitmapFont::new(glyphs, ascent + descent, ascent).into_font(default))
+    }
+
+    /// Load a bitmap font from the contents of a PSF (PC Screen Font) file, version 1 or 2, as
+    /// used for the Linux console. Characters are mapped to glyphs with the font's Unicode
+    /// table, or glyph `n` is character `n` if it doesn't have one. Every glyph is the same
+    /// width, and PSF fonts don't say where their baseline is, so it's assumed to be an eighth of
+    /// the way up from the bottom. Missing characters fall back to `?`. Returns a `String`
+    /// describing the error if the font can't be parsed.
+    pub fn from_psf(psf: &[u8]) -> Result<Self, String> {
+        let truncated = || "PSF font is truncated".to_string();
+        let empty = || "PSF glyphs have no size".to_string();
+        let (glyph_count, bytes_per_glyph, width, height, glyph_data, table) =
+            if psf.starts_with(&PSF2_MAGIC) {
+                let header = |i: usize| -> Result<usize, String> {
+                    let bytes = psf.get(i * 4..i * 4 + 4).ok_or_else(truncated)?;
+                    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+                };
+                let (header_size, flags, count) = (header(2)?, header(3)?, header(4)?);
+                let (bytes_per_glyph, height, width) = (header(5)?, header(6)?, header(7)?);
+                if bytes_per_glyph == 0 || height == 0 || width == 0 {
+                    return Err(empty());
+                }
+                // The header can't be trusted, so the count is capped by the glyphs that fit
+                let available = psf.len().saturating_sub(header_size) / bytes_per_glyph;
+                if count > available {
+                    return Err(truncated());
+                }
+                let end = count
+                    .checked_mul(bytes_per_glyph)
+                    .and_then(|size| size.checked_add(header_size))
+                    .ok_or_else(truncated)?;
+                let data = psf.get(header_size..end).ok_or_else(truncated)?;
+                let table = if flags & 1 != 0 {
+                    Some(psf2_table(&psf[end..], count))
+                } else {
+                    None
+                };
+                (count, bytes_per_glyph, width, height, data, table)
+            } else if psf.starts_with(&PSF1_MAGIC) {
+                let (mode, height) = (*psf.get(2).ok_or_else(truncated)?, psf.get(3));
+                let height = *height.ok_or_else(truncated)? as usize;
+                if height == 0 {
+                    return Err(empty());
+                }
+                let count = if mode & 1 != 0 { 512 } else { 256 };
+                let end = 4 + count * height;
+                let data = psf.get(4..end).ok_or_else(truncated)?;
+                let table = if mode & 6 != 0 {
+                    Some(psf1_table(&psf[end..], count))
+                } else {
+                    None
+                };
+                (count, height, 8, height, data, table)
+            } else {
+                return Err("Not a PSF font".to_string());
+            };
+
+        let row_bytes = width.div_ceil(8);
+        let glyph_size = row_bytes.checked_mul(height);
+        if glyph_size.filter(|&size| size <= bytes_per_glyph).is_none() {
+            return Err("PSF glyphs are too small for their size".to_string());
+        }
+        let baseline = (height - height / 8) as u32;
+        let glyph = |index: usize| {
+            let bits = &glyph_data[index * bytes_per_glyph..];
+            bitmap_glyph(width as u32, height as u32, |x, y| {
+                bits[y as usize * row_bytes + x as usize / 8] & (0x80 >> (x % 8)) != 0
+            })
+            .offset(0, -(baseline as i32), width as f32)
+        };
+        let mut glyphs = FnvHashMap::default();
+        match table {
+            Some(table) => {
+                for (c, index) in table {
+                    glyphs.insert(c, glyph(index));
+                }
+            }
+            None => {
+                for index in 0..glyph_count {
+                    if let Some(c) = char::from_u32(index as u32) {
+                        glyphs.insert(c, glyph(index));
+                    }
+                }
+            }
+        }
+        Ok(BitmapFont::new(glyphs, height as u32, baseline).into_font(None))
+    }
+
+    /// Create a bitmap font from a font sheet: an image divided into a grid of `cell_width` by
+    /// `cell_height` cells, with one character in each. Cells are read from left to right, then
+    /// top to bottom, and hold consecutive characters starting with `first`. Pixels that are
+    /// both bright and opaque are part of a glyph, so light text on either a dark or a
+    /// transparent background works.
+    ///
+    /// If `monospace` is `true`, every character is as wide as a cell. Otherwise, glyphs are
+    /// trimmed to their visible pixels with one pixel of space after them, and empty cells, such
+    /// as a space, are half a cell wide. The baseline is assumed to be an eighth of the way up
+    /// from the bottom of a cell. Missing characters fall back to `?`. Returns a `String`
+    /// describing the error if sdl fails.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{canvas::Font, prelude::*};
+    /// # use sdl2::surface::Surface;
+    /// # fn load(canvas: &mut WindowCanvas) -> Result<(), String> {
+    /// // Printable ASCII, 16 characters to a row
+    /// let sheet = Surface::load_bmp("assets/font.bmp")?;
+    /// canvas.set_font(Some(Font::from_font_sheet(&sheet, 8, 8, ' ', false)?));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_font_sheet(
+        sheet: &Surface,
+        cell_width: u32,
+        cell_height: u32,
+        first: char,
+        monospace: bool,
+    ) -> Result<Self, String> {
+        let sheet = sheet.convert_format(PixelFormatEnum::RGBA32)?;
+        let pitch = sheet.pitch() as usize;
+        let pixels = sheet.without_lock().ok_or("Font sheet needs locking")?;
+        let lit = |x: u32, y: u32| {
+            let offset = y as usize * pitch + x as usize * 4;
+            let [r, g, b, a] = [0, 1, 2, 3].map(|i| pixels[offset + i]);
+            a >= 128 && r.max(g).max(b) >= 128
+        };
+
+        let mut glyphs = FnvHashMap::default();
+        let baseline = cell_height - cell_height / 8;
+        let (columns, rows) = match (cell_width, cell_height) {
+            (0, _) | (_, 0) => (0, 0),
+            _ => (sheet.width() / cell_width, sheet.height() / cell_height),
+        };
+        let cells = (0..rows).flat_map(|row| (0..columns).map(move |col| (col, row)));
+        for (i, (col, row)) in cells.enumerate() {
+            let c = match char::from_u32(first as u32 + i as u32) {
+                Some(c) => c,
+                None => continue,
+            };
+            let (left, top) = (col * cell_width, row * cell_height);
+            let cell_lit = |x, y| lit(left + x, top + y);
+            let glyph = bitmap_glyph(cell_width, cell_height, cell_lit);
+            let visible = (0..cell_width).filter(|&x| (0..cell_height).any(|y| cell_lit(x, y)));
+            let (min, max) = (visible.clone().min(), visible.max());
+            let glyph = match (monospace, min, max) {
+                (true, _, _) => glyph.offset(0, -(baseline as i32), cell_width as f32),
+                (false, Some(min), Some(max)) => {
+                    let width = max - min + 1;
+                    bitmap_glyph(width, cell_height, |x, y| cell_lit(min + x, y)).offset(
+                        0,
+                        -(baseline as i32),
+                        (width + 1) as f32,
+                    )
+                }
+                (false, _, _) => {
+                    bitmap_glyph(0, 0, |_, _| false).offset(0, 0, (cell_width / 2) as f32)
+                }
+            };
+            glyphs.insert(c, glyph);
+        }
+        Ok(BitmapFont::new(glyphs, cell_height, baseline).into_font(None))
+    }
+}
+
+impl Glyph {
+    /// Move the glyph's bitmap to `(left, top)` relative to its origin, and set its advance.
+    fn offset(self, left: i32, top: i32, advance: f32) -> Self {
+        Self {
+            left,
+            top,
+            advance,
+            ..self
+        }
+    }
+}
+
+/// Create a glyph from a bitmap, where `lit` returns `true` for each pixel that's part of the
+/// glyph.
+fn bitmap_glyph<F: Fn(u32, u32) -> bool>(width: u32, height: u32, lit: F) -> Glyph {
+    let coverage = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| if lit(x, y) { 255 } else { 0 })
+        .collect();
+    Glyph {
+        id: 0,
+        advance: width as f32,
+        left: 0,
+        top: 0,
+        width,
+        height,
+        coverage,
+    }
+}
+
+/// Returns a copy of a glyph, scaled up by a whole number.
+fn scale_glyph(glyph: &Glyph, scale: u32) -> Glyph {
+    let width = glyph.width * scale;
+    let coverage = (0..glyph.height * scale)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| glyph.coverage[((y / scale) * glyph.width + x / scale) as usize])
+        .collect();
+    Glyph {
+        id: glyph.id,
+        advance: glyph.advance * scale as f32,
+        left: glyph.left * scale as i32,
+        top: glyph.top * scale as i32,
+        width,
+        height: glyph.height * scale,
+        coverage,
+    }
+}
+
+/// Parse `N` numbers from the rest of a line of a BDF file.
+fn bdf_numbers<'a, const N: usize>(
+    number: usize,
+    words: impl Iterator<Item = &'a str>,
+) -> Result<[i32; N], String> {
+    let mut numbers = [0; N];
+    let mut words = words;
+    for n in numbers.iter_mut() {
+        let word = words
+            .next()
+            .ok_or_else(|| format!("Line {}: expected {} numbers", number, N))?;
+        *n = word
+            .parse()
+            .map_err(|_| format!("Line {}: invalid number {:?}", number, word))?;
+    }
+    Ok(numbers)
+}
+
+/// Parse a glyph from a BDF file, after its `STARTCHAR` line. Returns `None` for glyphs that
+/// aren't mapped to a character.
+fn bdf_glyph<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    bounding_box: Option<[i32; 4]>,
+) -> Result<Option<(char, Glyph)>, String> {
+    let mut c = None;
+    let mut advance = None;
+    let mut bounding_box = bounding_box.unwrap_or([0, 0, 0, 0]);
+    let mut rows: Vec<&str> = Vec::new();
+    let mut in_bitmap = false;
+    for (number, line) in lines {
+        let mut words = line.split_whitespace();
+        let keyword = words.next();
+        if keyword == Some("ENDCHAR") {
+            let [width, height, x_offset, y_offset] = bounding_box;
+            let (width, height) = (width.max(0) as u32, height.max(0) as u32);
+            let lit = |x: u32, y: u32| {
+                let byte = rows
+                    .get(y as usize)
+                    .and_then(|row| row.get(x as usize / 8 * 2..x as usize / 8 * 2 + 2))
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .unwrap_or(0);
+                byte & (0x80 >> (x % 8)) != 0
+            };
+            let advance = advance.unwrap_or(width as i32) as f32;
+            let glyph = bitmap_glyph(width, height, lit).offset(
+                x_offset,
+                -(y_offset + height as i32),
+                advance,
+            );
+            return Ok(c.map(|c| (c, glyph)));
+        }
+        if in_bitmap {
+            rows.push(line.trim());
+            continue;
+        }
+        match keyword {
+            Some("ENCODING") => {
+                let codes: Vec<i64> = words.filter_map(|w| w.parse().ok()).collect();
+                // Unmapped glyphs are -1, optionally followed by a code in another encoding
+                c = match codes[..] {
+                    [code, ..] if code >= 0 => char::from_u32(code as u32),
+                    _ => None,
+                };
+            }
+            Some("DWIDTH") => advance = Some(bdf_numbers::<2>(number, words)?[0]),
+            Some("BBX") => bounding_box = bdf_numbers::<4>(number, words)?,
+            Some("BITMAP") => in_bitmap = true,
+            _ => {}
+        }
+    }
+    Err("BDF glyph has no ENDCHAR".to_string())
+}
+
+/// Parse the Unicode table of a PSF version 1 font, mapping characters to glyph indices.
+fn psf1_table(table: &[u8], glyph_count: usize) -> Vec<(char, usize)> {
+    let mut mapping = Vec::new();
+    let mut values = table
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+    for index in 0..glyph_count {
+        let mut in_sequence = false;
+        for value in values.by_ref() {
+            match value {
+                0xffff => break,
+                // Sequences of combining characters aren't supported
+                0xfffe => in_sequence = true,
+                _ if in_sequence => {}
+                _ => mapping.extend(char::from_u32(value as u32).map(|c| (c, index))),
+            }
+        }
+    }
+    mapping
+}
+
+/// Parse the Unicode table of a PSF version 2 font, mapping characters to glyph indices.
+fn psf2_table(table: &[u8], glyph_count: usize) -> Vec<(char, usize)> {
+    let mut mapping = Vec::new();
+    let mut entries = table.split(|&b| b == 0xff);
+    for index in 0..glyph_count {
+        let entry = match entries.next() {
+            Some(entry) => entry,
+            None => break,
+        };
+        // Sequences of combining characters aren't supported
+        let single = entry.split(|&b| b == 0xfe).next().unwrap_or(&[]);
+        let chars = String::from_utf8_lossy(single);
+        mapping.extend(
+            chars
+                .chars()
+                .filter(|&c| c != char::REPLACEMENT_CHARACTER)
+                .map(|c| (c, index)),
+        );
+    }
+    mapping
+}
+
+/// A tiny BDF font for tests, with a space, `A` and `?`.
+#[cfg(test)]
+pub(super) const TEST_BDF: &str = "STARTFONT 2.1
+FONT -test-tiny
+SIZE 6 75 75
+FONTBOUNDINGBOX 4 6 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 5
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 3
+STARTCHAR space
+ENCODING 32
+DWIDTH 3 0
+BBX 1 1 0 0
+BITMAP
+00
+ENDCHAR
+STARTCHAR A
+ENCODING 65
+DWIDTH 5 0
+BBX 4 5 0 0
+BITMAP
+60
+90
+F0
+90
+90
+ENDCHAR
+STARTCHAR question
+ENCODING 63
+DWIDTH 4 0
+BBX 3 6 0 -1
+BITMAP
+E0
+20
+40
+00
+40
+00
+ENDCHAR
+ENDFONT
+";
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::Color, rect::Rect};
+
+    use super::*;
+
+    /// Returns which pixels of a line of text are lit, as a string per row.
+    fn lit_rows(font: &Font, text: &str) -> Vec<String> {
+        let surface = font.draw(text, Color::WHITE, false).unwrap();
+        let surface = surface.convert_format(PixelFormatEnum::RGBA32).unwrap();
+        let (width, pitch) = (surface.width() as usize, surface.pitch() as usize);
+        let pixels = surface.without_lock().unwrap();
+        pixels
+            .chunks(pitch)
+            .map(|row| {
+                (0..width)
+                    .map(|x| if row[x * 4 + 3] > 0 { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Test loading a BDF font, with per-glyph advances and a fallback glyph.
+    #[test]
+    fn test_bdf() {
+        assert!(Font::from_bdf("STARTCHAR A").is_err());
+        let mut font = Font::from_bdf(TEST_BDF).unwrap();
+        assert_eq!((font.line_height(), font.baseline()), (6, 5));
+        assert_eq!(font.fallback(), Some('?'));
+        assert_eq!(font.measure_width("A A", false), Ok(13));
+        // Missing characters are drawn as a question mark
+        assert_eq!(font.measure_width("é", false), Ok(4));
+        assert_eq!(
+            lit_rows(&font, "A?"),
+            vec![
+                ".##..###.",
+                "#..#...#.",
+                "####..#..",
+                "#..#.....",
+                "#..#..#..",
+                ".........",
+            ]
+        );
+        font.set_fallback(None);
+        assert!(font.measure_width("é", false).is_err());
+
+        font.set_size(13.0);
+        assert_eq!(
+            (font.size(), font.line_height(), font.baseline()),
+            (12.0, 12, 10)
+        );
+        assert_eq!(font.measure_width("A", false), Ok(10));
+        assert_eq!(&lit_rows(&font, "A")[..2], ["..####....", "..####...."]);
+    }
+
+    /// Test loading PSF version 1 and 2 fonts.
+    #[test]
+    fn test_psf() {
+        assert!(Font::from_psf(&[1, 2, 3]).is_err());
+        // Version 1, with 256 glyphs 2 pixels tall and no Unicode table
+        let mut psf1 = vec![0x36, 0x04, 0, 2];
+        psf1.extend((0..256).flat_map(|i| [i as u8, 0]));
+        let font = Font::from_psf(&psf1).unwrap();
+        assert_eq!((font.line_height(), font.baseline()), (2, 2));
+        assert_eq!(lit_rows(&font, "\u{81}"), vec!["#......#", "........"]);
+
+        // Version 2, with two 3x2 glyphs, for "a" and both "b" and "c"
+        let mut psf2 = PSF2_MAGIC.to_vec();
+        for value in [0u32, 32, 1, 2, 2, 2, 3] {
+            psf2.extend(value.to_le_bytes());
+        }
+        psf2.extend([0b1010_0000, 0b0100_0000, 0b1110_0000, 0]);
+        psf2.extend(b"a\xff");
+        psf2.extend(b"bc\xfec\xcc\x81\xff");
+        let font = Font::from_psf(&psf2).unwrap();
+        assert_eq!(font.fallback(), None);
+        assert!(font.has_glyph('c') && !font.has_glyph('?'));
+        assert_eq!(font.measure_width("abc", false), Ok(9));
+        assert_eq!(lit_rows(&font, "ab"), vec!["#.####", ".#...."]);
+
+        // Headers with empty glyphs, or more glyphs than the file holds, are rejected
+        let header = |values: [u32; 7]| {
+            let mut psf = PSF2_MAGIC.to_vec();
+            psf.extend(values.iter().flat_map(|value| value.to_le_bytes()));
+            psf
+        };
+        let empty = header([0, 32, 0, u32::MAX, 0, 0, 0]);
+        assert_eq!(
+            Font::from_psf(&empty).err().as_deref(),
+            Some("PSF glyphs have no size")
+        );
+        let mut huge = header([0, 32, 0, u32::MAX, 2, 2, 3]);
+        huge.extend([0; 4]);
+        assert_eq!(
+            Font::from_psf(&huge).err().as_deref(),
+            Some("PSF font is truncated")
+        );
+        let wide = header([0, 32, 0, 1, 1, u32::MAX, u32::MAX]);
+        assert!(Font::from_psf(&[wide, vec![0]].concat()).is_err());
+    }
+
+    /// Test slicing a font sheet into proportional and monospaced glyphs.
+    #[test]
+    fn test_font_sheet() {
+        // A space, then a 2 pixel wide "!" on a black background
+        let mut sheet = Surface::new(8, 4, PixelFormatEnum::RGBA8888).unwrap();
+        sheet.fill_rect(None, Color::BLACK).unwrap();
+        sheet
+            .fill_rect(Rect::new(5, 0, 2, 3), Color::WHITE)
+            .unwrap();
+        let font = Font::from_font_sheet(&sheet, 4, 4, ' ', false).unwrap();
+        assert_eq!((font.line_height(), font.baseline()), (4, 4));
+        assert_eq!(font.measure_width(" ", false), Ok(2));
+        assert_eq!(font.measure_width("!", false), Ok(3));
+        assert_eq!(lit_rows(&font, "!"), vec!["##.", "##.", "##.", "..."]);
+
+        let font = Font::from_font_sheet(&sheet, 4, 4, ' ', true).unwrap();
+        assert_eq!(font.measure_width(" !", false), Ok(8));
+        assert_eq!(lit_rows(&font, "!")[0], ".##.");
+    }
+}
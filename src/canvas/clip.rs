@@ -0,0 +1,131 @@
+//! A stack of clipping rectangles, for nested UI panels and split-screen views.
+
+use std::ops::{Deref, DerefMut};
+
+use sdl2::{rect::Rect, render::RenderTarget};
+
+use super::Canvas;
+
+/// sdl2 rectangles can't be empty, so clipping everything away is done with a rectangle just
+/// off the top-left of the render target.
+pub(super) fn empty_clip() -> Rect {
+    Rect::new(-1, -1, 1, 1)
+}
+
+/// Keeps a clipping rectangle pushed onto a [`Canvas`] until it's dropped. Created with
+/// [`Canvas::scoped_clip`], and dereferences to the canvas so it can be drawn to as normal.
+pub struct ClipGuard<'a, T: RenderTarget, U> {
+    canvas: &'a mut Canvas<T, U>,
+}
+
+impl<T: RenderTarget, U> Deref for ClipGuard<'_, T, U> {
+    type Target = Canvas<T, U>;
+
+    fn deref(&self) -> &Self::Target {
+        self.canvas
+    }
+}
+
+impl<T: RenderTarget, U> DerefMut for ClipGuard<'_, T, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.canvas
+    }
+}
+
+impl<T: RenderTarget, U> Drop for ClipGuard<'_, T, U> {
+    fn drop(&mut self) {
+        self.canvas.pop_clip();
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns the area everything is currently clipped to, in screen coordinates, or `None` if
+    /// nothing is clipped.
+    pub fn clip(&self) -> Option<Rect> {
+        self.clip
+    }
+
+    /// Only allow drawing inside `rect` (in screen coordinates) and inside any clipping
+    /// rectangles that are already pushed, until [`pop_clip`][Self::pop_clip] is called. This
+    /// applies to everything drawn to the canvas, including text and textures.
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.clip_stack.push(self.clip);
+        let clip = match self.clip {
+            Some(clip) => clip.intersection(rect).unwrap_or_else(empty_clip),
+            None => rect,
+        };
+        self.clip = Some(clip);
+        self.apply_clip();
+    }
+
+    /// Restore the clipping rectangle that was active before the last call to
+    /// [`push_clip`][Self::push_clip]. Does nothing if there's nothing to pop.
+    pub fn pop_clip(&mut self) {
+        if let Some(clip) = self.clip_stack.pop() {
+            self.clip = clip;
+            self.apply_clip();
+        }
+    }
+
+    /// Push a clipping rectangle, call `f`, then pop it again.
+    pub fn with_clip<F, R>(&mut self, rect: Rect, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.push_clip(rect);
+        let result = f(self);
+        self.pop_clip();
+        result
+    }
+
+    /// Push a clipping rectangle, which is popped again when the returned guard is dropped.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::prelude::*;
+    /// # fn draw(canvas: &mut WindowCanvas) -> Result<(), String> {
+    /// let mut panel = canvas.scoped_clip(Rect::new(10, 10, 100, 50));
+    /// // Only the part inside the panel is drawn
+    /// panel.fill_circle((60, 10), 40)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scoped_clip(&mut self, rect: Rect) -> ClipGuard<'_, T, U> {
+        self.push_clip(rect);
+        ClipGuard { canvas: self }
+    }
+
+    /// Tell sdl about the current clipping rectangle. sdl keeps a separate clipping rectangle
+    /// for each render target, so this is also needed after switching layers.
+    pub(super) fn apply_clip(&mut self) {
+        self.inner.set_clip_rect(self.clip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+
+    use super::*;
+    use crate::canvas::SurfaceCanvas;
+
+    /// Test nested clipping rectangles intersect, and are restored when popped.
+    #[test]
+    fn test_clip_stack() {
+        let surface = Surface::new(64, 64, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        assert_eq!(canvas.clip(), None);
+        canvas.push_clip(Rect::new(0, 0, 32, 32));
+        {
+            let mut inner = canvas.scoped_clip(Rect::new(16, 16, 32, 32));
+            assert_eq!(inner.clip(), Some(Rect::new(16, 16, 16, 16)));
+            inner.with_clip(Rect::new(40, 40, 8, 8), |canvas| {
+                assert_eq!(canvas.clip(), Some(empty_clip()));
+            });
+            assert_eq!(inner.clip_rect(), Some(Rect::new(16, 16, 16, 16)));
+        }
+        assert_eq!(canvas.clip(), Some(Rect::new(0, 0, 32, 32)));
+        canvas.pop_clip();
+        canvas.pop_clip();
+        assert_eq!(canvas.clip(), None);
+    }
+}
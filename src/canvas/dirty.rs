@@ -0,0 +1,169 @@
+//! Retained mode, where the screen keeps its contents between frames, and tracking which parts
+//! of it are dirty and need redrawing.
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    render::{BlendMode, RenderTarget},
+};
+
+use super::{clip::empty_clip, Canvas, LayerTexture};
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns `true` if the canvas is in retained mode. See [`set_retained`][Self::set_retained].
+    pub fn is_retained(&self) -> bool {
+        self.retained.is_some()
+    }
+
+    /// Turn retained mode on or off. Normally, the screen's contents are lost every time it's
+    /// presented, so the whole screen has to be redrawn every frame. In retained mode, drawing
+    /// to the screen goes to a texture that keeps its contents, which is copied to the screen by
+    /// [`composite_layers`][Self::composite_layers], so only the parts that change need
+    /// redrawing. Mark them with [`mark_dirty`][Self::mark_dirty].
+    ///
+    /// Turning retained mode on clears the screen to black and marks all of it dirty. Returns a
+    /// `String` describing the error if sdl fails.
+    pub fn set_retained(&mut self, retained: bool) -> Result<(), String> {
+        if retained == self.is_retained() {
+            return Ok(());
+        }
+        if !retained {
+            // Stop drawing to the texture before destroying it
+            let texture = self.retained.take();
+            let result = self.set_draw_layer_index(self.draw_layer);
+            drop(texture);
+            return result;
+        }
+        let (width, height) = self.inner.output_size()?;
+        let mut texture = self
+            .texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA8888, width, height)
+            .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::None);
+        self.retained = Some(LayerTexture::new(texture));
+        self.with_layer_index(None, |canvas| {
+            let color = canvas.inner.draw_color();
+            canvas.inner.set_draw_color(Color::BLACK);
+            canvas.inner.clear();
+            canvas.inner.set_draw_color(color);
+        })?;
+        self.mark_all_dirty()
+    }
+
+    /// Mark part of the screen, in screen coordinates, as needing to be redrawn and shown.
+    /// Overlapping rectangles are merged together.
+    pub fn mark_dirty(&mut self, rect: Rect) {
+        let mut rect = rect;
+        while let Some(i) = self.dirty.iter().position(|r| r.has_intersection(rect)) {
+            rect = rect.union(self.dirty.swap_remove(i));
+        }
+        self.dirty.push(rect);
+    }
+
+    /// Mark the whole screen as needing to be redrawn and shown. Returns a `String` describing
+    /// the error if sdl fails.
+    pub fn mark_all_dirty(&mut self) -> Result<(), String> {
+        let (width, height) = self.inner.output_size()?;
+        self.dirty.clear();
+        self.dirty.push(Rect::new(0, 0, width, height));
+        Ok(())
+    }
+
+    /// Returns `true` if any part of the screen has been marked dirty since it was last
+    /// presented.
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Returns the parts of the screen that have been marked dirty since it was last presented.
+    /// None of them overlap.
+    pub fn dirty_rects(&self) -> &[Rect] {
+        &self.dirty
+    }
+
+    /// Returns the smallest rectangle containing every dirty part of the screen, or `None` if
+    /// nothing is dirty.
+    pub fn dirty_bounds(&self) -> Option<Rect> {
+        self.dirty.iter().copied().reduce(|a, b| a.union(b))
+    }
+
+    /// Mark the whole screen as clean, without presenting it.
+    pub fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+
+    /// Clip drawing to the [dirty bounds][Self::dirty_bounds], call `f`, then remove the clip
+    /// again. If nothing is dirty, nothing is drawn. This makes it cheap to redraw a whole scene
+    /// when only a small part of it has changed.
+    pub fn with_dirty_clip<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let bounds = self.dirty_bounds().unwrap_or_else(empty_clip);
+        self.with_clip(bounds, f)
+    }
+
+    /// Show everything drawn since the last call on the screen, and mark the whole screen as
+    /// clean. In retained mode, drawing goes back to the retained screen afterwards.
+    pub fn present(&mut self) {
+        self.inner.present();
+        self.dirty.clear();
+        if self.is_retained() {
+            // Only fails if the renderer is broken, in which case the next frame will fail too
+            self.set_draw_layer_index(self.draw_layer).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::surface::Surface;
+
+    use super::*;
+    use crate::canvas::SurfaceCanvas;
+
+    /// Test overlapping dirty rectangles are merged.
+    #[test]
+    fn test_mark_dirty() {
+        let surface = Surface::new(64, 64, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        assert!(!canvas.is_dirty());
+        canvas.mark_dirty(Rect::new(0, 0, 10, 10));
+        canvas.mark_dirty(Rect::new(20, 20, 10, 10));
+        canvas.mark_dirty(Rect::new(5, 5, 20, 4));
+        assert_eq!(
+            canvas.dirty_rects(),
+            &[Rect::new(20, 20, 10, 10), Rect::new(0, 0, 25, 10)]
+        );
+        assert_eq!(canvas.dirty_bounds(), Some(Rect::new(0, 0, 30, 30)));
+        canvas.present();
+        assert_eq!(canvas.dirty_bounds(), None);
+    }
+
+    /// Test the retained screen keeps its contents after being presented.
+    #[test]
+    fn test_retained() {
+        let surface = Surface::new(4, 4, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_retained(true).unwrap();
+        assert_eq!(canvas.dirty_rects(), &[Rect::new(0, 0, 4, 4)]);
+        canvas.set_draw_color(Color::RED);
+        canvas.fill_rect(Rect::new(1, 1, 1, 1)).unwrap();
+        canvas.composite_layers().unwrap();
+        assert_eq!(canvas.get_pixel((1, 1)), Ok(Color::RED));
+        canvas.present();
+
+        // Only redraw the dirty part
+        canvas.mark_dirty(Rect::new(2, 2, 1, 1));
+        canvas.set_draw_color(Color::BLUE);
+        canvas
+            .with_dirty_clip(|canvas| canvas.fill_rect(None))
+            .unwrap();
+        canvas.composite_layers().unwrap();
+        assert_eq!(canvas.get_pixel((1, 1)), Ok(Color::RED));
+        assert_eq!(canvas.get_pixel((2, 2)), Ok(Color::BLUE));
+        assert_eq!(canvas.get_pixel((3, 3)), Ok(Color::BLACK));
+        canvas.present();
+        canvas.set_retained(false).unwrap();
+    }
+}
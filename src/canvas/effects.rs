@@ -0,0 +1,57 @@
+//! Applying the canvas's post-processing [effects][crate::effects] to the whole frame.
+
+use sdl2::{
+    pixels::PixelFormatEnum,
+    render::{BlendMode, RenderTarget},
+};
+
+use super::{Canvas, LayerTexture};
+use crate::effects::EffectChain;
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns the post-processing effects applied to every frame.
+    pub fn effects(&self) -> &EffectChain {
+        &self.effects
+    }
+
+    /// Returns the post-processing effects applied to every frame, so effects can be added,
+    /// removed or changed.
+    pub fn effects_mut(&mut self) -> &mut EffectChain {
+        &mut self.effects
+    }
+
+    /// Read back the current render target, apply every post-processing effect to it, then
+    /// draw the result back over the whole target, ignoring the clipping rectangle. Does nothing
+    /// if there are no effects. The engine calls this at the end of every frame, after
+    /// [`composite_layers`][Self::composite_layers]. Returns a `String` describing the error if
+    /// sdl fails.
+    pub fn apply_effects(&mut self, elapsed_time: f64) -> Result<(), String> {
+        if self.effects.is_empty() {
+            return Ok(());
+        }
+        let mut frame = self.screenshot()?;
+        self.effects.apply(&mut frame, elapsed_time);
+        // Reuse the same streaming texture every frame, unless the size changes
+        let size = (frame.width, frame.height);
+        if self.effects_texture.as_ref().map(|(_, s)| *s) != Some(size) {
+            self.effects_texture = None;
+            let texture = self
+                .texture_creator
+                .create_texture_streaming(PixelFormatEnum::RGBA32, size.0, size.1)
+                .map_err(|e| e.to_string())?;
+            self.effects_texture = Some((LayerTexture::new(texture), size));
+        }
+        let result = {
+            let (texture, _) = self.effects_texture.as_ref().unwrap();
+            let mut texture = texture.as_texture(&self.texture_creator);
+            texture
+                .update(None, &frame.pixels, frame.width as usize * 4)
+                .map_err(|e| e.to_string())?;
+            texture.set_blend_mode(BlendMode::None);
+            self.inner.set_clip_rect(None);
+            self.inner.copy(&texture, None, None)
+        };
+        self.apply_clip();
+        result
+    }
+}
@@ -0,0 +1,296 @@
+//! Gradient and pattern fills for rectangles, circles and polygons.
+
+use sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+    render::{RenderTarget, Texture},
+};
+
+use super::{style, transform::polygon_spans, Canvas};
+use crate::color::ColorExt;
+
+/// A smooth transition between colours, used by [`Fill::LinearGradient`] and
+/// [`Fill::RadialGradient`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    /// Create a gradient that fades from `start` to `end`.
+    pub fn new<C1: Into<Color>, C2: Into<Color>>(start: C1, end: C2) -> Self {
+        Self {
+            stops: vec![(0.0, start.into()), (1.0, end.into())],
+        }
+    }
+
+    /// Add a colour part way along the gradient, where an `offset` of `0.0` is the start and
+    /// `1.0` is the end.
+    pub fn stop<C: Into<Color>>(mut self, offset: f32, color: C) -> Self {
+        let offset = offset.clamp(0.0, 1.0);
+        // Keep the stops sorted, with later stops at the same offset after earlier ones
+        let index = self.stops.partition_point(|&(o, _)| o <= offset);
+        self.stops.insert(index, (offset, color.into()));
+        self
+    }
+
+    /// Returns the colour at `offset` along the gradient, clamped to `0.0..=1.0`.
+    pub fn color_at(&self, offset: f32) -> Color {
+        let offset = offset.clamp(0.0, 1.0);
+        let index = self.stops.partition_point(|&(o, _)| o <= offset);
+        if index == 0 {
+            return self.stops[0].1;
+        }
+        let (start, from) = self.stops[index - 1];
+        match self.stops.get(index) {
+            Some(&(end, to)) => from.lerp(to, (offset - start) / (end - start)),
+            None => from,
+        }
+    }
+}
+
+/// How to fill a shape drawn with [`fill_rect_with`][Canvas::fill_rect_with],
+/// [`fill_circle_with`][Canvas::fill_circle_with] or
+/// [`fill_polygon_with`][Canvas::fill_polygon_with]. Positions are in the same coordinates as the
+/// shape, so they're transformed along with it.
+#[derive(Clone)]
+pub enum Fill<'a> {
+    /// A single colour.
+    Solid(Color),
+    /// A gradient along the line from `start` to `end`. Everything before `start` is the first
+    /// colour of the gradient, and everything after `end` is the last.
+    LinearGradient {
+        /// Where the gradient starts.
+        start: Point,
+        /// Where the gradient ends.
+        end: Point,
+        /// The colours of the gradient.
+        gradient: Gradient,
+    },
+    /// A gradient in circles around `center`, reaching its last colour at `radius`.
+    RadialGradient {
+        /// The center of the gradient.
+        center: Point,
+        /// Distance from the center where the gradient ends.
+        radius: u32,
+        /// The colours of the gradient.
+        gradient: Gradient,
+    },
+    /// A texture repeated in every direction, with one of its top-left corners at `origin`.
+    /// The texture isn't scaled or rotated by the current transform.
+    Pattern {
+        /// The texture to repeat.
+        texture: &'a Texture<'a>,
+        /// Where one of the copies of the texture starts.
+        origin: Point,
+    },
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Fills a rectangle with a gradient or pattern, after applying the current transform.
+    /// Returns a `String` describing the error if sdl fails.
+    pub fn fill_rect_with(&mut self, rect: Rect, fill: &Fill) -> Result<(), String> {
+        let spans = if self.transform.is_axis_aligned() {
+            let rect = self.transform.apply_rect(rect);
+            (rect.top()..rect.bottom())
+                .map(|y| Rect::new(rect.x(), y, rect.width(), 1))
+                .collect()
+        } else {
+            polygon_spans(&self.transform_corners(rect))
+        };
+        self.fill_spans(&spans, fill)
+    }
+
+    /// Fills a circle with a gradient or pattern, after applying the current transform.
+    /// Returns a `String` describing the error if sdl fails.
+    pub fn fill_circle_with<P: Into<Point>>(
+        &mut self,
+        center: P,
+        radius: i32,
+        fill: &Fill,
+    ) -> Result<(), String> {
+        let center = self.transform.apply_point(center.into());
+        let radius = (radius as f64 * self.transform.scale.abs()).round() as i32;
+        let spans: Vec<_> = (-radius..=radius)
+            .map(|dy| {
+                // Squared as floats, since the squares of large radii overflow an i32
+                let half = ((radius as f64).powi(2) - (dy as f64).powi(2))
+                    .sqrt()
+                    .round() as i32;
+                Rect::new(center.x() - half, center.y() + dy, 2 * half as u32 + 1, 1)
+            })
+            .collect();
+        self.fill_spans(&spans, fill)
+    }
+
+    /// Fills a polygon with a gradient or pattern using the even-odd rule, after applying the
+    /// current transform. Returns a `String` describing the error if sdl fails.
+    pub fn fill_polygon_with(&mut self, points: &[Point], fill: &Fill) -> Result<(), String> {
+        let points: Vec<_> = points
+            .iter()
+            .map(|p| self.transform.apply((p.x() as f64, p.y() as f64)))
+            .collect();
+        self.fill_spans(&polygon_spans(&points), fill)
+    }
+
+    /// Fill one-pixel-high spans, in screen coordinates.
+    fn fill_spans(&mut self, spans: &[Rect], fill: &Fill) -> Result<(), String> {
+        let color = self.inner.draw_color();
+        let result = match fill {
+            Fill::Solid(fill) => {
+                self.inner.set_draw_color(self.styled_color(*fill));
+                self.styled_draw(|inner| inner.fill_rects(spans))
+            }
+            Fill::LinearGradient {
+                start,
+                end,
+                gradient,
+            } => {
+                let to_point = |p: &Point| (p.x() as f64, p.y() as f64);
+                let start = self.transform.apply(to_point(start));
+                let end = self.transform.apply(to_point(end));
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let length = dx * dx + dy * dy;
+                self.fill_spans_by(spans, |x, y| {
+                    let along = (x - start.0) * dx + (y - start.1) * dy;
+                    gradient.color_at(if length == 0.0 { 1.0 } else { along / length } as f32)
+                })
+            }
+            Fill::RadialGradient {
+                center,
+                radius,
+                gradient,
+            } => {
+                let center = self.transform.apply((center.x() as f64, center.y() as f64));
+                let radius = *radius as f64 * self.transform.scale.abs();
+                self.fill_spans_by(spans, |x, y| {
+                    let distance = (x - center.0).hypot(y - center.1);
+                    gradient.color_at(if radius == 0.0 {
+                        1.0
+                    } else {
+                        distance / radius
+                    } as f32)
+                })
+            }
+            Fill::Pattern { texture, origin } => {
+                let origin = self.transform.apply_point(*origin);
+                self.fill_spans_pattern(spans, texture, origin)
+            }
+        };
+        self.inner.set_draw_color(color);
+        result
+    }
+
+    /// Fill spans with the colour returned by `color_at` for the center of each pixel. Runs of
+    /// pixels with the same colour are filled together.
+    fn fill_spans_by<F>(&mut self, spans: &[Rect], color_at: F) -> Result<(), String>
+    where
+        F: Fn(f64, f64) -> Color,
+    {
+        for span in spans {
+            let y = span.y() as f64 + 0.5;
+            let mut run_start = span.left();
+            let mut run_color = None;
+            // One past the end of the span, to finish the last run
+            for x in span.left()..=span.right() {
+                let color = if x < span.right() {
+                    Some(color_at(x as f64 + 0.5, y))
+                } else {
+                    None
+                };
+                if color != run_color {
+                    if let Some(run_color) = run_color {
+                        self.inner.set_draw_color(self.styled_color(run_color));
+                        let width = (x - run_start) as u32;
+                        self.inner
+                            .fill_rect(Rect::new(run_start, span.y(), width, 1))?;
+                    }
+                    run_start = x;
+                    run_color = color;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill spans with copies of a texture, repeated every direction from `origin`.
+    fn fill_spans_pattern(
+        &mut self,
+        spans: &[Rect],
+        texture: &Texture,
+        origin: Point,
+    ) -> Result<(), String> {
+        let query = texture.query();
+        let (width, height) = (query.width as i32, query.height as i32);
+        for span in spans {
+            let src_y = (span.y() - origin.y()).rem_euclid(height);
+            let mut x = span.left();
+            while x < span.right() {
+                let src_x = (x - origin.x()).rem_euclid(width);
+                let len = (width - src_x).min(span.right() - x) as u32;
+                let src = Rect::new(src_x, src_y, len, 1);
+                let inner = &mut self.inner;
+                style::with_texture_style(texture, self.style, || {
+                    inner.copy(texture, src, Rect::new(x, span.y(), len, 1))
+                })?;
+                x += len as i32;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+
+    use super::*;
+    use crate::canvas::SurfaceCanvas;
+
+    /// Test looking up colours along a gradient with several stops.
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient::new(Color::BLACK, Color::WHITE).stop(0.5, Color::RED);
+        assert_eq!(gradient.color_at(-1.0), Color::BLACK);
+        assert_eq!(gradient.color_at(0.25), Color::RGB(128, 0, 0));
+        assert_eq!(gradient.color_at(0.5), Color::RED);
+        assert_eq!(gradient.color_at(1.0), Color::WHITE);
+    }
+
+    /// Test filling shapes with a gradient.
+    #[test]
+    fn test_gradient_fill() {
+        let surface = Surface::new(8, 8, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        let fill = Fill::LinearGradient {
+            start: Point::new(0, 0),
+            end: Point::new(8, 0),
+            gradient: Gradient::new(Color::BLACK, Color::BLUE),
+        };
+        canvas.fill_rect_with(Rect::new(0, 0, 8, 4), &fill).unwrap();
+        let fill = Fill::RadialGradient {
+            center: Point::new(4, 6),
+            radius: 4,
+            gradient: Gradient::new(Color::WHITE, Color::BLACK),
+        };
+        canvas.fill_circle_with((4, 6), 1, &fill).unwrap();
+        let color = |x: usize, y: usize| {
+            canvas.surface().with_lock(|pixels| {
+                let pixel = &pixels[y * 32 + x * 4..][..4];
+                Color::RGBA(pixel[3], pixel[2], pixel[1], pixel[0])
+            })
+        };
+        // Pixel centers are used, so neither end quite reaches the end of the gradient
+        assert_eq!(color(0, 0), Color::RGB(0, 0, 16));
+        assert_eq!(color(7, 3), Color::RGB(0, 0, 239));
+        assert_eq!(color(4, 6), Color::RGB(210, 210, 210));
+        assert_eq!(color(4, 7), Color::RGB(154, 154, 154));
+        assert_eq!(color(0, 7), Color::RGBA(0, 0, 0, 0));
+
+        // Circles much larger than the canvas cover all of it
+        let fill = Fill::Solid(Color::GREEN);
+        canvas.fill_circle_with((4, 4), 50_000, &fill).unwrap();
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::GREEN));
+        assert_eq!(canvas.get_pixel((7, 7)), Ok(Color::GREEN));
+    }
+}
@@ -0,0 +1,399 @@
+//! Fonts, and choosing which one the canvas draws text with.
+
+use std::rc::Rc;
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    render::RenderTarget,
+    surface::Surface,
+};
+#[cfg(feature = "unifont")]
+use sdl2_unifont::renderer::SurfaceRenderer as TextRenderer;
+
+#[cfg(feature = "ttf")]
+use super::ttf::OutlineFont;
+use super::{bitmap_font::BitmapFont, Canvas};
+
+/// Where the baseline of Unifont's glyphs is, in pixels from the top, before scaling.
+#[cfg(feature = "unifont")]
+const UNIFONT_BASELINE: u32 = 14;
+
+/// Every font a canvas can draw text with, and the text colour and weight they share. Unifont is
+/// used unless a [`Font`] has been selected.
+pub(super) struct Fonts {
+    #[cfg(feature = "unifont")]
+    pub(super) unifont: TextRenderer,
+    // Without Unifont, its renderer can't hold the colour and weight
+    #[cfg(not(feature = "unifont"))]
+    fg_color: Color,
+    #[cfg(not(feature = "unifont"))]
+    bold: bool,
+    pub(super) font: Option<Font>,
+}
+
+impl Fonts {
+    /// Create a set of fonts that draw text in `fg_color`, with Unifont selected if it's
+    /// available.
+    pub(super) fn new(fg_color: Color) -> Self {
+        Self {
+            #[cfg(feature = "unifont")]
+            unifont: TextRenderer::new(fg_color, Color::RGBA(0, 0, 0, 0)),
+            #[cfg(not(feature = "unifont"))]
+            fg_color,
+            #[cfg(not(feature = "unifont"))]
+            bold: false,
+            font: None,
+        }
+    }
+
+    /// Returns the colour text is drawn in.
+    pub(super) fn fg_color(&self) -> Color {
+        #[cfg(feature = "unifont")]
+        return self.unifont.fg_color;
+        #[cfg(not(feature = "unifont"))]
+        return self.fg_color;
+    }
+
+    /// Set the colour text is drawn in.
+    pub(super) fn set_fg_color(&mut self, color: Color) {
+        #[cfg(feature = "unifont")]
+        {
+            self.unifont.fg_color = color;
+        }
+        #[cfg(not(feature = "unifont"))]
+        {
+            self.fg_color = color;
+        }
+    }
+
+    /// Returns whether text is drawn bold.
+    pub(super) fn bold(&self) -> bool {
+        #[cfg(feature = "unifont")]
+        return self.unifont.bold;
+        #[cfg(not(feature = "unifont"))]
+        return self.bold;
+    }
+
+    /// Set whether text is drawn bold.
+    pub(super) fn set_bold(&mut self, bold: bool) {
+        #[cfg(feature = "unifont")]
+        {
+            self.unifont.bold = bold;
+        }
+        #[cfg(not(feature = "unifont"))]
+        {
+            self.bold = bold;
+        }
+    }
+
+    /// Draw a single line of text with the selected font. Returns a `String` describing the
+    /// error if sdl fails, no font is selected, or the font has no glyph for one of the
+    /// characters.
+    pub(super) fn draw(&self, text: &str) -> Result<Surface<'_>, String> {
+        if let Some(font) = &self.font {
+            return font.draw(text, self.fg_color(), self.bold());
+        }
+        #[cfg(feature = "unifont")]
+        return self.unifont.draw(text);
+        #[cfg(not(feature = "unifont"))]
+        Err(no_font(text))
+    }
+
+    /// Returns the width of a single line of text in the selected font, before the text style's
+    /// scale is applied.
+    pub(super) fn measure_width(&self, text: &str) -> Result<u32, String> {
+        if let Some(font) = &self.font {
+            return font.measure_width(text, self.bold());
+        }
+        #[cfg(feature = "unifont")]
+        return self.unifont.measure_width(text);
+        #[cfg(not(feature = "unifont"))]
+        Err(no_font(text))
+    }
+
+    /// Returns the height of a line of text in the selected font, or 0 if there isn't one.
+    pub(super) fn line_height(&self) -> u32 {
+        if let Some(font) = &self.font {
+            return font.line_height();
+        }
+        // Never fails, as every line is the same height
+        #[cfg(feature = "unifont")]
+        return self.unifont.measure_height("").unwrap();
+        #[cfg(not(feature = "unifont"))]
+        0
+    }
+
+    /// Returns where the baseline of the selected font is, in pixels from the top of a line.
+    pub(super) fn baseline(&self) -> u32 {
+        if let Some(font) = &self.font {
+            return font.baseline();
+        }
+        #[cfg(feature = "unifont")]
+        return UNIFONT_BASELINE * self.unifont.scale;
+        #[cfg(not(feature = "unifont"))]
+        0
+    }
+}
+
+/// Returns the error for drawing or measuring text without a font selected.
+#[cfg(not(feature = "unifont"))]
+fn no_font(text: &str) -> String {
+    format!(
+        "No font selected to draw {:?} with; see Canvas::set_font",
+        text
+    )
+}
+
+/// A glyph ready to be drawn, with its origin on the baseline at (0, 0).
+#[derive(Clone)]
+pub(super) struct Glyph {
+    /// The glyph's ID in its font, used for kerning.
+    pub(super) id: u16,
+    /// How far to move along the line after drawing the glyph, in pixels.
+    pub(super) advance: f32,
+    /// Where the top-left corner of the bitmap goes, relative to the origin.
+    pub(super) left: i32,
+    pub(super) top: i32,
+    pub(super) width: u32,
+    pub(super) height: u32,
+    /// How much of each pixel is covered by the glyph, from 0 to 255, row by row.
+    pub(super) coverage: Vec<u8>,
+}
+
+/// The kinds of font a [`Font`] can be.
+#[derive(Clone)]
+pub(super) enum FontKind {
+    #[cfg(feature = "ttf")]
+    Outline(OutlineFont),
+    Bitmap(BitmapFont),
+}
+
+/// A font that can be made the canvas's active font with [`Canvas::set_font`], so every text
+/// drawing and measuring method of the canvas uses it instead of Unifont. Bitmap fonts can be
+/// loaded from BDF files ([`from_bdf`][Self::from_bdf]), PSF console fonts
+/// ([`from_psf`][Self::from_psf]) and font sheet images
+/// ([`from_font_sheet`][Self::from_font_sheet]). With the "ttf" feature, TrueType and OpenType
+/// fonts can be loaded too, with `from_file` and `from_bytes`.
+///
+/// Glyphs are prepared the first time they're drawn, and cached until the font's size changes.
+/// Characters the font has no glyph for are drawn with the [fallback][Self::set_fallback]
+/// character instead.
+///
+/// Cloning a font is cheap, as the clones share its font data, though each has its own size and
+/// glyph cache.
+#[derive(Clone)]
+pub struct Font {
+    pub(super) kind: FontKind,
+    fallback: Option<char>,
+}
+
+impl Font {
+    /// Create a font, that draws `fallback` in place of characters it has no glyph for.
+    pub(super) fn new(kind: FontKind, fallback: Option<char>) -> Self {
+        Self { kind, fallback }
+    }
+
+    /// Returns the height of a line of text, in pixels, that the font is drawn at.
+    pub fn size(&self) -> f32 {
+        match &self.kind {
+            #[cfg(feature = "ttf")]
+            FontKind::Outline(font) => font.size(),
+            FontKind::Bitmap(font) => font.size() as f32,
+        }
+    }
+
+    /// Change the height of a line of text, in pixels. Bitmap fonts are only scaled by whole
+    /// numbers to keep them crisp, so they're drawn at the nearest multiple of their natural
+    /// size instead. This empties the glyph cache.
+    pub fn set_size(&mut self, size: f32) {
+        match &mut self.kind {
+            #[cfg(feature = "ttf")]
+            FontKind::Outline(font) => font.set_size(size),
+            FontKind::Bitmap(font) => font.set_size(size),
+        }
+    }
+
+    /// Returns the character drawn in place of characters the font has no glyph for.
+    pub fn fallback(&self) -> Option<char> {
+        self.fallback
+    }
+
+    /// Set the character drawn in place of characters the font has no glyph for. With `None`,
+    /// or if the font has no glyph for the fallback either, drawing and measuring text with
+    /// missing characters fails. Bitmap fonts fall back to the font's default character, or `?`,
+    /// when they're loaded, while TrueType fonts have no fallback.
+    pub fn set_fallback(&mut self, fallback: Option<char>) {
+        self.fallback = fallback;
+    }
+
+    /// Returns `true` if the font has a glyph for `c`, without falling back to another
+    /// character.
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.kind_glyph(c).is_some()
+    }
+
+    /// Returns the height of a line of text, rounded up to whole pixels.
+    pub fn line_height(&self) -> u32 {
+        match &self.kind {
+            #[cfg(feature = "ttf")]
+            FontKind::Outline(font) => font.line_height(),
+            FontKind::Bitmap(font) => font.line_height(),
+        }
+    }
+
+    /// Returns the distance from the top of a line to the baseline that letters sit on, in
+    /// pixels.
+    pub fn baseline(&self) -> u32 {
+        match &self.kind {
+            #[cfg(feature = "ttf")]
+            FontKind::Outline(font) => font.baseline(),
+            FontKind::Bitmap(font) => font.baseline(),
+        }
+    }
+
+    /// Returns the width of a single line of `text` in pixels, one pixel wider if it's `bold`.
+    /// Returns a `String` describing the error if the font has no glyph for one of the
+    /// characters, or the fallback character.
+    pub fn measure_width(&self, text: &str, bold: bool) -> Result<u32, String> {
+        let mut width = 0.0;
+        let mut previous: Option<Rc<Glyph>> = None;
+        for c in text.chars() {
+            let glyph = self.glyph(c)?;
+            width += self.kern(previous.as_deref(), &glyph) + glyph.advance;
+            previous = Some(glyph);
+        }
+        Ok(width.ceil() as u32 + bold as u32)
+    }
+
+    /// Draw a single line of `text` in `color` to a new surface, sized to fit it exactly. Bold
+    /// text is drawn twice, one pixel apart. Returns a `String` describing the error if sdl
+    /// fails or the font has no glyph for one of the characters, or the fallback character.
+    pub fn draw(&self, text: &str, color: Color, bold: bool) -> Result<Surface<'static>, String> {
+        let width = self.measure_width(text, bold)?.max(1);
+        let height = self.line_height().max(1);
+        let baseline = self.baseline() as i32;
+        let mut coverage = vec![0u8; (width * height) as usize];
+        let mut x = 0.0;
+        let mut previous: Option<Rc<Glyph>> = None;
+        for c in text.chars() {
+            let glyph = self.glyph(c)?;
+            x += self.kern(previous.as_deref(), &glyph);
+            let left = x.round() as i32 + glyph.left;
+            let top = baseline + glyph.top;
+            for strike in 0..=bold as i32 {
+                for gy in 0..glyph.height as i32 {
+                    for gx in 0..glyph.width as i32 {
+                        let (px, py) = (left + gx + strike, top + gy);
+                        if px < 0 || py < 0 || px >= width as i32 || py >= height as i32 {
+                            continue;
+                        }
+                        let value = glyph.coverage[(gy * glyph.width as i32 + gx) as usize];
+                        let pixel = &mut coverage[(py * width as i32 + px) as usize];
+                        *pixel = (*pixel).max(value);
+                    }
+                }
+            }
+            x += glyph.advance;
+            previous = Some(glyph);
+        }
+
+        let mut pixels: Vec<u8> = coverage
+            .into_iter()
+            .flat_map(|c| {
+                let alpha = (color.a as u32 * c as u32 / 255) as u8;
+                [color.r, color.g, color.b, alpha]
+            })
+            .collect();
+        let surface = Surface::from_data(
+            &mut pixels,
+            width,
+            height,
+            width * 4,
+            PixelFormatEnum::RGBA32,
+        )?
+        .convert_format(PixelFormatEnum::RGBA8888)?;
+        Ok(surface)
+    }
+
+    /// Returns the glyph for `c`, or for the fallback character if there isn't one.
+    fn glyph(&self, c: char) -> Result<Rc<Glyph>, String> {
+        self.kind_glyph(c)
+            .or_else(|| self.fallback.and_then(|c| self.kind_glyph(c)))
+            .ok_or_else(|| format!("Font has no glyph for {:?}", c))
+    }
+
+    /// Returns the glyph for `c`, without falling back to another character.
+    fn kind_glyph(&self, c: char) -> Option<Rc<Glyph>> {
+        match &self.kind {
+            #[cfg(feature = "ttf")]
+            FontKind::Outline(font) => font.glyph(c),
+            FontKind::Bitmap(font) => font.glyph(c),
+        }
+    }
+
+    /// Returns the kerning between two glyphs, or nothing at the start of a line.
+    fn kern(&self, previous: Option<&Glyph>, glyph: &Glyph) -> f32 {
+        let previous = match previous {
+            Some(previous) => previous,
+            None => return 0.0,
+        };
+        match &self.kind {
+            #[cfg(feature = "ttf")]
+            FontKind::Outline(font) => font.kern(previous, glyph),
+            FontKind::Bitmap(font) => font.kern(previous, glyph),
+        }
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns the active [`Font`], or `None` if text is drawn with Unifont.
+    pub fn font(&self) -> Option<&Font> {
+        self.fonts.font.as_ref()
+    }
+
+    /// Returns a mutable reference to the active [`Font`], or `None` if text is drawn with
+    /// Unifont.
+    pub fn font_mut(&mut self) -> Option<&mut Font> {
+        self.fonts.font.as_mut()
+    }
+
+    /// Make `font` the active font, so every method that draws or measures text uses it,
+    /// including [text styles][Self::set_text_style] and [rich text][Self::draw_rich_text].
+    /// Text is still drawn in the [text colour][Self::set_text_color]. With `None`, text is
+    /// drawn with Unifont again, or can't be drawn at all if the "unifont" feature isn't
+    /// enabled. Returns the font that was active before, so several fonts can be swapped in and
+    /// out.
+    pub fn set_font(&mut self, font: Option<Font>) -> Option<Font> {
+        std::mem::replace(&mut self.fonts.font, font)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::{bitmap_font::TEST_BDF, SurfaceCanvas};
+
+    /// Test the canvas's text methods switch to the active font.
+    #[test]
+    fn test_set_font() {
+        let surface = Surface::new(64, 64, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        let font = Font::from_bdf(TEST_BDF).unwrap();
+        let width = font.measure_width("AA", false).unwrap();
+        let height = font.line_height();
+        assert!(canvas.set_font(Some(font)).is_none());
+        assert_eq!(canvas.measure_text("AA\nA"), Ok((width, height * 2)));
+
+        canvas.set_draw_color(Color::GREEN);
+        let rect = canvas.draw_text_surface("AA", (0, 0)).unwrap().rect();
+        assert_eq!((rect.width(), rect.height()), (width, height));
+        let image = canvas.screenshot().unwrap();
+        let lit = (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .any(|(x, y)| image.pixel(x, y) == Some(Color::GREEN));
+        assert!(lit);
+
+        assert!(canvas.set_font(None).is_some());
+        assert!(canvas.font().is_none());
+    }
+}
@@ -0,0 +1,201 @@
+//! Drawing sprite batches as triangles with `SDL_RenderGeometry`, so each run of images on the
+//! same atlas page is a single draw call.
+
+use std::os::raw::c_int;
+
+use sdl2::{
+    pixels::Color,
+    render::{RenderTarget, Texture},
+    sys,
+};
+
+use super::{style, Canvas};
+use crate::atlas::{Quad, SpriteBatch, TextureAtlas};
+
+/// A corner of a triangle, laid out like `SDL_Vertex`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Vertex {
+    position: [f32; 2],
+    color: [u8; 4],
+    tex_coord: [f32; 2],
+}
+
+// sdl2-sys 0.34 has no binding for geometry rendering, which was added in SDL 2.0.18. Only call
+// this once `geometry_supported` has checked the running version.
+extern "C" {
+    fn SDL_RenderGeometry(
+        renderer: *mut sys::SDL_Renderer,
+        texture: *mut sys::SDL_Texture,
+        vertices: *const Vertex,
+        num_vertices: c_int,
+        indices: *const c_int,
+        num_indices: c_int,
+    ) -> c_int;
+}
+
+/// Returns `true` if the version of sdl running supports geometry rendering.
+fn geometry_supported() -> bool {
+    let version = sdl2::version::version();
+    (version.major, version.minor, version.patch) >= (2, 0, 18)
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Draw every image queued in a [`SpriteBatch`], using the textures from `atlas`, in the
+    /// order they were pushed. Each run of consecutive upright images on the same atlas page is
+    /// drawn with a single call to sdl's geometry rendering, so a batch whose images were packed
+    /// onto one page is drawn all at once. Images are transformed by the current [`Transform`]
+    /// and faded and blended by the current [`DrawStyle`], just like [`copy_ex`][Self::copy_ex].
+    ///
+    /// Rotated and flipped images, and every image while the transform is rotated, are copied
+    /// separately, as are all images with sdl older than 2.0.18.
+    ///
+    /// [`Transform`]: crate::camera::Transform
+    /// [`DrawStyle`]: super::DrawStyle
+    pub fn draw_batch(&mut self, atlas: &TextureAtlas, batch: &SpriteBatch) -> Result<(), String> {
+        // sdl 2.0.18's software renderer draws nothing for rectangles whose texture is flipped
+        // or turned around, so only upright images are drawn as geometry
+        let geometry =
+            geometry_supported() && self.transform.is_axis_aligned() && self.transform.scale > 0.0;
+        let mut quads = batch.quads.as_slice();
+        while let Some(quad) = quads.first() {
+            let texture = atlas
+                .page(quad.page)
+                .ok_or_else(|| format!("Texture atlas has no page {}", quad.page))?;
+            let upright = |other: &Quad| {
+                other.page == quad.page
+                    && other.angle == 0.0
+                    && !other.flip_horizontal
+                    && !other.flip_vertical
+            };
+            let run = if geometry {
+                quads.iter().take_while(|quad| upright(quad)).count()
+            } else {
+                0
+            };
+            if run > 0 {
+                self.draw_quads(texture, &quads[..run])?;
+                quads = &quads[run..];
+            } else {
+                self.copy_ex(
+                    texture,
+                    quad.src,
+                    quad.dst,
+                    quad.angle,
+                    None,
+                    quad.flip_horizontal,
+                    quad.flip_vertical,
+                )?;
+                quads = &quads[1..];
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw upright `quads` from `texture` in one call to `SDL_RenderGeometry`.
+    fn draw_quads(&mut self, texture: &Texture, quads: &[Quad]) -> Result<(), String> {
+        let (canvas, transform) = (&mut self.inner, self.transform);
+        style::with_texture_style(texture, self.style, || {
+            // Geometry rendering ignores the texture's own colour and alpha modulation, so they
+            // go on the vertices instead, after the style has faded the alpha
+            let query = texture.query();
+            let Color { r, g, b, .. } = texture.color_mod().into();
+            let color = [r, g, b, texture.alpha_mod()];
+            let (width, height) = (query.width as f32, query.height as f32);
+            let mut vertices = Vec::with_capacity(quads.len() * 4);
+            let mut indices = Vec::with_capacity(quads.len() * 6);
+            for quad in quads {
+                let first = vertices.len() as c_int;
+                let (src, dst) = (quad.src, quad.dst);
+                // Clockwise from the top left
+                let corners = [
+                    (dst.left(), dst.top(), src.left(), src.top()),
+                    (dst.right(), dst.top(), src.right(), src.top()),
+                    (dst.right(), dst.bottom(), src.right(), src.bottom()),
+                    (dst.left(), dst.bottom(), src.left(), src.bottom()),
+                ];
+                vertices.extend(corners.iter().map(|&(x, y, u, v)| {
+                    let (x, y) = transform.apply((x as f64, y as f64));
+                    Vertex {
+                        position: [x as f32, y as f32],
+                        color,
+                        tex_coord: [u as f32 / width, v as f32 / height],
+                    }
+                }));
+                indices.extend([0, 1, 2, 0, 2, 3].iter().map(|i| first + i));
+            }
+            // Safety: the renderer and texture are alive, and the indices are all in range
+            let result = unsafe {
+                SDL_RenderGeometry(
+                    canvas.raw(),
+                    texture.raw(),
+                    vertices.as_ptr(),
+                    vertices.len() as c_int,
+                    indices.as_ptr(),
+                    indices.len() as c_int,
+                )
+            };
+            if result != 0 {
+                return Err(sdl2::get_error());
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::PixelFormatEnum, rect::Rect, surface::Surface};
+
+    use super::*;
+    use crate::{
+        atlas::AtlasBuilder,
+        camera::Transform,
+        canvas::{Blend, DrawStyle, SurfaceCanvas},
+    };
+
+    /// Test drawing a batch in order, transformed and styled.
+    #[test]
+    fn test_draw_batch() {
+        let surface = Surface::new(8, 4, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        let mut builder = AtlasBuilder::new(16, 16);
+        for (name, color) in [("red", Color::RED), ("blue", Color::BLUE)].iter() {
+            let mut surface = Surface::new(2, 2, PixelFormatEnum::RGBA8888).unwrap();
+            surface.fill_rect(None, *color).unwrap();
+            builder.add(*name, surface);
+        }
+        let creator = (*canvas).texture_creator();
+        let atlas = builder.build(&creator).unwrap();
+        let (red, blue) = (atlas.region("red").unwrap(), atlas.region("blue").unwrap());
+        let mut batch = SpriteBatch::new();
+        batch.push(red, (0, 0));
+        // Drawn over the first image
+        batch.push(blue, (1, 0));
+        batch.push(red, (0, 2));
+        // Copied separately, but still in order
+        batch.push_ex(blue, Rect::new(1, 2, 2, 2), 180.0, true, false);
+
+        let transform = Transform::translate(2.0, 0.0);
+        canvas
+            .with_transform(transform, |canvas| canvas.draw_batch(&atlas, &batch))
+            .unwrap();
+        assert_eq!(canvas.get_pixel((2, 0)), Ok(Color::RED));
+        assert_eq!(canvas.get_pixel((3, 0)), Ok(Color::BLUE));
+        assert_eq!(canvas.get_pixel((4, 1)), Ok(Color::BLUE));
+        assert_eq!(canvas.get_pixel((2, 3)), Ok(Color::RED));
+        assert_eq!(canvas.get_pixel((3, 3)), Ok(Color::BLUE));
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::RGBA(0, 0, 0, 0)));
+
+        canvas.set_draw_color(Color::BLACK);
+        canvas.clear();
+        let faded = DrawStyle::new(Blend::Alpha, 0.5);
+        canvas
+            .with_style(faded, |canvas| canvas.draw_batch(&atlas, &batch))
+            .unwrap();
+        let pixel = canvas.get_pixel((0, 0)).unwrap();
+        assert!(pixel.r > 100 && pixel.r < 155, "{:?}", pixel);
+        // The texture's own alpha modulation is put back afterwards
+        assert_eq!(atlas.page(0).unwrap().alpha_mod(), 255);
+    }
+}
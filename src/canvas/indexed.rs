@@ -0,0 +1,282 @@
+//! An 8-bit draw target, where pixels are indices into a palette rather than colours.
+
+use std::ops::RangeInclusive;
+
+use sdl2::{
+    pixels::{Color, Palette, PixelFormatEnum},
+    rect::{Point, Rect},
+    render::RenderTarget,
+    surface::Surface,
+};
+
+use super::{style, transform, Canvas};
+use crate::color::palette::Cga;
+
+/// A draw target for retro-style graphics, where every pixel is an index into a palette of 256
+/// colours rather than a colour of its own.
+///
+/// Changing a palette entry changes every pixel drawn with that index, without redrawing
+/// anything, so effects such as flowing water or flickering fire can be made by rotating a range
+/// of the palette each frame (see [`rotate_palette`][Self::rotate_palette]).
+///
+/// The palette is only applied when the canvas is drawn to the screen with
+/// [`Canvas::draw_indexed`], usually once at the end of each frame.
+/// # Example
+/// ```no_run
+/// use simple_game_engine::{canvas::IndexedCanvas, prelude::*};
+/// # fn draw(canvas: &mut WindowCanvas, screen: &mut IndexedCanvas) -> Result<(), String> {
+/// // Entries 16 to 19 are shades of blue, which flow along the river each frame
+/// screen.rotate_palette(16..=19, 1)?;
+/// screen.set_draw_index(16);
+/// screen.fill_rect(Rect::new(0, 100, 320, 4));
+/// // ...
+/// // Scale the whole thing up to fill the window
+/// canvas.draw_indexed(screen, None)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct IndexedCanvas {
+    surface: Surface<'static>,
+    palette: [Color; 256],
+    draw_index: u8,
+}
+
+impl IndexedCanvas {
+    /// Create a new canvas, with every pixel set to index 0. The first 16 palette entries are
+    /// the [CGA colours][Cga], and the rest are black. Returns a `String` describing the error
+    /// if sdl couldn't create the surface.
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        let mut palette = [Color::BLACK; 256];
+        palette[..16].copy_from_slice(&Cga::PALETTE);
+        let mut canvas = Self {
+            surface: Surface::new(width, height, PixelFormatEnum::Index8)?,
+            palette,
+            draw_index: 0,
+        };
+        canvas.sync_palette()?;
+        canvas.clear();
+        Ok(canvas)
+    }
+
+    /// Returns the width of the canvas, in pixels.
+    pub fn width(&self) -> u32 {
+        self.surface.width()
+    }
+
+    /// Returns the height of the canvas, in pixels.
+    pub fn height(&self) -> u32 {
+        self.surface.height()
+    }
+
+    /// Returns the underlying 8-bit [`Surface`], with the current palette applied.
+    pub fn surface(&self) -> &Surface<'static> {
+        &self.surface
+    }
+
+    /// Returns every entry in the palette.
+    pub fn palette(&self) -> &[Color; 256] {
+        &self.palette
+    }
+
+    /// Returns the colour of the palette entry at `index`.
+    pub fn palette_color(&self, index: u8) -> Color {
+        self.palette[index as usize]
+    }
+
+    /// Change the colour of the palette entry at `index`, which changes every pixel drawn with
+    /// that index. Returns a `String` describing the error if sdl can't update the surface's
+    /// palette.
+    pub fn set_palette_color<C: Into<Color>>(&mut self, index: u8, color: C) -> Result<(), String> {
+        self.palette[index as usize] = color.into();
+        self.sync_palette()
+    }
+
+    /// Replace palette entries with `colors`, starting at `start`. Any colours that would go past
+    /// the end of the palette are ignored. Returns a `String` describing the error if sdl can't
+    /// update the surface's palette.
+    pub fn set_palette(&mut self, start: u8, colors: &[Color]) -> Result<(), String> {
+        let entries = &mut self.palette[start as usize..];
+        let len = colors.len().min(entries.len());
+        entries[..len].copy_from_slice(&colors[..len]);
+        self.sync_palette()
+    }
+
+    /// Rotate the palette entries in `range` by `amount` places, so the colour at each index
+    /// moves to the index `amount` higher, wrapping around to the start of the range. Negative
+    /// amounts rotate the other way. Returns a `String` describing the error if sdl can't update
+    /// the surface's palette.
+    pub fn rotate_palette(&mut self, range: RangeInclusive<u8>, amount: i32) -> Result<(), String> {
+        if range.is_empty() {
+            return Ok(());
+        }
+        let entries = &mut self.palette[*range.start() as usize..=*range.end() as usize];
+        entries.rotate_right(amount.rem_euclid(entries.len() as i32) as usize);
+        self.sync_palette()
+    }
+
+    /// Set the palette index used by the drawing methods.
+    pub fn set_draw_index(&mut self, index: u8) {
+        self.draw_index = index;
+    }
+
+    /// Returns the palette index used by the drawing methods.
+    pub fn draw_index(&self) -> u8 {
+        self.draw_index
+    }
+
+    /// Returns the palette index of the pixel at `(x, y)`, or `None` if it's outside the canvas.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<u8> {
+        let offset = self.offset(x, y)?;
+        Some(self.surface.with_lock(|pixels| pixels[offset]))
+    }
+
+    /// Set every pixel to the draw index.
+    pub fn clear(&mut self) {
+        let rect = self.surface.rect();
+        self.fill_rect(rect);
+    }
+
+    /// Set a single pixel to the draw index. Points outside the canvas are ignored.
+    pub fn draw_point<P: Into<Point>>(&mut self, point: P) {
+        let point = point.into();
+        if let Some(offset) = self.offset(point.x(), point.y()) {
+            let index = self.draw_index;
+            self.surface.with_lock_mut(|pixels| pixels[offset] = index);
+        }
+    }
+
+    /// Draw a line between two points, including both ends.
+    pub fn draw_line<P: Into<Point>>(&mut self, start: P, end: P) {
+        let (start, end) = (start.into(), end.into());
+        let (mut x, mut y) = (start.x(), start.y());
+        let (dx, dy) = ((end.x() - x).abs(), -(end.y() - y).abs());
+        let (step_x, step_y) = ((end.x() - x).signum(), (end.y() - y).signum());
+        let mut error = dx + dy;
+        loop {
+            self.draw_point((x, y));
+            if x == end.x() && y == end.y() {
+                break;
+            }
+            if 2 * error >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if 2 * error <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draw the outline of a rectangle.
+    pub fn draw_rect(&mut self, rect: Rect) {
+        let (left, top) = (rect.left(), rect.top());
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+        self.draw_line((left, top), (right, top));
+        self.draw_line((left, bottom), (right, bottom));
+        self.draw_line((left, top), (left, bottom));
+        self.draw_line((right, top), (right, bottom));
+    }
+
+    /// Fill a rectangle with the draw index.
+    pub fn fill_rect(&mut self, rect: Rect) {
+        let rect = match rect.intersection(self.surface.rect()) {
+            Some(rect) => rect,
+            None => return,
+        };
+        let pitch = self.surface.pitch() as usize;
+        let index = self.draw_index;
+        self.surface.with_lock_mut(|pixels| {
+            for y in rect.top()..rect.bottom() {
+                let start = y as usize * pitch + rect.left() as usize;
+                pixels[start..start + rect.width() as usize]
+                    .iter_mut()
+                    .for_each(|p| *p = index);
+            }
+        });
+    }
+
+    /// Returns the offset of a pixel in the surface's pixel data, or `None` if it's outside the
+    /// canvas.
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as u32 >= self.width() || y as u32 >= self.height() {
+            return None;
+        }
+        Some(y as usize * self.surface.pitch() as usize + x as usize)
+    }
+
+    /// Give sdl a copy of the palette, so it's used when the surface is converted.
+    fn sync_palette(&mut self) -> Result<(), String> {
+        self.surface
+            .set_palette(&Palette::with_colors(&self.palette)?)
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Convert an [`IndexedCanvas`] to full colour using its current palette, then draw it to
+    /// `dst`, scaling it to fit. If `dst` is `None`, it's stretched over the whole render target.
+    /// Returns a `String` describing the error if sdl fails.
+    pub fn draw_indexed<R: Into<Option<Rect>>>(
+        &mut self,
+        indexed: &IndexedCanvas,
+        dst: R,
+    ) -> Result<(), String> {
+        let texture = self
+            .texture_creator
+            .create_texture_from_surface(&indexed.surface)
+            .map_err(|e| e.to_string())?;
+        let (canvas, transform) = (&mut self.inner, self.transform);
+        style::with_texture_style(&texture, self.style, || {
+            transform::copy_ex(
+                canvas,
+                transform,
+                &texture,
+                None,
+                dst.into(),
+                0.0,
+                None,
+                false,
+                false,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{canvas::SurfaceCanvas, color::palette::Pico8};
+
+    /// Test drawing indices, then changing the palette they're displayed with.
+    #[test]
+    fn test_indexed_canvas() {
+        let mut indexed = IndexedCanvas::new(4, 4).unwrap();
+        indexed.set_palette(1, &Pico8::PALETTE[1..4]).unwrap();
+        indexed.set_draw_index(1);
+        indexed.draw_line((0, 0), (3, 3));
+        indexed.set_draw_index(3);
+        indexed.fill_rect(Rect::new(2, 0, 8, 1));
+        assert_eq!(indexed.pixel(1, 1), Some(1));
+        assert_eq!(indexed.pixel(3, 0), Some(3));
+        assert_eq!(indexed.pixel(4, 0), None);
+        indexed.rotate_palette(1..=3, 1).unwrap();
+        assert_eq!(indexed.palette_color(2), Pico8::PALETTE[1]);
+        assert_eq!(indexed.palette_color(1), Pico8::PALETTE[3]);
+        indexed.set_palette_color(3, Color::WHITE).unwrap();
+        assert_eq!(indexed.palette_color(3), Color::WHITE);
+        indexed.set_palette_color(3, Pico8::PALETTE[2]).unwrap();
+
+        let surface = Surface::new(4, 4, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.draw_indexed(&indexed, None).unwrap();
+        let color = |x: usize, y: usize| {
+            canvas.surface().with_lock(|pixels| {
+                let pixel = &pixels[y * 16 + x * 4..][..4];
+                Color::RGBA(pixel[3], pixel[2], pixel[1], pixel[0])
+            })
+        };
+        assert_eq!(color(1, 1), Pico8::PALETTE[3]);
+        assert_eq!(color(3, 0), Pico8::PALETTE[2]);
+        assert_eq!(color(0, 3), Cga::PALETTE[0]);
+    }
+}
@@ -0,0 +1,244 @@
+//! Named layers, backed by target textures, that are drawn separately and composited together
+//! at the end of each frame.
+
+use std::{
+    mem::{self, ManuallyDrop},
+    ptr,
+};
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    rect::{Point, Rect},
+    render::{BlendMode, RenderTarget, Texture, TextureCreator},
+    sys,
+};
+
+use super::Canvas;
+
+/// A texture owned by a canvas, such as the texture behind a [`RenderLayer`], or by an
+/// [`Assets`][crate::assets::Assets] manager.
+///
+/// sdl2 textures borrow the [`TextureCreator`] that made them, which would make a canvas
+/// borrow itself, so the canvas owns the raw texture and destroys it when dropped. These
+/// textures are always dropped before the renderer, as they're declared first in [`Canvas`],
+/// and before the texture creator that keeps the renderer alive in `Assets`.
+pub(crate) struct LayerTexture(*mut sys::SDL_Texture);
+
+impl LayerTexture {
+    /// Take ownership of a texture.
+    pub(crate) fn new(texture: Texture) -> Self {
+        let raw = texture.raw();
+        mem::forget(texture);
+        Self(raw)
+    }
+
+    /// Borrow the texture as an sdl2 [`Texture`], for passing to the sdl2 API.
+    pub(crate) fn as_texture<'a, U>(
+        &self,
+        creator: &'a TextureCreator<U>,
+    ) -> ManuallyDrop<Texture<'a>> {
+        // Safety: the texture was made by this creator's renderer, and `ManuallyDrop` stops the
+        // returned `Texture` destroying it.
+        ManuallyDrop::new(unsafe { creator.raw_create_texture(self.0) })
+    }
+}
+
+impl Drop for LayerTexture {
+    fn drop(&mut self) {
+        // Safety: the texture is only destroyed here, and the renderer is still alive
+        unsafe { sys::SDL_DestroyTexture(self.0) };
+    }
+}
+
+/// A layer that can be drawn to with the normal [`Canvas`] API, and is composited onto the
+/// screen by [`Canvas::composite_layers`]. A layer keeps its contents between frames, so things
+/// that rarely change, such as a background, only need drawing once.
+pub struct RenderLayer {
+    name: String,
+    texture: LayerTexture,
+    size: (u32, u32),
+    /// Whether the layer is composited onto the screen.
+    pub visible: bool,
+    /// How the layer is blended with the layers beneath it.
+    pub blend_mode: BlendMode,
+    /// Colour the layer is multiplied by when composited. The alpha component controls the
+    /// opacity of the whole layer.
+    pub tint: Color,
+    /// Where the top-left corner of the layer is drawn on the screen.
+    pub offset: Point,
+}
+
+impl RenderLayer {
+    /// Returns the name of this layer.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the width and height of this layer, in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Add a layer the same size as the render target on top of all the existing layers. It
+    /// starts off transparent. Returns a `String` describing the error if the layer couldn't be
+    /// created, or if there's already a layer with that name.
+    pub fn add_layer<S: Into<String>>(&mut self, name: S) -> Result<&mut RenderLayer, String> {
+        let name = name.into();
+        if self.layer(&name).is_some() {
+            return Err(format!("There's already a layer called {:?}", name));
+        }
+        let (width, height) = self.inner.output_size()?;
+        let mut texture = self
+            .texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA8888, width, height)
+            .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+        let texture = LayerTexture::new(texture);
+        self.layers.push(RenderLayer {
+            name,
+            texture,
+            size: (width, height),
+            visible: true,
+            blend_mode: BlendMode::Blend,
+            tint: Color::WHITE,
+            offset: Point::new(0, 0),
+        });
+        let index = self.layers.len() - 1;
+        // Start with a transparent layer, rather than whatever was in video memory
+        self.with_layer_index(Some(index), |canvas| {
+            let color = canvas.inner.draw_color();
+            canvas.inner.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            canvas.inner.clear();
+            canvas.inner.set_draw_color(color);
+        })?;
+        Ok(&mut self.layers[index])
+    }
+
+    /// Remove a layer, returning `false` if there's no such layer. If it's the current draw
+    /// layer, drawing goes to the screen again.
+    pub fn remove_layer(&mut self, name: &str) -> bool {
+        let index = match self.layers.iter().position(|l| l.name == name) {
+            Some(index) => index,
+            None => return false,
+        };
+        if self.draw_layer == Some(index) {
+            self.set_draw_layer(None).ok();
+        } else if let Some(draw_layer) = self.draw_layer.filter(|&i| i > index) {
+            self.draw_layer = Some(draw_layer - 1);
+        }
+        self.layers.remove(index);
+        true
+    }
+
+    /// Get a layer by name.
+    pub fn layer(&self, name: &str) -> Option<&RenderLayer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    /// Get a layer by name, to change its visibility or how it's composited.
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut RenderLayer> {
+        self.layers.iter_mut().find(|l| l.name == name)
+    }
+
+    /// Returns every layer, from bottom to top.
+    pub fn layers(&self) -> &[RenderLayer] {
+        &self.layers
+    }
+
+    /// Send all drawing to the named layer, or to the screen if `name` is `None`. Returns a
+    /// `String` describing the error if there's no such layer, or if sdl fails.
+    pub fn set_draw_layer(&mut self, name: Option<&str>) -> Result<(), String> {
+        let index = match name {
+            Some(name) => Some(
+                self.layers
+                    .iter()
+                    .position(|l| l.name == name)
+                    .ok_or_else(|| format!("There's no layer called {:?}", name))?,
+            ),
+            None => None,
+        };
+        self.set_draw_layer_index(index)
+    }
+
+    /// Returns the name of the layer currently being drawn to, or `None` for the screen.
+    pub fn draw_layer(&self) -> Option<&str> {
+        self.draw_layer.map(|i| self.layers[i].name.as_str())
+    }
+
+    /// Draw to the named layer inside `f`, then go back to drawing wherever we were before.
+    pub fn with_layer<F, R>(&mut self, name: &str, f: F) -> Result<R, String>
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let index = self
+            .layers
+            .iter()
+            .position(|l| l.name == name)
+            .ok_or_else(|| format!("There's no layer called {:?}", name))?;
+        self.with_layer_index(Some(index), f)
+    }
+
+    pub(super) fn with_layer_index<F, R>(&mut self, index: Option<usize>, f: F) -> Result<R, String>
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let previous = self.draw_layer;
+        self.set_draw_layer_index(index)?;
+        let result = f(self);
+        self.set_draw_layer_index(previous)?;
+        Ok(result)
+    }
+
+    /// Send drawing to a layer, or to the screen if `index` is `None`. In retained mode, the
+    /// screen is the retained texture.
+    pub(super) fn set_draw_layer_index(&mut self, index: Option<usize>) -> Result<(), String> {
+        let texture = match index {
+            Some(i) => self.layers[i].texture.0,
+            None => self.retained.as_ref().map_or(ptr::null_mut(), |t| t.0),
+        };
+        // Safety: both the renderer and texture are alive, and the texture is a render target
+        // created by this renderer.
+        if unsafe { sys::SDL_SetRenderTarget(self.inner.raw(), texture) } != 0 {
+            return Err(sdl2::get_error());
+        }
+        self.draw_layer = index;
+        self.apply_clip();
+        Ok(())
+    }
+
+    /// Draw every visible layer onto the screen, from bottom to top, over whatever has been
+    /// drawn to the screen directly. The engine calls this at the end of every frame, just
+    /// before the screen is updated.
+    ///
+    /// In retained mode, the retained screen is copied to the real one first, and drawing goes
+    /// to the real screen until it's [presented][Self::present], so anything drawn after this
+    /// only lasts for one frame.
+    pub fn composite_layers(&mut self) -> Result<(), String> {
+        if self.layers.is_empty() && self.retained.is_none() {
+            return Ok(());
+        }
+        self.set_draw_layer_index(None)?;
+        if let Some(retained) = &self.retained {
+            // Safety: the renderer is alive
+            if unsafe { sys::SDL_SetRenderTarget(self.inner.raw(), ptr::null_mut()) } != 0 {
+                return Err(sdl2::get_error());
+            }
+            let texture = retained.as_texture(&self.texture_creator);
+            self.inner.set_clip_rect(None);
+            self.inner.copy(&texture, None, None)?;
+            self.inner.set_clip_rect(self.clip);
+        }
+        for layer in self.layers.iter().filter(|l| l.visible) {
+            let mut texture = layer.texture.as_texture(&self.texture_creator);
+            texture.set_blend_mode(layer.blend_mode);
+            texture.set_color_mod(layer.tint.r, layer.tint.g, layer.tint.b);
+            texture.set_alpha_mod(layer.tint.a);
+            let (width, height) = layer.size;
+            let dst = Rect::new(layer.offset.x(), layer.offset.y(), width, height);
+            self.inner.copy(&texture, None, dst)?;
+        }
+        Ok(())
+    }
+}
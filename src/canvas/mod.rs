@@ -0,0 +1,484 @@
+//! Provides the [`Canvas`] struct, which allows the screen to be manipulated, such as by drawing
+//! points, lines, rectangles, text, or textures to it.
+
+mod bitmap_font;
+mod clip;
+pub use clip::ClipGuard;
+mod dirty;
+mod effects;
+mod fill;
+pub use fill::{Fill, Gradient};
+mod font;
+pub use font::Font;
+use font::Fonts;
+mod geometry;
+mod indexed;
+pub use indexed::IndexedCanvas;
+mod layer;
+pub(crate) use layer::LayerTexture;
+pub use layer::RenderLayer;
+mod pixels;
+mod rich_text;
+pub use rich_text::{RichSpan, RichText, TextEffect};
+mod screenshot;
+pub use screenshot::Image;
+mod style;
+pub use style::{Blend, DrawStyle, PixelMode};
+mod text;
+pub use text::{TextAlign, TextBaseline, TextLayout};
+mod text_style;
+pub use text_style::{TextOutline, TextShadow, TextStyle};
+mod transform;
+#[cfg(feature = "ttf")]
+mod ttf;
+
+use std::ops::{Deref, DerefMut};
+
+use sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+    render::{BlendMode, Canvas as SdlCanvas, RenderTarget, Texture, TextureCreator},
+    surface::{Surface, SurfaceContext},
+    video::{Window, WindowContext},
+};
+#[cfg(feature = "unifont")]
+use sdl2_unifont::renderer::SurfaceRenderer as TextRenderer;
+
+use crate::{
+    camera::Transform,
+    effects::EffectChain,
+    sprite::SpriteSheet,
+    tilemap::{Layer, TileLayer, Tilemap},
+};
+
+/// A [`Canvas`] that internally renders to a [`Surface`][sdl2::surface::Surface].
+pub type SurfaceCanvas<'a> = Canvas<Surface<'a>, SurfaceContext<'a>>;
+/// A [`Canvas`] that renders to a [`Window`][Window] on the screen.
+pub type WindowCanvas = Canvas<Window, WindowContext>;
+
+/// This struct allows you to draw to the screen in various ways. It is a wrapper around:
+/// * An [sdl2 `Canvas`][SdlCanvas], which allows you to draw points, lines, rectangles, etc, and to "blit"
+///   textures and surfaces onto the screen.
+/// * An [sdl2 `TextureCreator`][TextureCreator], which is linked to the sdl2 `Canvas`, for creating textures.
+/// * An [sdl2-unifont `SurfaceRenderer`][TextRenderer] for rendering text to a surface, or a
+///   [`Font`] selected with [`set_font`][Self::set_font].
+///
+/// This struct implements [`Deref`][std::ops::Deref] and [`DerefMut`][std::ops::DerefMut] for the sdl2 `Canvas`, so you can call any of the
+/// normal drawing routines via deref coersion.
+///
+/// Points, lines, rectangles and texture copies drawn through this struct are transformed by the
+/// current [`Transform`] (see [`push_transform`][Self::push_transform]), so they can be drawn in
+/// world coordinates. With no transform pushed, they behave exactly like the sdl2 routines.
+///
+/// Drawing can also be sent to named [`RenderLayer`]s rather than straight to the screen (see
+/// [`add_layer`][Self::add_layer]), which are composited together at the end of each frame.
+/// In retained mode (see [`set_retained`][Self::set_retained]), the screen keeps its contents
+/// between frames, so only the parts marked dirty need redrawing.
+/// Drawing can be restricted to part of the screen with a stack of clipping rectangles (see
+/// [`push_clip`][Self::push_clip]), blended translucently with a stack of draw styles (see
+/// [`push_style`][Self::push_style]), and post-processing [effects][crate::effects] can be
+/// applied to the whole frame (see [`effects_mut`][Self::effects_mut]).
+pub struct Canvas<T: RenderTarget, U> {
+    // Must be dropped before the renderer
+    layers: Vec<RenderLayer>,
+    effects_texture: Option<(LayerTexture, (u32, u32))>,
+    retained: Option<LayerTexture>,
+    draw_layer: Option<usize>,
+    inner: SdlCanvas<T>,
+    texture_creator: TextureCreator<U>,
+    transform: Transform,
+    transform_stack: Vec<Transform>,
+    clip: Option<Rect>,
+    clip_stack: Vec<Option<Rect>>,
+    style: Option<DrawStyle>,
+    style_stack: Vec<Option<DrawStyle>>,
+    unstyled_blend: BlendMode,
+    draw_color: Color,
+    pixel_mode: PixelMode,
+    dirty: Vec<Rect>,
+    effects: EffectChain,
+    fonts: Fonts,
+    synced_colors: bool,
+    text_style: TextStyle,
+}
+
+impl WindowCanvas {
+    /// Create a new `Canvas` from the specified sdl2 `WindowCanvas` that draws to a window on the
+    /// screen
+    pub fn new(inner: SdlCanvas<Window>) -> Self {
+        let texture_creator = inner.texture_creator();
+        let draw_color = inner.draw_color();
+        Self {
+            layers: Vec::new(),
+            effects_texture: None,
+            retained: None,
+            draw_layer: None,
+            inner,
+            texture_creator,
+            transform: Transform::IDENTITY,
+            transform_stack: Vec::new(),
+            clip: None,
+            clip_stack: Vec::new(),
+            style: None,
+            style_stack: Vec::new(),
+            unstyled_blend: BlendMode::None,
+            draw_color,
+            pixel_mode: PixelMode::Normal,
+            dirty: Vec::new(),
+            effects: EffectChain::new(),
+            fonts: Fonts::new(draw_color),
+            synced_colors: true,
+            text_style: TextStyle::default(),
+        }
+    }
+}
+
+impl<'a> SurfaceCanvas<'a> {
+    /// Create a new `Canvas` from an sdl2 `SurfaceCanvas`, that draws internally to an sdl2
+    /// `Surface`.
+    pub fn new(inner: SdlCanvas<Surface<'a>>) -> Self {
+        let texture_creator = inner.texture_creator();
+        let draw_color = inner.draw_color();
+        Self {
+            layers: Vec::new(),
+            effects_texture: None,
+            retained: None,
+            draw_layer: None,
+            inner,
+            texture_creator,
+            transform: Transform::IDENTITY,
+            transform_stack: Vec::new(),
+            clip: None,
+            clip_stack: Vec::new(),
+            style: None,
+            style_stack: Vec::new(),
+            unstyled_blend: BlendMode::None,
+            draw_color,
+            pixel_mode: PixelMode::Normal,
+            dirty: Vec::new(),
+            effects: EffectChain::new(),
+            fonts: Fonts::new(draw_color),
+            synced_colors: true,
+            text_style: TextStyle::default(),
+        }
+    }
+
+    /// Draw the specified text to a point on the screen. Returns a
+    /// [`Surface`][Surface] representing the
+    /// rendered text, or a `String` indicating an error from sdl.
+    ///
+    /// This is a `Canvas<Surface>` specific alternative to [`draw_text`][Self::draw_text], which internally creates
+    /// a texture for the rendered text.
+    pub fn draw_text_surface<P: Into<Point>>(
+        &mut self,
+        text: &str,
+        pos: P,
+    ) -> Result<Surface<'_>, String> {
+        let pos = pos.into();
+        let (surface, offset) = text_style::render_text(&self.fonts, self.text_style, text)?;
+        let mut rect = surface.rect();
+        rect.set_x(pos.x() + offset.x());
+        rect.set_y(pos.y() + offset.y());
+        // Surfaces can't be rotated, so only the position and size are transformed
+        if self.transform.is_axis_aligned() {
+            rect = self.transform.apply_rect(rect);
+        } else {
+            let pos = self.transform.apply_point(rect.top_left());
+            rect.set_x(pos.x());
+            rect.set_y(pos.y());
+        }
+        // Blitting bypasses the renderer, so it needs clipping separately
+        let clip = self.clip;
+        let target = self.inner.surface_mut();
+        target.set_clip_rect(clip);
+        let result = surface.blit_scaled(None, target, rect);
+        target.set_clip_rect(None);
+        result?;
+        Ok(surface)
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns an immutable reference to the [`TextureCreator`][TextureCreator] associated with this canvas.
+    pub fn texture_creator(&self) -> &TextureCreator<U> {
+        &self.texture_creator
+    }
+
+    /// Returns a mutable reference to the [`TextureCreator`][TextureCreator] associated with this canvas.
+    pub fn texture_creator_mut(&mut self) -> &mut TextureCreator<U> {
+        &mut self.texture_creator
+    }
+
+    /// Returns an immutable reference to the [sdl2-unifont `SurfaceRenderer`][TextRenderer] for text rendering, associated with this canvas.
+    #[cfg(feature = "unifont")]
+    pub fn text_renderer(&self) -> &TextRenderer {
+        &self.fonts.unifont
+    }
+
+    /// Returns a mutable reference to the [sdl2-unifont `SurfaceRenderer`][TextRenderer] for text rendering, associated with this canvas.
+    #[cfg(feature = "unifont")]
+    pub fn text_renderer_mut(&mut self) -> &mut TextRenderer {
+        &mut self.fonts.unifont
+    }
+
+    /// Set the draw color for the standard sdl2 `canvas` drawing routines, faded by the current
+    /// [`DrawStyle`]'s opacity. If colors are synchronised (I.E. `canvas.set_text_color(None)`),
+    /// Also changes the default text color.
+    pub fn set_draw_color<C: Into<Color>>(&mut self, color: C) {
+        let color = color.into();
+        if self.synced_colors {
+            self.fonts.set_fg_color(color);
+        }
+        self.draw_color = color;
+        self.inner.set_draw_color(self.styled_color(color))
+    }
+
+    /// If called with `Some(color)`, set the color used when rendering text. If called with `None`,
+    /// resynchronises the drawing and text colors.
+    pub fn set_text_color<C>(&mut self, color: C)
+    where
+        C: Into<Option<Color>>,
+    {
+        self.synced_colors = if let Some(color) = color.into() {
+            self.fonts.set_fg_color(color);
+            false
+        } else {
+            self.fonts.set_fg_color(self.draw_color);
+            true
+        };
+    }
+
+    /// Draw the specified text to a point on the screen, scaled, outlined and shadowed
+    /// according to the current [`TextStyle`]. Returns a [`Texture`] representing the rendered
+    /// text, or a `String` indicating an error from sdl.
+    pub fn draw_text<P: Into<Point>>(&mut self, text: &str, pos: P) -> Result<Texture<'_>, String> {
+        let pos = pos.into();
+        let (surface, offset) = text_style::render_text(&self.fonts, self.text_style, text)?;
+        let texture = surface.as_texture(&self.texture_creator).unwrap();
+        let mut rect = surface.rect();
+        rect.set_x(pos.x() + offset.x());
+        rect.set_y(pos.y() + offset.y());
+        let (canvas, transform) = (&mut self.inner, self.transform);
+        style::with_texture_style(&texture, self.style, || {
+            transform::copy_ex(
+                canvas,
+                transform,
+                &texture,
+                None,
+                Some(rect),
+                0.0,
+                None,
+                false,
+                false,
+            )
+        })?;
+        Ok(texture)
+    }
+
+    /// Copy frame number `frame` of a [`SpriteSheet`] from `texture` to the screen, at its
+    /// natural size with its top-left corner at `pos`. Use [`Animation::frame`][crate::sprite::Animation::frame]
+    /// to get the frame to draw for an animation.
+    pub fn draw_frame<P: Into<Point>>(
+        &mut self,
+        texture: &Texture,
+        sheet: &SpriteSheet,
+        frame: usize,
+        pos: P,
+    ) -> Result<(), String> {
+        let pos = pos.into();
+        let src = sheet
+            .frame(frame)
+            .ok_or_else(|| format!("Sprite sheet has no frame {}", frame))?
+            .rect;
+        let dst = Rect::new(pos.x(), pos.y(), src.width(), src.height());
+        self.copy(texture, src, dst)
+    }
+
+    /// Draw the part of a tile layer that's visible through `view`, a rectangle in map pixel
+    /// coordinates (such as [`Camera2D::visible_area`][crate::camera::Camera2D::visible_area]).
+    /// Tiles are drawn at their position on the map, so push a [`Transform`] to scroll the map
+    /// across the screen. `textures` must contain the texture for each of the map's
+    /// [`tilesets`][Tilemap::tilesets], in the same order. The layer's opacity fades its tiles
+    /// as if it were pushed as a [`DrawStyle`]. Tiles outside the view aren't drawn at all, so
+    /// this is cheap even for very large maps.
+    pub fn draw_tile_layer(
+        &mut self,
+        map: &Tilemap,
+        layer: &TileLayer,
+        textures: &[Texture],
+        view: Rect,
+    ) -> Result<(), String> {
+        if !layer.visible {
+            return Ok(());
+        }
+        if layer.opacity >= 1.0 {
+            return self.draw_tiles(map, layer, textures, view);
+        }
+        let blend = self.style.map_or(Blend::Alpha, |style| style.blend);
+        self.push_style(DrawStyle::new(blend, layer.opacity.max(0.0) as f32));
+        let result = self.draw_tiles(map, layer, textures, view);
+        self.pop_style();
+        result
+    }
+
+    /// Draw the tiles of a layer that are visible through `view`.
+    fn draw_tiles(
+        &mut self,
+        map: &Tilemap,
+        layer: &TileLayer,
+        textures: &[Texture],
+        view: Rect,
+    ) -> Result<(), String> {
+        // Tiles taller than the grid stick up out of it, so look one extra row down
+        let view = Rect::new(
+            view.x() - layer.offset_x,
+            view.y() - layer.offset_y,
+            view.width(),
+            view.height() + map.tile_height,
+        );
+        let (columns, rows) = map.visible_range(view);
+        for y in rows {
+            for x in columns.clone() {
+                let tile = match layer.get(x, y) {
+                    Some(tile) => tile,
+                    None => continue,
+                };
+                let (tileset, src) = match map.tile_source(tile) {
+                    Some(source) => source,
+                    None => continue,
+                };
+                let texture = textures
+                    .get(tileset)
+                    .ok_or_else(|| format!("No texture for tileset {}", tileset))?;
+                // Tiles are aligned to the bottom-left of their grid cell
+                let dst = Rect::new(
+                    (x * map.tile_width) as i32 + layer.offset_x,
+                    ((y + 1) * map.tile_height) as i32 - src.height() as i32 + layer.offset_y,
+                    src.width(),
+                    src.height(),
+                );
+                let (angle, flip_horizontal, flip_vertical) = tile.transform();
+                self.copy_ex(
+                    texture,
+                    src,
+                    dst,
+                    angle,
+                    None,
+                    flip_horizontal,
+                    flip_vertical,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw every visible tile layer of a map, from bottom to top. See
+    /// [`draw_tile_layer`][Self::draw_tile_layer] for details.
+    pub fn draw_tilemap(
+        &mut self,
+        map: &Tilemap,
+        textures: &[Texture],
+        view: Rect,
+    ) -> Result<(), String> {
+        for layer in &map.layers {
+            if let Layer::Tiles(layer) = layer {
+                self.draw_tile_layer(map, layer, textures, view)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_circle_points(&mut self, center: Point, point: Point) -> Result<(), String> {
+        let points = &[
+            Point::new(center.x() + point.x(), center.y() + point.y()),
+            Point::new(center.x() - point.x(), center.y() + point.y()),
+            Point::new(center.x() + point.x(), center.y() - point.y()),
+            Point::new(center.x() - point.x(), center.y() - point.y()),
+            Point::new(center.x() + point.y(), center.y() + point.x()),
+            Point::new(center.x() - point.y(), center.y() + point.x()),
+            Point::new(center.x() + point.y(), center.y() - point.x()),
+            Point::new(center.x() - point.y(), center.y() - point.x()),
+        ];
+        self.styled_draw(|inner| inner.draw_points(points.as_ref()))
+    }
+
+    fn fill_circle_lines(&mut self, center: Point, point: Point) -> Result<(), String> {
+        let points = &[
+            Point::new(center.x() + point.x(), center.y() + point.y()),
+            Point::new(center.x() - point.x(), center.y() + point.y()),
+            Point::new(center.x() + point.x(), center.y() - point.y()),
+            Point::new(center.x() - point.x(), center.y() - point.y()),
+            Point::new(center.x() + point.y(), center.y() + point.x()),
+            Point::new(center.x() - point.y(), center.y() + point.x()),
+            Point::new(center.x() + point.y(), center.y() - point.x()),
+            Point::new(center.x() - point.y(), center.y() - point.x()),
+        ];
+        self.styled_draw(|inner| inner.draw_lines(points.as_ref()))
+    }
+
+    /// Draws a circle outline using Bresenham's algorithm, with the given center and radius.
+    pub fn draw_circle<P>(&mut self, center: P, radius: i32) -> Result<(), String>
+    where
+        P: Into<Point>,
+    {
+        let center = self.transform.apply_point(center.into());
+        let radius = (radius as f64 * self.transform.scale.abs()).round() as i32;
+        let mut current = Point::new(0, radius);
+        let mut d = 3 - 2 * radius;
+        self.draw_circle_points(center, current)?;
+        while current.y() >= current.x() {
+            // for each pixel we will draw all eight pixels
+            current = current.offset(1, 0);
+
+            // check for decision parameter and correspondingly update d, x, y
+            if d > 0 {
+                current = current.offset(0, -1);
+                d += 4 * (current.x() - current.y()) + 10;
+            } else {
+                d += 4 * current.x() + 6;
+                self.draw_circle_points(center, current)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws a filled circle using Bresenham's algorithm, with the given center and radius.
+    pub fn fill_circle<P>(&mut self, center: P, radius: i32) -> Result<(), String>
+    where
+        P: Into<Point>,
+    {
+        let center = self.transform.apply_point(center.into());
+        let radius = (radius as f64 * self.transform.scale.abs()).round() as i32;
+        let mut current = Point::new(0, radius);
+        let mut d = 3 - 2 * radius;
+        self.fill_circle_lines(center, current)?;
+        while current.y() >= current.x() {
+            // for each pixel we will draw all eight pixels
+            current = current.offset(1, 0);
+
+            // check for decision parameter and correspondingly update d, x, y
+            if d > 0 {
+                current = current.offset(0, -1);
+                d += 4 * (current.x() - current.y()) + 10;
+            } else {
+                d += 4 * current.x() + 6;
+                self.fill_circle_lines(center, current)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: RenderTarget, U> Deref for Canvas<T, U> {
+    type Target = SdlCanvas<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: RenderTarget, U> DerefMut for Canvas<T, U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
@@ -0,0 +1,156 @@
+//! Reading pixels back from the render target, and paint-bucket fills.
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    rect::{Point, Rect},
+    render::RenderTarget,
+};
+
+use super::Canvas;
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Read the pixels in `rect` from the current render target, or the whole target if `rect`
+    /// is `None`. The pixels are returned row by row, with 4 bytes per pixel, in the order red,
+    /// green, blue, alpha.
+    ///
+    /// This works on any canvas, but reading from a window is slow, so avoid doing it every
+    /// frame. Returns a `String` describing the error if `rect` isn't entirely inside the render
+    /// target, or if sdl fails.
+    pub fn read_pixels<R: Into<Option<Rect>>>(&self, rect: R) -> Result<Vec<u8>, String> {
+        let rect = rect.into();
+        if let Some(rect) = rect {
+            let (width, height) = self.inner.output_size()?;
+            if rect.intersection(Rect::new(0, 0, width, height)) != Some(rect) {
+                return Err(format!("{:?} isn't inside the render target", rect));
+            }
+        }
+        self.inner.read_pixels(rect, PixelFormatEnum::RGBA32)
+    }
+
+    /// Returns the colour of a single pixel in screen coordinates. Returns a `String` describing
+    /// the error if the point is outside the render target, or if sdl fails.
+    pub fn get_pixel<P: Into<Point>>(&self, point: P) -> Result<Color, String> {
+        let point = point.into();
+        let pixel = self.read_pixels(Rect::new(point.x(), point.y(), 1, 1))?;
+        Ok(Color::RGBA(pixel[0], pixel[1], pixel[2], pixel[3]))
+    }
+
+    /// Fill the area around `point` that's the same colour as it with `color`, like the paint
+    /// bucket in a paint program. Pixels count as the same colour if none of their components
+    /// differ from the starting pixel's by more than `tolerance`. The fill doesn't leave the
+    /// current clipping rectangle.
+    ///
+    /// `point` is transformed by the current transform. The render target is read back to find
+    /// the area to fill, which is slow on a window, so avoid doing it every frame. Returns a
+    /// `String` describing the error if sdl fails.
+    pub fn flood_fill<P, C>(&mut self, point: P, color: C, tolerance: u8) -> Result<(), String>
+    where
+        P: Into<Point>,
+        C: Into<Color>,
+    {
+        let point = self.transform.apply_point(point.into());
+        let (width, height) = self.inner.output_size()?;
+        let target = Rect::new(0, 0, width, height);
+        let bounds = match self.clip {
+            Some(clip) => match clip.intersection(target) {
+                Some(bounds) => bounds,
+                None => return Ok(()),
+            },
+            None => target,
+        };
+        let pixels = self.read_pixels(None)?;
+        let spans = flood_spans(&pixels, width, bounds, point, tolerance);
+        let previous = self.inner.draw_color();
+        self.inner.set_draw_color(self.styled_color(color.into()));
+        let result = self.styled_draw(|inner| inner.fill_rects(&spans));
+        self.inner.set_draw_color(previous);
+        result
+    }
+}
+
+/// Find the one-pixel-high spans covering the area of an RGBA image connected to `seed` that's
+/// within `tolerance` of its colour, without leaving `bounds`.
+fn flood_spans(pixels: &[u8], width: u32, bounds: Rect, seed: Point, tolerance: u8) -> Vec<Rect> {
+    let mut spans = Vec::new();
+    if !bounds.contains_point(seed) {
+        return spans;
+    }
+    let pixel = |x: i32, y: i32| {
+        let offset = (y as usize * width as usize + x as usize) * 4;
+        &pixels[offset..offset + 4]
+    };
+    let target = pixel(seed.x(), seed.y());
+    let mut filled = vec![false; pixels.len() / 4];
+    // Whether (x, y) is in bounds, not filled yet, and close enough to the starting colour
+    let fillable = |filled: &[bool], x: i32, y: i32| {
+        bounds.contains_point((x, y))
+            && !filled[y as usize * width as usize + x as usize]
+            && pixel(x, y)
+                .iter()
+                .zip(target)
+                .all(|(&a, &b)| (a as i16 - b as i16).unsigned_abs() <= tolerance as u16)
+    };
+    let mut stack = vec![(seed.x(), seed.y())];
+    while let Some((x, y)) = stack.pop() {
+        if !fillable(&filled, x, y) {
+            continue;
+        }
+        let mut left = x;
+        while fillable(&filled, left - 1, y) {
+            left -= 1;
+        }
+        let mut right = x + 1;
+        while fillable(&filled, right, y) {
+            right += 1;
+        }
+        let row = y as usize * width as usize;
+        filled[row + left as usize..row + right as usize]
+            .iter_mut()
+            .for_each(|f| *f = true);
+        spans.push(Rect::new(left, y, (right - left) as u32, 1));
+        // Queue the start of every fillable run in the rows above and below
+        for next_y in [y - 1, y + 1].iter().copied() {
+            let mut in_run = false;
+            for next_x in left..right {
+                let can_fill = fillable(&filled, next_x, next_y);
+                if can_fill && !in_run {
+                    stack.push((next_x, next_y));
+                }
+                in_run = can_fill;
+            }
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::surface::Surface;
+
+    use super::*;
+    use crate::canvas::SurfaceCanvas;
+
+    /// Test filling a ring leaves the outside untouched.
+    #[test]
+    fn test_flood_fill() {
+        let surface = Surface::new(8, 8, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_draw_color(Color::BLACK);
+        canvas.clear();
+        canvas.set_draw_color(Color::RGB(255, 0, 0));
+        canvas.draw_rect(Rect::new(1, 1, 6, 6)).unwrap();
+        // Slightly different, but within tolerance
+        canvas.set_draw_color(Color::RGB(2, 0, 0));
+        canvas.draw_point((3, 3)).unwrap();
+        canvas.flood_fill((4, 4), Color::BLUE, 4).unwrap();
+
+        assert_eq!(canvas.get_pixel((3, 3)), Ok(Color::BLUE));
+        assert_eq!(canvas.get_pixel((2, 5)), Ok(Color::BLUE));
+        assert_eq!(canvas.get_pixel((1, 4)), Ok(Color::RGB(255, 0, 0)));
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::BLACK));
+        assert_eq!(canvas.read_pixels(Rect::new(6, 0, 2, 2)).unwrap().len(), 16);
+        assert!(canvas.read_pixels(Rect::new(7, 0, 2, 2)).is_err());
+        // Draw colour is restored afterwards
+        assert_eq!(canvas.draw_color(), Color::RGB(2, 0, 0));
+    }
+}
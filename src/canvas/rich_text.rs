@@ -0,0 +1,440 @@
+//! Rich text, with colours, bold text and animated effects changing part way through a line,
+//! written with a small markup language.
+
+use sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+    render::RenderTarget,
+};
+
+use super::{text::LaidOutText, Canvas, SurfaceCanvas, TextLayout};
+use crate::color::ColorExt;
+
+/// An animated effect applied to each letter of a [`RichSpan`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextEffect {
+    /// Letters bob up and down in a wave.
+    Wave,
+    /// Letters jitter around randomly.
+    Shake,
+}
+
+/// Part of a [`RichText`], where every letter looks the same.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RichSpan {
+    /// The text of the span.
+    pub text: String,
+    /// The colour of the text, or `None` for the canvas's current text colour.
+    pub color: Option<Color>,
+    /// Whether the text is bold.
+    pub bold: bool,
+    /// An animated effect, if any.
+    pub effect: Option<TextEffect>,
+}
+
+/// The style of a span, without its text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+struct SpanStyle {
+    color: Option<Color>,
+    bold: bool,
+    effect: Option<TextEffect>,
+}
+
+/// Text made up of [`RichSpan`]s that each look different, parsed from markup with
+/// [`RichText::parse`] and drawn with [`Canvas::draw_rich_text`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RichText {
+    spans: Vec<RichSpan>,
+}
+
+impl RichText {
+    /// Parse rich text from markup. Tags are written in square brackets, and apply until the
+    /// matching closing tag:
+    /// * `[color=red]...[/color]` changes the colour of the text. Colours can be written as hex
+    ///   (see [`ColorExt::from_hex`]), or as one of the names `black`, `white`, `grey`, `gray`,
+    ///   `red`, `green`, `blue`, `yellow`, `cyan` or `magenta`.
+    /// * `[b]...[/b]` makes the text bold.
+    /// * `[wave]...[/wave]` makes the letters bob up and down.
+    /// * `[shake]...[/shake]` makes the letters jitter around.
+    ///
+    /// Tags can be nested, and `[[` is a literal `[`. Tags that are still open at the end of
+    /// the text are closed automatically. Returns a `String` describing the error if a tag or
+    /// colour isn't recognised, or a closing tag doesn't match the last tag opened.
+    /// # Example
+    /// ```
+    /// # use simple_game_engine::canvas::RichText;
+    /// let text = RichText::parse("Take the [color=#ffd700][wave]golden key[/wave][/color]!")?;
+    /// assert_eq!(text.spans().len(), 3);
+    /// assert_eq!(text.plain_text(), "Take the golden key!");
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn parse(markup: &str) -> Result<Self, String> {
+        let mut text = Self::default();
+        let mut style = SpanStyle::default();
+        // The tags that are open, and the style before each one
+        let mut open: Vec<(&str, SpanStyle)> = Vec::new();
+        let mut rest = markup;
+        while let Some(start) = rest.find('[') {
+            text.push(&rest[..start], style);
+            rest = &rest[start + 1..];
+            if let Some(after) = rest.strip_prefix('[') {
+                text.push("[", style);
+                rest = after;
+                continue;
+            }
+            let end = rest
+                .find(']')
+                .ok_or_else(|| format!("Unclosed tag in {:?}", markup))?;
+            let tag = &rest[..end];
+            rest = &rest[end + 1..];
+            if let Some(name) = tag.strip_prefix('/') {
+                match open.pop() {
+                    Some((opened, previous)) if opened == name => style = previous,
+                    _ => return Err(format!("Unexpected closing tag [{}]", tag)),
+                }
+                continue;
+            }
+            let (name, value) = match tag.find('=') {
+                Some(i) => (&tag[..i], Some(&tag[i + 1..])),
+                None => (tag, None),
+            };
+            open.push((name, style));
+            match (name, value) {
+                ("color", Some(value)) => style.color = Some(parse_color(value)?),
+                ("b", None) => style.bold = true,
+                ("wave", None) => style.effect = Some(TextEffect::Wave),
+                ("shake", None) => style.effect = Some(TextEffect::Shake),
+                _ => return Err(format!("Unknown tag [{}]", tag)),
+            }
+        }
+        text.push(rest, style);
+        Ok(text)
+    }
+
+    /// Returns every span of the text, in order.
+    pub fn spans(&self) -> &[RichSpan] {
+        &self.spans
+    }
+
+    /// Returns the text without any styling.
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Add text to the end, joining it to the last span if it looks the same.
+    fn push(&mut self, text: &str, style: SpanStyle) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if span_style(last) == style => last.text.push_str(text),
+            _ => self.spans.push(RichSpan {
+                text: text.to_string(),
+                color: style.color,
+                bold: style.bold,
+                effect: style.effect,
+            }),
+        }
+    }
+}
+
+impl From<&str> for RichText {
+    /// Rich text with no styling, without parsing any markup.
+    fn from(text: &str) -> Self {
+        let mut rich = Self::default();
+        rich.push(text, SpanStyle::default());
+        rich
+    }
+}
+
+/// Returns the style of a span.
+fn span_style(span: &RichSpan) -> SpanStyle {
+    SpanStyle {
+        color: span.color,
+        bold: span.bold,
+        effect: span.effect,
+    }
+}
+
+/// Parse a colour name or hex colour.
+fn parse_color(value: &str) -> Result<Color, String> {
+    Ok(match value {
+        "black" => Color::BLACK,
+        "white" => Color::WHITE,
+        "grey" | "gray" => Color::GREY,
+        "red" => Color::RED,
+        "green" => Color::GREEN,
+        "blue" => Color::BLUE,
+        "yellow" => Color::YELLOW,
+        "cyan" => Color::CYAN,
+        "magenta" => Color::MAGENTA,
+        hex => Color::from_hex(hex)?,
+    })
+}
+
+/// A line of rich text, as the styled characters on it.
+type RichLine = Vec<(char, SpanStyle)>;
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns the width and height of rich text in pixels, laid out with `layout`. Returns a
+    /// `String` describing the error if the font has no glyph for one of the characters.
+    pub fn measure_rich_text(
+        &self,
+        text: &RichText,
+        layout: &TextLayout,
+    ) -> Result<(u32, u32), String> {
+        let text = self.layout_rich_text(text, Point::new(0, 0), layout)?;
+        Ok((text.width, text.height))
+    }
+
+    /// Draw rich text at `pos`, aligned and wrapped according to `layout`, in the current text
+    /// colour wherever the markup doesn't say otherwise. `time` is how long the text has been
+    /// shown in seconds, which animates effects such as `[wave]`. Returns the area covered by
+    /// the text, before the current transform is applied, or a `String` describing the error if
+    /// sdl fails or the font has no glyph for one of the characters.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{canvas::{RichText, TextLayout}, prelude::*};
+    /// # fn draw(canvas: &mut WindowCanvas, time: f64) -> Result<(), String> {
+    /// let text = RichText::parse("[b]Warning:[/b] the bridge is [color=red][shake]out[/shake][/color]")?;
+    /// canvas.draw_rich_text(&text, (10, 10), &TextLayout::default().wrapped(200), time)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_rich_text<P: Into<Point>>(
+        &mut self,
+        text: &RichText,
+        pos: P,
+        layout: &TextLayout,
+        time: f64,
+    ) -> Result<Rect, String> {
+        self.draw_rich_text_with(text, pos.into(), layout, time, |canvas, text, pos| {
+            canvas.draw_text(text, pos).map(|_| ())
+        })
+    }
+
+    /// Lay out rich text, then draw each run of letters that look the same with `draw`.
+    fn draw_rich_text_with<F>(
+        &mut self,
+        text: &RichText,
+        pos: Point,
+        layout: &TextLayout,
+        time: f64,
+        mut draw: F,
+    ) -> Result<Rect, String>
+    where
+        F: FnMut(&mut Self, &str, Point) -> Result<(), String>,
+    {
+        let text = self.layout_rich_text(text, pos, layout)?;
+        let (color, bold) = (self.fonts.fg_color(), self.fonts.bold());
+        let amplitude = (self.line_height() as f64 / 8.0).max(1.0);
+        let mut result = Ok(());
+        let mut index = 0;
+        'lines: for (line, pos) in &text.lines {
+            let mut x = pos.x();
+            let mut start = 0;
+            while start < line.len() {
+                let style = line[start].1;
+                let len = line[start..]
+                    .iter()
+                    .take_while(|(_, s)| *s == style)
+                    .count();
+                let run: String = line[start..start + len].iter().map(|(c, _)| c).collect();
+                self.fonts.set_fg_color(style.color.unwrap_or(color));
+                self.fonts.set_bold(bold || style.bold);
+                let drawn = match style.effect {
+                    None => {
+                        let drawn = draw(self, &run, Point::new(x, pos.y()));
+                        x += self.measure_line(&run)? as i32;
+                        index += len;
+                        drawn
+                    }
+                    Some(effect) => run.chars().try_for_each(|c| {
+                        let c = c.encode_utf8(&mut [0; 4]).to_string();
+                        let (dx, dy) = effect_offset(effect, index, time, amplitude);
+                        let drawn = draw(self, &c, Point::new(x + dx, pos.y() + dy));
+                        x += self.measure_line(&c)? as i32;
+                        index += 1;
+                        drawn
+                    }),
+                };
+                if drawn.is_err() {
+                    result = drawn;
+                    break 'lines;
+                }
+                start += len;
+            }
+        }
+        self.fonts.set_fg_color(color);
+        self.fonts.set_bold(bold);
+        result.map(|_| text.bounds())
+    }
+
+    /// Split rich text into lines, wrapping them like [`wrap_text`][Self::wrap_text], and work
+    /// out where each one goes.
+    fn layout_rich_text(
+        &self,
+        text: &RichText,
+        pos: Point,
+        layout: &TextLayout,
+    ) -> Result<LaidOutText<RichLine>, String> {
+        let chars: RichLine = text
+            .spans
+            .iter()
+            .flat_map(|span| span.text.chars().map(move |c| (c, span_style(span))))
+            .collect();
+        let mut lines = Vec::new();
+        for paragraph in chars.split(|(c, _)| *c == '\n') {
+            match layout.max_width {
+                Some(max_width) => self.wrap_rich_line(paragraph, max_width, &mut lines)?,
+                None => lines.push(paragraph.to_vec()),
+            }
+        }
+        let lines = lines
+            .into_iter()
+            .map(|line| Ok((self.measure_rich_line(&line)?, line)))
+            .collect::<Result<_, String>>()?;
+        Ok(self.place_lines(lines, pos, layout))
+    }
+
+    /// Wrap a paragraph of rich text into lines no wider than `max_width`.
+    fn wrap_rich_line(
+        &self,
+        paragraph: &[(char, SpanStyle)],
+        max_width: u32,
+        lines: &mut Vec<RichLine>,
+    ) -> Result<(), String> {
+        let mut line = RichLine::new();
+        for word in paragraph
+            .split(|(c, _)| *c == ' ')
+            .filter(|w| !w.is_empty())
+        {
+            let mut joined = line.clone();
+            if !joined.is_empty() {
+                joined.push((' ', word[0].1));
+            }
+            joined.extend_from_slice(word);
+            if self.measure_rich_line(&joined)? <= max_width {
+                line = joined;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(line);
+            }
+            line = word.to_vec();
+            // Break words that don't fit on a line of their own
+            while self.measure_rich_line(&line)? > max_width {
+                let mut split = 1;
+                while split < line.len() && self.measure_rich_line(&line[..=split])? <= max_width {
+                    split += 1;
+                }
+                let rest = line.split_off(split);
+                lines.push(line);
+                line = rest;
+            }
+        }
+        lines.push(line);
+        Ok(())
+    }
+
+    /// Returns the width of a line of rich text.
+    fn measure_rich_line(&self, line: &[(char, SpanStyle)]) -> Result<u32, String> {
+        let text: String = line.iter().map(|(c, _)| c).collect();
+        self.measure_line(&text)
+    }
+}
+
+impl<'a> SurfaceCanvas<'a> {
+    /// Draw rich text straight to the surface at `pos`. This is the `Canvas<Surface>` specific
+    /// alternative to [`draw_rich_text`][Canvas::draw_rich_text]; see
+    /// [`draw_text_surface`][Self::draw_text_surface].
+    pub fn draw_rich_text_surface<P: Into<Point>>(
+        &mut self,
+        text: &RichText,
+        pos: P,
+        layout: &TextLayout,
+        time: f64,
+    ) -> Result<Rect, String> {
+        self.draw_rich_text_with(text, pos.into(), layout, time, |canvas, text, pos| {
+            canvas.draw_text_surface(text, pos).map(|_| ())
+        })
+    }
+}
+
+/// Returns how far the letter at `index` is moved by an effect at `time`.
+fn effect_offset(effect: TextEffect, index: usize, time: f64, amplitude: f64) -> (i32, i32) {
+    match effect {
+        TextEffect::Wave => {
+            let phase = time * 6.0 - index as f64 * 0.6;
+            (0, (phase.sin() * amplitude).round() as i32)
+        }
+        TextEffect::Shake => {
+            // A new random offset 20 times a second
+            let step = (time * 20.0) as u64;
+            let mut hash =
+                (index as u64 ^ step.rotate_left(32)).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            hash ^= hash >> 29;
+            let jitter = |bits: u64| ((bits % 3) as i32 - 1) * amplitude.round() as i32;
+            (jitter(hash), jitter(hash >> 8))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test parsing markup into spans.
+    #[test]
+    fn test_parse() {
+        let text = RichText::parse("a [color=red]b [b]c[/b][/color][[d[wave]e").unwrap();
+        let styles: Vec<_> = text
+            .spans()
+            .iter()
+            .map(|s| (s.text.as_str(), s.color, s.bold, s.effect))
+            .collect();
+        assert_eq!(
+            styles,
+            vec![
+                ("a ", None, false, None),
+                ("b ", Some(Color::RED), false, None),
+                ("c", Some(Color::RED), true, None),
+                ("[d", None, false, None),
+                ("e", None, false, Some(TextEffect::Wave)),
+            ]
+        );
+        assert!(RichText::parse("[b]a[/color]").is_err());
+        assert!(RichText::parse("[color=plaid]a").is_err());
+        assert!(RichText::parse("[big]a[/big]").is_err());
+        assert!(RichText::parse("[b").is_err());
+    }
+
+    /// Test drawing rich text in several colours, wrapped across lines.
+    #[cfg(feature = "unifont")]
+    #[test]
+    fn test_draw_rich_text() {
+        use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+
+        let surface = Surface::new(64, 64, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_draw_color(Color::WHITE);
+        let text = RichText::parse("l [color=red]l[/color] [color=blue][shake]ll[/shake]").unwrap();
+        let layout = TextLayout::default().wrapped(24);
+        assert_eq!(canvas.measure_rich_text(&text, &layout), Ok((24, 32)));
+        let bounds = canvas
+            .draw_rich_text_surface(&text, (0, 0), &layout, 0.0)
+            .unwrap();
+        assert_eq!(bounds, Rect::new(0, 0, 24, 32));
+        assert_eq!(canvas.fonts.fg_color(), Color::WHITE);
+        let image = canvas.screenshot().unwrap();
+        let has = |color, rows: std::ops::Range<u32>| {
+            rows.flat_map(|y| (0..64).map(move |x| (x, y)))
+                .any(|(x, y)| image.pixel(x, y) == Some(color))
+        };
+        assert!(has(Color::WHITE, 0..16));
+        assert!(has(Color::RED, 0..16));
+        assert!(!has(Color::BLUE, 0..16));
+        // Shaken letters can move a couple of pixels outside their line
+        assert!(has(Color::BLUE, 14..34));
+    }
+}
@@ -0,0 +1,237 @@
+//! Capturing the contents of a canvas, and saving and loading image files.
+
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    render::RenderTarget,
+    surface::Surface,
+};
+
+use super::Canvas;
+
+/// An owned RGBA image, such as a screenshot taken with [`Canvas::screenshot`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    /// Width of the image, in pixels.
+    pub width: u32,
+    /// Height of the image, in pixels.
+    pub height: u32,
+    /// The pixels of the image, row by row, with 4 bytes per pixel, in the order red, green,
+    /// blue, alpha.
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Returns the colour of the pixel at `(x, y)`, or `None` if it's outside the image.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let offset = (y * self.width + x) as usize * 4;
+        let pixel = &self.pixels[offset..offset + 4];
+        Some(Color::RGBA(pixel[0], pixel[1], pixel[2], pixel[3]))
+    }
+
+    /// Returns a [`Surface`] that shares this image's pixels, for passing to the sdl2 API.
+    pub fn as_surface(&mut self) -> Result<Surface<'_>, String> {
+        Surface::from_data(
+            &mut self.pixels,
+            self.width,
+            self.height,
+            self.width * 4,
+            PixelFormatEnum::RGBA32,
+        )
+    }
+
+    /// Save the image as a BMP file. Returns a `String` describing the error if the file
+    /// couldn't be written.
+    pub fn save_bmp<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        // `as_surface` needs mutable access, but saving doesn't change anything
+        let mut pixels = self.pixels.clone();
+        let surface = Surface::from_data(
+            &mut pixels,
+            self.width,
+            self.height,
+            self.width * 4,
+            PixelFormatEnum::RGBA32,
+        )?;
+        surface.save_bmp(path)
+    }
+
+    /// Save the image as a PNG file. Returns a `String` describing the error if the file
+    /// couldn't be written.
+    #[cfg(feature = "png")]
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Decode the contents of a PNG file. Images in any colour format are converted to RGBA.
+    /// Returns a `String` describing the error if it isn't a valid PNG.
+    #[cfg(feature = "png")]
+    pub fn from_png(data: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+        let buffer = &buffer[..info.buffer_size()];
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer.to_vec(),
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+            // Palettes are expanded by the transformations
+            png::ColorType::Indexed => return Err("Unexpected palette image".to_string()),
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    /// Load a PNG file. Returns a `String` describing the error if the file couldn't be read,
+    /// or isn't a valid PNG.
+    #[cfg(feature = "png")]
+    pub fn load_png<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        std::fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| Self::from_png(&data))
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Capture everything drawn to the current render target so far. At the end of a frame,
+    /// after [`composite_layers`][Self::composite_layers], this is exactly what's about to be
+    /// shown on the screen. Returns a `String` describing the error if sdl fails.
+    pub fn screenshot(&self) -> Result<Image, String> {
+        let (width, height) = self.inner.output_size()?;
+        Ok(Image {
+            width,
+            height,
+            pixels: self.read_pixels(None)?,
+        })
+    }
+
+    /// Take a [`screenshot`][Self::screenshot] and save it in `dir`, with a name based on the
+    /// current date and time, such as `screenshot-20240131-235959-123.png`. `dir` is created if
+    /// it doesn't exist yet. Screenshots are saved as PNG files if the "png" feature is enabled,
+    /// or BMP files otherwise. Returns the path of the new file, or a `String` describing the
+    /// error if it couldn't be saved.
+    pub fn save_screenshot<P: AsRef<Path>>(&self, dir: P) -> Result<PathBuf, String> {
+        let image = self.screenshot()?;
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let name = format!("screenshot-{}", timestamp(SystemTime::now()));
+        let mut path = dir.join(name);
+        #[cfg(feature = "png")]
+        {
+            path.set_extension("png");
+            image.save_png(&path)?;
+        }
+        #[cfg(not(feature = "png"))]
+        {
+            path.set_extension("bmp");
+            image.save_bmp(&path)?;
+        }
+        Ok(path)
+    }
+}
+
+/// Format a time as `YYYYMMDD-HHMMSS-mmm`, in UTC.
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (days, seconds) = ((seconds / 86400) as i64, seconds % 86400);
+    // Convert days since the epoch to a date in the proleptic Gregorian calendar
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sdl2::rect::Rect;
+
+    use super::*;
+    use crate::{canvas::SurfaceCanvas, test_util::test_dir};
+
+    /// Test screenshot file names.
+    #[test]
+    fn test_timestamp() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_709_251_199_123);
+        assert_eq!(timestamp(time), "20240229-235959-123");
+        assert_eq!(timestamp(SystemTime::UNIX_EPOCH), "19700101-000000-000");
+    }
+
+    /// Test taking a screenshot of a surface, and saving it to a file.
+    #[test]
+    fn test_screenshot() {
+        let surface = Surface::new(4, 2, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_draw_color(Color::RED);
+        canvas.fill_rect(Rect::new(1, 1, 1, 1)).unwrap();
+        let image = canvas.screenshot().unwrap();
+        assert_eq!((image.width, image.height), (4, 2));
+        assert_eq!(image.pixel(1, 1), Some(Color::RED));
+        assert_eq!(image.pixel(4, 0), None);
+
+        // The directory is created if it's missing
+        let root = test_dir("screenshot");
+        let dir = root.join("screenshots");
+        let path = canvas.save_screenshot(&dir).unwrap();
+        assert!(path.starts_with(&dir));
+        #[cfg(not(feature = "png"))]
+        {
+            let loaded = Surface::load_bmp(&path).unwrap();
+            assert_eq!(loaded.size(), (4, 2));
+        }
+        #[cfg(feature = "png")]
+        assert_eq!(Image::load_png(&path), Ok(image));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,492 @@
+//! Scoped draw styles, which control how everything drawn through a canvas is blended with what's
+//! already there, and PGE-style pixel modes for drawing straight to a surface.
+
+use std::os::raw::c_int;
+
+use sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+    render::{BlendMode, Canvas as SdlCanvas, RenderTarget, Texture},
+    sys,
+};
+
+use super::{Canvas, Image, SurfaceCanvas};
+
+// sdl2-sys only knows the blend modes from before SDL 2.0.12, and its bindings take them as a
+// Rust enum, which can't hold any other value. These declare the same functions, taking the blend
+// mode as the plain 32-bit integer it is in C. Only the setters are declared: sdl2 reads blend
+// modes back into its enum, so a mode it doesn't know about is only ever set for a single draw
+// call, and a mode it knows about is always restored before returning.
+#[allow(clashing_extern_declarations)]
+extern "C" {
+    /// Set the blend mode used for drawing primitives with `renderer`.
+    fn SDL_SetRenderDrawBlendMode(renderer: *mut sys::SDL_Renderer, mode: u32) -> c_int;
+    /// Set the blend mode used for copying `texture`.
+    fn SDL_SetTextureBlendMode(texture: *mut sys::SDL_Texture, mode: u32) -> c_int;
+}
+
+/// `SDL_BLENDMODE_MUL`, which was added in SDL 2.0.12.
+const BLENDMODE_MUL: u32 = 8;
+
+/// How drawing is combined with what's already on the render target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Blend {
+    /// Draw over the target, letting it show through transparent parts.
+    Alpha,
+    /// Add to the target, brightening it. Good for light, fire and glows.
+    Additive,
+    /// Multiply the target, darkening it, while letting it show through transparent parts.
+    /// Good for shadows. Needs SDL 2.0.12 or later, and falls back to [`Modulate`][Self::Modulate]
+    /// with older versions.
+    Multiply,
+    /// Multiply the target, ignoring transparency.
+    Modulate,
+}
+
+impl Blend {
+    /// Returns the raw sdl blend mode.
+    fn raw(self) -> u32 {
+        match self {
+            Self::Alpha => sys::SDL_BlendMode::SDL_BLENDMODE_BLEND as u32,
+            Self::Additive => sys::SDL_BlendMode::SDL_BLENDMODE_ADD as u32,
+            Self::Multiply => BLENDMODE_MUL,
+            Self::Modulate => sys::SDL_BlendMode::SDL_BLENDMODE_MOD as u32,
+        }
+    }
+
+    /// Returns the closest blend mode sdl2 knows about, which is left set on the renderer
+    /// between draw calls.
+    fn sdl_mode(self) -> BlendMode {
+        match self {
+            Self::Alpha => BlendMode::Blend,
+            Self::Additive => BlendMode::Add,
+            Self::Multiply | Self::Modulate => BlendMode::Mod,
+        }
+    }
+}
+
+/// A blend mode and opacity applied to everything drawn through a [`Canvas`] while it's pushed
+/// with [`push_style`][Canvas::push_style], including primitives, text, sprites and tiles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawStyle {
+    /// How drawing is combined with the render target.
+    pub blend: Blend,
+    /// Opacity from 0.0 (invisible) to 1.0 (opaque), which multiplies the alpha of the draw
+    /// colour and of textures. Has no effect with [`Blend::Modulate`].
+    pub opacity: f32,
+}
+
+impl DrawStyle {
+    /// Create a style with the given blend mode and opacity.
+    pub fn new(blend: Blend, opacity: f32) -> Self {
+        Self { blend, opacity }
+    }
+
+    /// Create a style that alpha blends with the given opacity.
+    pub fn opacity(opacity: f32) -> Self {
+        Self::new(Blend::Alpha, opacity)
+    }
+
+    /// Returns the alpha value `alpha` is reduced to by this style's opacity.
+    fn apply_alpha(self, alpha: u8) -> u8 {
+        (alpha as f32 * self.opacity.clamp(0.0, 1.0)).round() as u8
+    }
+}
+
+impl Default for DrawStyle {
+    /// Alpha blending, fully opaque.
+    fn default() -> Self {
+        Self::opacity(1.0)
+    }
+}
+
+impl From<Blend> for DrawStyle {
+    fn from(blend: Blend) -> Self {
+        Self::new(blend, 1.0)
+    }
+}
+
+/// How [`SurfaceCanvas::draw_pixel`] and [`SurfaceCanvas::draw_image`] combine each pixel with
+/// the one already on the surface, like `olc::PixelGameEngine`'s `SetPixelMode`.
+#[derive(Default)]
+pub enum PixelMode {
+    /// Replace the pixel, including its alpha.
+    #[default]
+    Normal,
+    /// Only draw fully opaque pixels, skipping everything else. Good for sprites with a
+    /// transparent background.
+    Mask,
+    /// Alpha blend with the pixel underneath. The current [`DrawStyle`]'s opacity, if any, fades
+    /// every pixel further.
+    Alpha,
+    /// Call a function with the position on the surface, the new colour and the colour already
+    /// there, and draw the colour it returns.
+    Custom(Box<dyn FnMut(i32, i32, Color, Color) -> Color>),
+}
+
+impl PixelMode {
+    /// Returns the colour to draw at `(x, y)`, or `None` to leave it alone.
+    fn blend(&mut self, x: i32, y: i32, src: Color, dst: Color, opacity: f32) -> Option<Color> {
+        match self {
+            Self::Normal => Some(src),
+            Self::Mask if src.a == 255 => Some(src),
+            Self::Mask => None,
+            Self::Alpha => {
+                let alpha = src.a as f32 / 255.0 * opacity;
+                let mix =
+                    |s: u8, d: u8| (s as f32 * alpha + d as f32 * (1.0 - alpha)).round() as u8;
+                Some(Color::RGBA(
+                    mix(src.r, dst.r),
+                    mix(src.g, dst.g),
+                    mix(src.b, dst.b),
+                    mix(255, dst.a),
+                ))
+            }
+            Self::Custom(f) => Some(f(x, y, src, dst)),
+        }
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns the style currently applied to everything drawn through this canvas, or `None`
+    /// if drawing uses the sdl2 blend modes as normal.
+    pub fn style(&self) -> Option<DrawStyle> {
+        self.style
+    }
+
+    /// Apply `style` to everything drawn from now on, until [`pop_style`][Self::pop_style] is
+    /// called. The blend mode replaces the current style's, and the opacity multiplies it, so
+    /// nested translucent panels fade together. [`Blend::Multiply`] only applies to drawing
+    /// through this canvas's own methods; sdl2 routines called on the inner canvas modulate
+    /// instead.
+    pub fn push_style(&mut self, style: DrawStyle) {
+        let style = match self.style {
+            Some(current) => DrawStyle::new(style.blend, style.opacity * current.opacity),
+            None => {
+                self.unstyled_blend = self.inner.blend_mode();
+                style
+            }
+        };
+        self.style_stack.push(self.style);
+        self.style = Some(style);
+        self.apply_style();
+    }
+
+    /// Restore the style that was active before the last call to
+    /// [`push_style`][Self::push_style]. Does nothing if there's nothing to pop.
+    pub fn pop_style(&mut self) {
+        if let Some(style) = self.style_stack.pop() {
+            self.style = style;
+            self.apply_style();
+        }
+    }
+
+    /// Push a style, call `f`, then pop it again.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{canvas::{Blend, DrawStyle}, prelude::*};
+    /// # fn draw(canvas: &mut WindowCanvas) -> Result<(), String> {
+    /// // A half-transparent black shadow, then a glow that brightens whatever it's drawn over
+    /// canvas.with_style(DrawStyle::opacity(0.5), |canvas| {
+    ///     canvas.set_draw_color(Color::BLACK);
+    ///     canvas.fill_circle((52, 52), 20)
+    /// })?;
+    /// canvas.with_style(DrawStyle::new(Blend::Additive, 0.8), |canvas| {
+    ///     canvas.set_draw_color(Color::RGB(255, 160, 0));
+    ///     canvas.fill_circle((48, 48), 24)
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_style<F, R>(&mut self, style: DrawStyle, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.push_style(style);
+        let result = f(self);
+        self.pop_style();
+        result
+    }
+
+    /// Returns `color` with the current style's opacity applied.
+    pub(super) fn styled_color(&self, color: Color) -> Color {
+        match self.style {
+            Some(style) => Color::RGBA(color.r, color.g, color.b, style.apply_alpha(color.a)),
+            None => color,
+        }
+    }
+
+    /// Tell sdl about the current style's blend mode and draw colour.
+    fn apply_style(&mut self) {
+        let blend = match self.style {
+            Some(style) => style.blend.sdl_mode(),
+            None => self.unstyled_blend,
+        };
+        self.inner.set_blend_mode(blend);
+        self.inner
+            .set_draw_color(self.styled_color(self.draw_color));
+    }
+
+    /// Call `f` to draw primitives with the inner canvas, using the current style's blend mode.
+    /// Blend modes sdl2 doesn't know about are only set for the duration of `f`, so the inner
+    /// canvas never sees them. Falls back to modulating if sdl doesn't support multiplying.
+    pub(super) fn styled_draw<F>(&mut self, f: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut SdlCanvas<T>) -> Result<(), String>,
+    {
+        if self.style.map(|style| style.blend) != Some(Blend::Multiply) {
+            return f(&mut self.inner);
+        }
+        // Safety: the renderer is alive
+        if unsafe { SDL_SetRenderDrawBlendMode(self.inner.raw(), BLENDMODE_MUL) } != 0 {
+            return f(&mut self.inner);
+        }
+        let result = f(&mut self.inner);
+        self.inner.set_blend_mode(Blend::Multiply.sdl_mode());
+        result
+    }
+}
+
+/// Apply `style` to `texture`, call `f` to copy it, then restore the texture's own blend mode
+/// and alpha modulation. This is separate from [`Canvas`] so it can be used while fields of the
+/// canvas are borrowed.
+pub(super) fn with_texture_style<F>(
+    texture: &Texture,
+    style: Option<DrawStyle>,
+    f: F,
+) -> Result<(), String>
+where
+    F: FnOnce() -> Result<(), String>,
+{
+    let style = match style {
+        Some(style) => style,
+        None => return f(),
+    };
+    let raw = texture.raw();
+    let (blend, alpha) = (texture.blend_mode(), texture.alpha_mod());
+    // Safety: the texture is alive for as long as it's borrowed, and its settings are restored
+    // before returning, so the sdl2 `Texture` never sees a blend mode it doesn't know about.
+    unsafe {
+        if SDL_SetTextureBlendMode(raw, style.blend.raw()) != 0 {
+            SDL_SetTextureBlendMode(raw, style.blend.sdl_mode() as u32);
+        }
+        sys::SDL_SetTextureAlphaMod(raw, style.apply_alpha(alpha));
+    }
+    let result = f();
+    unsafe {
+        SDL_SetTextureBlendMode(raw, blend as u32);
+        sys::SDL_SetTextureAlphaMod(raw, alpha);
+    }
+    result
+}
+
+impl<'a> SurfaceCanvas<'a> {
+    /// Returns how [`draw_pixel`][Self::draw_pixel] and [`draw_image`][Self::draw_image]
+    /// combine pixels with the surface.
+    pub fn pixel_mode(&self) -> &PixelMode {
+        &self.pixel_mode
+    }
+
+    /// Set how [`draw_pixel`][Self::draw_pixel] and [`draw_image`][Self::draw_image] combine
+    /// pixels with the surface.
+    /// # Example
+    /// ```
+    /// # use simple_game_engine::{canvas::{PixelMode, SurfaceCanvas}, prelude::*};
+    /// # use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+    /// # let surface = Surface::new(8, 8, PixelFormatEnum::RGBA8888).unwrap();
+    /// # let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+    /// // Invert whatever is underneath, ignoring the colour being drawn
+    /// canvas.set_pixel_mode(PixelMode::Custom(Box::new(|_x, _y, _src, dst| {
+    ///     Color::RGBA(255 - dst.r, 255 - dst.g, 255 - dst.b, dst.a)
+    /// })));
+    /// canvas.draw_pixel((4, 4), Color::WHITE)?;
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn set_pixel_mode(&mut self, mode: PixelMode) {
+        self.pixel_mode = mode;
+    }
+
+    /// Draw a single pixel straight to the surface, combined with the pixel already there using
+    /// the current [`PixelMode`]. Only the position is transformed, and the pixel is clipped as
+    /// normal. Returns a `String` describing the error if sdl fails.
+    pub fn draw_pixel<P: Into<Point>>(&mut self, pos: P, color: Color) -> Result<(), String> {
+        let pos = self.transform.apply_point(pos.into());
+        self.plot(std::iter::once((pos, color)))
+    }
+
+    /// Draw an [`Image`] straight to the surface, with its top-left corner at `pos`, combining
+    /// each pixel with the surface using the current [`PixelMode`]. Only the position is
+    /// transformed, so the image is never scaled or rotated, and it's clipped as normal.
+    /// Returns a `String` describing the error if sdl fails.
+    pub fn draw_image<P: Into<Point>>(&mut self, image: &Image, pos: P) -> Result<(), String> {
+        let pos = self.transform.apply_point(pos.into());
+        let width = image.width as usize;
+        let pixels = image.pixels.chunks_exact(4).enumerate().map(|(i, p)| {
+            let pixel = pos.offset((i % width) as i32, (i / width) as i32);
+            (pixel, Color::RGBA(p[0], p[1], p[2], p[3]))
+        });
+        self.plot(pixels)
+    }
+
+    /// Combine pixels, in screen coordinates, with the surface.
+    fn plot<I: Iterator<Item = (Point, Color)>>(&mut self, pixels: I) -> Result<(), String> {
+        // Make sure anything sdl is still holding on to is drawn first
+        // Safety: the renderer is alive
+        if unsafe { sys::SDL_RenderFlush(self.inner.raw()) } != 0 {
+            return Err(sdl2::get_error());
+        }
+        let opacity = self.style.map_or(1.0, |style| style.opacity);
+        let mode = &mut self.pixel_mode;
+        let clip = self.clip;
+        let surface = self.inner.surface_mut();
+        let bounds = Rect::new(0, 0, surface.width(), surface.height());
+        let bounds = match clip.map_or(Some(bounds), |clip| clip.intersection(bounds)) {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+        let format = surface.pixel_format();
+        let bytes = surface.pixel_format_enum().byte_size_per_pixel();
+        let pitch = surface.pitch() as usize;
+        surface.with_lock_mut(|data| {
+            for (pos, src) in pixels {
+                if !bounds.contains_point(pos) {
+                    continue;
+                }
+                let offset = pos.y() as usize * pitch + pos.x() as usize * bytes;
+                let pixel = &mut data[offset..offset + bytes];
+                let mut raw = [0; 4];
+                raw[..bytes].copy_from_slice(pixel);
+                let dst = Color::from_u32(&format, u32::from_ne_bytes(raw));
+                if let Some(color) = mode.blend(pos.x(), pos.y(), src, dst, opacity) {
+                    pixel.copy_from_slice(&color.to_u32(&format).to_ne_bytes()[..bytes]);
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+
+    use super::*;
+
+    fn canvas() -> SurfaceCanvas<'static> {
+        let surface = Surface::new(4, 4, PixelFormatEnum::RGBA8888).unwrap();
+        SurfaceCanvas::new(surface.into_canvas().unwrap())
+    }
+
+    /// Test styles fade and blend primitives and textures, and are restored when popped.
+    #[test]
+    fn test_draw_style() {
+        let mut canvas = canvas();
+        canvas.set_draw_color(Color::RGB(100, 100, 100));
+        canvas.clear();
+        canvas.push_style(DrawStyle::opacity(0.5));
+        canvas.set_draw_color(Color::RGB(200, 0, 0));
+        canvas.fill_rect(Rect::new(0, 0, 1, 1)).unwrap();
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::RGB(149, 49, 49)));
+        canvas.with_style(DrawStyle::new(Blend::Additive, 0.5), |canvas| {
+            // The opacity is multiplied by the outer style's
+            assert_eq!(canvas.style().unwrap().opacity, 0.25);
+            canvas.fill_rect(Rect::new(1, 0, 1, 1)).unwrap();
+        });
+        assert_eq!(canvas.get_pixel((1, 0)), Ok(Color::RGB(150, 100, 100)));
+        canvas.pop_style();
+        assert_eq!(canvas.style(), None);
+        canvas.fill_rect(Rect::new(2, 0, 1, 1)).unwrap();
+        assert_eq!(canvas.get_pixel((2, 0)), Ok(Color::RGB(200, 0, 0)));
+
+        // A separate creator, so the texture doesn't borrow the canvas
+        let creator = (*canvas).texture_creator();
+        let mut texture = creator
+            .create_texture_static(PixelFormatEnum::RGBA32, 1, 1)
+            .unwrap();
+        texture.update(None, &[0, 0, 200, 255], 4).unwrap();
+        canvas.push_style(DrawStyle::opacity(0.5));
+        canvas.copy(&texture, None, Rect::new(3, 0, 1, 1)).unwrap();
+        canvas.pop_style();
+        assert_eq!(canvas.get_pixel((3, 0)), Ok(Color::RGB(49, 49, 149)));
+        assert_eq!(texture.alpha_mod(), 255);
+
+        // Multiplying is only set for each draw call, so sdl2 always reads back a blend mode
+        // it knows about
+        canvas.push_style(Blend::Multiply.into());
+        assert_eq!((*canvas).blend_mode(), BlendMode::Mod);
+        canvas.fill_rect(Rect::new(0, 1, 1, 1)).unwrap();
+        canvas.copy(&texture, None, Rect::new(1, 1, 1, 1)).unwrap();
+        assert_eq!((*canvas).blend_mode(), BlendMode::Mod);
+        assert_eq!(texture.blend_mode(), BlendMode::None);
+        canvas.pop_style();
+        assert_eq!((*canvas).blend_mode(), BlendMode::None);
+    }
+
+    /// Test a tile layer's opacity fades its tiles, without changing the canvas's style.
+    #[test]
+    fn test_tile_layer_opacity() {
+        use crate::tilemap::{Tile, TileLayer, Tilemap, Tileset};
+
+        let mut canvas = canvas();
+        canvas.set_draw_color(Color::RGB(100, 100, 100));
+        canvas.clear();
+        let creator = (*canvas).texture_creator();
+        let mut texture = creator
+            .create_texture_static(PixelFormatEnum::RGBA32, 1, 1)
+            .unwrap();
+        texture.update(None, &[200, 0, 0, 255], 4).unwrap();
+        let mut map = Tilemap::new(2, 1, 1, 1);
+        map.tilesets.push(Tileset::new(1, 1, 1, 1, 1));
+        let mut layer = TileLayer::new("ground", 2, 1);
+        layer.set(0, 0, Some(Tile::new(1)));
+        layer.opacity = 0.5;
+        let textures = [texture];
+        canvas
+            .draw_tile_layer(&map, &layer, &textures, Rect::new(0, 0, 2, 1))
+            .unwrap();
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::RGB(149, 49, 49)));
+        assert_eq!(canvas.style(), None);
+        assert_eq!(textures[0].alpha_mod(), 255);
+        layer.opacity = 1.0;
+        canvas
+            .draw_tile_layer(&map, &layer, &textures, Rect::new(0, 0, 2, 1))
+            .unwrap();
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::RGB(200, 0, 0)));
+    }
+
+    /// Test drawing pixels straight to a surface with each pixel mode.
+    #[test]
+    fn test_pixel_mode() {
+        let mut canvas = canvas();
+        canvas.set_draw_color(Color::RGB(0, 0, 100));
+        canvas.clear();
+        canvas
+            .draw_pixel((0, 0), Color::RGBA(200, 0, 0, 128))
+            .unwrap();
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::RGBA(200, 0, 0, 128)));
+
+        canvas.set_pixel_mode(PixelMode::Mask);
+        let mut image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 255, 255, 255, 255, 255, 255, 0],
+        };
+        canvas.draw_image(&image, (1, 0)).unwrap();
+        assert_eq!(canvas.get_pixel((1, 0)), Ok(Color::WHITE));
+        assert_eq!(canvas.get_pixel((2, 0)), Ok(Color::RGB(0, 0, 100)));
+
+        canvas.set_pixel_mode(PixelMode::Alpha);
+        image.pixels[7] = 102;
+        canvas.draw_image(&image, (1, 1)).unwrap();
+        assert_eq!(canvas.get_pixel((2, 1)), Ok(Color::RGB(102, 102, 162)));
+
+        canvas.set_pixel_mode(PixelMode::Custom(Box::new(|x, _, _, dst| {
+            Color::RGB(x as u8, dst.g, dst.b)
+        })));
+        canvas.push_clip(Rect::new(0, 2, 4, 1));
+        canvas.draw_pixel((3, 2), Color::BLACK).unwrap();
+        canvas.draw_pixel((3, 3), Color::BLACK).unwrap();
+        assert_eq!(canvas.get_pixel((3, 2)), Ok(Color::RGB(3, 0, 100)));
+        assert_eq!(canvas.get_pixel((3, 3)), Ok(Color::RGB(0, 0, 100)));
+    }
+}
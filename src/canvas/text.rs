@@ -0,0 +1,339 @@
+//! Measuring text, and drawing aligned, multi-line and word-wrapped text.
+
+use sdl2::{
+    rect::{Point, Rect},
+    render::RenderTarget,
+};
+
+use super::{Canvas, SurfaceCanvas};
+
+/// How lines of text are aligned horizontally with the position they're drawn at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    /// The position is the left edge of every line.
+    Left,
+    /// The position is the centre of every line.
+    Center,
+    /// The position is the right edge of every line.
+    Right,
+}
+
+/// How text is aligned vertically with the position it's drawn at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextBaseline {
+    /// The position is the top of the first line.
+    Top,
+    /// The position is the middle of all the lines.
+    Middle,
+    /// The position is the baseline of the first line, which letters sit on, so text in
+    /// different sizes can be lined up.
+    Baseline,
+    /// The position is the bottom of the last line.
+    Bottom,
+}
+
+/// How text is laid out by [`Canvas::draw_text_layout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextLayout {
+    /// Horizontal alignment of each line.
+    pub align: TextAlign,
+    /// Vertical alignment of the whole block of text.
+    pub baseline: TextBaseline,
+    /// If set, lines longer than this many pixels are wrapped between words. Words too long to
+    /// fit on a line by themselves are broken between letters.
+    pub max_width: Option<u32>,
+}
+
+impl TextLayout {
+    /// Create a layout with the given alignment, that doesn't wrap.
+    pub fn new(align: TextAlign, baseline: TextBaseline) -> Self {
+        Self {
+            align,
+            baseline,
+            max_width: None,
+        }
+    }
+
+    /// Wrap lines longer than `max_width` pixels.
+    pub fn wrapped(self, max_width: u32) -> Self {
+        Self {
+            max_width: Some(max_width),
+            ..self
+        }
+    }
+}
+
+impl Default for TextLayout {
+    /// Left aligned, with the position at the top, and no wrapping. This is how
+    /// [`draw_text`][Canvas::draw_text] draws text.
+    fn default() -> Self {
+        Self::new(TextAlign::Left, TextBaseline::Top)
+    }
+}
+
+/// Lines of text laid out by [`Canvas::place_lines`].
+pub(super) struct LaidOutText<L = String> {
+    /// Each line, and where its top-left corner goes.
+    pub(super) lines: Vec<(L, Point)>,
+    pub(super) left: i32,
+    pub(super) top: i32,
+    pub(super) width: u32,
+    pub(super) height: u32,
+}
+
+impl<L> LaidOutText<L> {
+    /// Returns the area covered by the text.
+    pub(super) fn bounds(&self) -> Rect {
+        Rect::new(self.left, self.top, self.width, self.height)
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns the width and height of `text` in pixels, as it would be drawn with the current
+    /// text renderer settings. Each `\n` starts a new line. Returns a `String` describing the
+    /// error if the font has no glyph for one of the characters.
+    pub fn measure_text(&self, text: &str) -> Result<(u32, u32), String> {
+        let mut width = 0;
+        for line in text.split('\n') {
+            width = width.max(self.measure_line(line)?);
+        }
+        Ok((width, self.line_height() * text.split('\n').count() as u32))
+    }
+
+    /// Split `text` into lines no wider than `max_width` pixels, breaking lines between words
+    /// where possible, and at every `\n`. Returns a `String` describing the error if the font
+    /// has no glyph for one of the characters.
+    pub fn wrap_text(&self, text: &str, max_width: u32) -> Result<Vec<String>, String> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            for word in paragraph.split(' ').filter(|w| !w.is_empty()) {
+                let joined = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                if self.measure_line(&joined)? <= max_width {
+                    line = joined;
+                    continue;
+                }
+                if !line.is_empty() {
+                    lines.push(line);
+                }
+                line = word.to_string();
+                // Break words that don't fit on a line of their own
+                while self.measure_line(&line)? > max_width {
+                    let split = self.fit_chars(&line, max_width)?;
+                    let rest = line.split_off(split);
+                    lines.push(line);
+                    line = rest;
+                }
+            }
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+
+    /// Returns the width and height of `text` in pixels, laid out with `layout`. Returns a
+    /// `String` describing the error if the font has no glyph for one of the characters.
+    pub fn measure_text_layout(
+        &self,
+        text: &str,
+        layout: &TextLayout,
+    ) -> Result<(u32, u32), String> {
+        let text = self.layout_lines(text, (0, 0).into(), layout)?;
+        Ok((text.width, text.height))
+    }
+
+    /// Draw `text` at `pos`, aligned and wrapped according to `layout`. Each `\n` starts a new
+    /// line. Returns the area covered by the text, before the current transform is applied, or a
+    /// `String` describing the error if sdl fails or the font has no glyph for one of the
+    /// characters.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{canvas::{TextAlign, TextBaseline, TextLayout}, prelude::*};
+    /// # fn draw(canvas: &mut WindowCanvas) -> Result<(), String> {
+    /// // A paragraph centred in a 320 pixel wide dialog box
+    /// let layout = TextLayout::new(TextAlign::Center, TextBaseline::Top).wrapped(300);
+    /// canvas.draw_text_layout("It's dangerous to go alone!\nTake this.", (160, 20), &layout)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_text_layout<P: Into<Point>>(
+        &mut self,
+        text: &str,
+        pos: P,
+        layout: &TextLayout,
+    ) -> Result<Rect, String> {
+        let text = self.layout_lines(text, pos.into(), layout)?;
+        for (line, pos) in &text.lines {
+            self.draw_text(line, *pos)?;
+        }
+        Ok(text.bounds())
+    }
+
+    /// Work out where each line of `text` that isn't empty goes, and the area covered by all of
+    /// them.
+    fn layout_lines(
+        &self,
+        text: &str,
+        pos: Point,
+        layout: &TextLayout,
+    ) -> Result<LaidOutText, String> {
+        let lines = match layout.max_width {
+            Some(max_width) => self.wrap_text(text, max_width)?,
+            None => text.split('\n').map(str::to_string).collect(),
+        };
+        let lines = lines
+            .into_iter()
+            .map(|line| Ok((self.measure_line(&line)?, line)))
+            .collect::<Result<_, String>>()?;
+        let mut text = self.place_lines(lines, pos, layout);
+        // Nothing to draw, but the line still takes up space
+        text.lines.retain(|(line, _)| !line.is_empty());
+        Ok(text)
+    }
+
+    /// Work out where lines of the given widths go, and the area covered by all of them.
+    pub(super) fn place_lines<L>(
+        &self,
+        lines: Vec<(u32, L)>,
+        pos: Point,
+        layout: &TextLayout,
+    ) -> LaidOutText<L> {
+        let line_height = self.line_height() as i32;
+        let height = line_height * lines.len() as i32;
+        let top = pos.y()
+            - match layout.baseline {
+                TextBaseline::Top => 0,
+                TextBaseline::Middle => height / 2,
+                TextBaseline::Baseline => self.scale_text_length(self.fonts.baseline()) as i32,
+                TextBaseline::Bottom => height,
+            };
+        let mut placed = Vec::new();
+        let (mut left, mut right) = (i32::MAX, i32::MIN);
+        for (i, (width, line)) in lines.into_iter().enumerate() {
+            let width = width as i32;
+            let x = pos.x()
+                - match layout.align {
+                    TextAlign::Left => 0,
+                    TextAlign::Center => width / 2,
+                    TextAlign::Right => width,
+                };
+            left = left.min(x);
+            right = right.max(x + width);
+            placed.push((line, Point::new(x, top + i as i32 * line_height)));
+        }
+        LaidOutText {
+            lines: placed,
+            left,
+            top,
+            width: (right - left) as u32,
+            height: height as u32,
+        }
+    }
+
+    /// Returns the width of a single line of text.
+    pub(super) fn measure_line(&self, line: &str) -> Result<u32, String> {
+        if line.is_empty() {
+            return Ok(0);
+        }
+        Ok(self.scale_text_length(self.fonts.measure_width(line)?))
+    }
+
+    /// Returns the height of a line of text.
+    pub(super) fn line_height(&self) -> u32 {
+        self.scale_text_length(self.fonts.line_height())
+    }
+
+    /// Returns the byte index of the end of the longest start of `text` that fits in
+    /// `max_width`, which is always at least one character.
+    fn fit_chars(&self, text: &str, max_width: u32) -> Result<usize, String> {
+        let mut end = text.chars().next().map_or(0, char::len_utf8);
+        for (i, c) in text.char_indices().skip(1) {
+            if self.measure_line(&text[..i + c.len_utf8()])? > max_width {
+                break;
+            }
+            end = i + c.len_utf8();
+        }
+        Ok(end)
+    }
+}
+
+impl<'a> SurfaceCanvas<'a> {
+    /// Draw `text` straight to the surface at `pos`, aligned and wrapped according to `layout`.
+    /// This is the `Canvas<Surface>` specific alternative to
+    /// [`draw_text_layout`][Canvas::draw_text_layout]; see
+    /// [`draw_text_surface`][Self::draw_text_surface]. Returns the area covered by the text,
+    /// before the current transform is applied, or a `String` describing the error if sdl fails
+    /// or the font has no glyph for one of the characters.
+    pub fn draw_text_surface_layout<P: Into<Point>>(
+        &mut self,
+        text: &str,
+        pos: P,
+        layout: &TextLayout,
+    ) -> Result<Rect, String> {
+        let text = self.layout_lines(text, pos.into(), layout)?;
+        for (line, pos) in &text.lines {
+            self.draw_text_surface(line, *pos)?;
+        }
+        Ok(text.bounds())
+    }
+}
+
+#[cfg(all(test, feature = "unifont"))]
+mod tests {
+    use sdl2::{
+        pixels::{Color, PixelFormatEnum},
+        surface::Surface,
+    };
+
+    use super::*;
+
+    fn canvas() -> SurfaceCanvas<'static> {
+        let surface = Surface::new(64, 64, PixelFormatEnum::RGBA8888).unwrap();
+        SurfaceCanvas::new(surface.into_canvas().unwrap())
+    }
+
+    /// Test measuring and wrapping text, where every ASCII character is 8 pixels wide.
+    #[test]
+    fn test_measure_and_wrap() {
+        let canvas = canvas();
+        assert_eq!(canvas.measure_text("ab\nabc"), Ok((24, 32)));
+        assert_eq!(canvas.measure_text(""), Ok((0, 16)));
+        assert_eq!(
+            canvas.wrap_text("one two  three\n\nfour", 56).unwrap(),
+            vec!["one two", "three", "", "four"]
+        );
+        assert_eq!(
+            canvas.wrap_text("a abcdefg", 24).unwrap(),
+            vec!["a", "abc", "def", "g"]
+        );
+        let layout = TextLayout::default().wrapped(56);
+        assert_eq!(
+            canvas.measure_text_layout("one two three", &layout),
+            Ok((56, 32))
+        );
+    }
+
+    /// Test aligning text around a point.
+    #[test]
+    fn test_draw_text_layout() {
+        let mut canvas = canvas();
+        canvas.set_draw_color(Color::WHITE);
+        let layout = TextLayout::new(TextAlign::Center, TextBaseline::Middle);
+        let bounds = canvas
+            .draw_text_surface_layout("ab\nabcd", (32, 32), &layout)
+            .unwrap();
+        assert_eq!(bounds, Rect::new(16, 16, 32, 32));
+        let layout = TextLayout::new(TextAlign::Right, TextBaseline::Baseline);
+        let bounds = canvas.draw_text_layout("ab", (64, 14), &layout).unwrap();
+        assert_eq!(bounds, Rect::new(48, 0, 16, 16));
+        // Somewhere in the "a" of the first line
+        let image = canvas.screenshot().unwrap();
+        let lit = (24..32)
+            .flat_map(|x| (16..32).map(move |y| (x, y)))
+            .any(|(x, y)| image.pixel(x, y) == Some(Color::WHITE));
+        assert!(lit);
+    }
+}
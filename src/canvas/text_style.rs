@@ -0,0 +1,289 @@
+//! Scaling text, and drawing outlines and drop shadows around it.
+
+use sdl2::{
+    pixels::{Color, PixelFormatEnum},
+    rect::Point,
+    render::{BlendMode, RenderTarget},
+    surface::Surface,
+};
+
+use super::{Canvas, Fonts};
+
+/// An outline drawn around every glyph, to keep text readable on busy backgrounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextOutline {
+    /// Colour of the outline.
+    pub color: Color,
+    /// Thickness of the outline, in pixels.
+    pub width: u32,
+}
+
+/// A copy of the text drawn behind it in a different colour, offset slightly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextShadow {
+    /// Colour of the shadow. A translucent colour gives a softer shadow.
+    pub color: Color,
+    /// How far the shadow is from the text, in pixels. Positive values move it right and down.
+    pub offset: Point,
+}
+
+/// How every piece of text drawn through a [`Canvas`] looks, on top of the active font's own
+/// settings. Set it with [`set_text_style`][Canvas::set_text_style].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    /// How much to scale text by, on top of the text renderer's integer scale or the active
+    /// `Font`'s size. Unifont glyphs are 16 pixels tall at a scale of 1. Whole numbers keep the
+    /// pixels crisp, while fractional scales make some rows and columns of pixels wider than
+    /// others.
+    pub scale: f32,
+    /// An outline around the text, if any.
+    pub outline: Option<TextOutline>,
+    /// A drop shadow behind the text, if any.
+    pub shadow: Option<TextShadow>,
+}
+
+impl TextStyle {
+    /// Create a style that scales text by `scale`, without an outline or shadow.
+    pub fn scaled(scale: f32) -> Self {
+        Self {
+            scale,
+            outline: None,
+            shadow: None,
+        }
+    }
+
+    /// Add an outline `width` pixels thick.
+    pub fn outlined(self, color: Color, width: u32) -> Self {
+        Self {
+            outline: Some(TextOutline { color, width }),
+            ..self
+        }
+    }
+
+    /// Add a drop shadow, `offset` pixels away from the text.
+    pub fn shadowed<P: Into<Point>>(self, color: Color, offset: P) -> Self {
+        let offset = offset.into();
+        Self {
+            shadow: Some(TextShadow { color, offset }),
+            ..self
+        }
+    }
+}
+
+impl Default for TextStyle {
+    /// Unscaled text with no outline or shadow.
+    fn default() -> Self {
+        Self::scaled(1.0)
+    }
+}
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns how text is scaled, outlined and shadowed.
+    pub fn text_style(&self) -> TextStyle {
+        self.text_style
+    }
+
+    /// Set how text is scaled, outlined and shadowed. This applies to every way of drawing
+    /// text, and scaling also changes how text is [measured][Self::measure_text]. Outlines and
+    /// shadows don't change the size or position of the text itself, and are drawn around it.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{canvas::TextStyle, prelude::*};
+    /// # fn draw(canvas: &mut WindowCanvas) -> Result<(), String> {
+    /// // Double size white text, with a black outline and shadow
+    /// canvas.set_text_style(
+    ///     TextStyle::scaled(2.0)
+    ///         .outlined(Color::BLACK, 2)
+    ///         .shadowed(Color::RGBA(0, 0, 0, 128), (3, 3)),
+    /// );
+    /// canvas.set_draw_color(Color::WHITE);
+    /// canvas.draw_text("Game Over", (100, 100))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_text_style(&mut self, style: TextStyle) {
+        self.text_style = style;
+    }
+
+    /// Returns `length` scaled by the text style's scale.
+    pub(super) fn scale_text_length(&self, length: u32) -> u32 {
+        scale_length(length, self.text_style.scale)
+    }
+}
+
+/// Render `text` with the selected font, then apply `style`. Returns the rendered text, and where its
+/// top-left corner goes relative to the top-left corner of the glyphs, as outlines and shadows
+/// can stick out above and to the left of them. This is separate from [`Canvas`] so it can be
+/// used while other fields of the canvas are borrowed.
+pub(super) fn render_text<'a>(
+    fonts: &'a Fonts,
+    style: TextStyle,
+    text: &str,
+) -> Result<(Surface<'a>, Point), String> {
+    let mut surface = fonts.draw(text)?;
+    if style.scale != 1.0 {
+        let width = scale_length(surface.width(), style.scale);
+        let height = scale_length(surface.height(), style.scale);
+        let mut scaled = Surface::new(width, height, PixelFormatEnum::RGBA8888)?;
+        surface.set_blend_mode(BlendMode::None)?;
+        surface.blit_scaled(None, &mut scaled, None)?;
+        surface = scaled;
+    }
+    if style.outline.is_none() && style.shadow.is_none() {
+        return Ok((surface, Point::new(0, 0)));
+    }
+    decorate(&surface, style.outline, style.shadow)
+}
+
+/// Returns `length` multiplied by `scale`, rounded to the nearest pixel.
+fn scale_length(length: u32, scale: f32) -> u32 {
+    (length as f32 * scale.max(0.0)).round() as u32
+}
+
+/// Draw an outline and shadow around rendered text, returning a new surface, and where it goes
+/// relative to the original.
+fn decorate(
+    glyphs: &Surface,
+    outline: Option<TextOutline>,
+    shadow: Option<TextShadow>,
+) -> Result<(Surface<'static>, Point), String> {
+    let glyphs = glyphs.convert_format(PixelFormatEnum::RGBA32)?;
+    let (width, height) = (glyphs.width() as i32, glyphs.height() as i32);
+    let pitch = glyphs.pitch() as usize;
+    let glyph_pixels = glyphs.without_lock().ok_or("Text surface needs locking")?;
+    let glyph = |x: i32, y: i32| -> [u8; 4] {
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return [0; 4];
+        }
+        let offset = y as usize * pitch + x as usize * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&glyph_pixels[offset..offset + 4]);
+        pixel
+    };
+
+    let border = outline.map_or(0, |o| o.width as i32);
+    let shadow_offset = shadow.map_or(Point::new(0, 0), |s| s.offset);
+    let left = border + (-shadow_offset.x()).max(0);
+    let top = border + (-shadow_offset.y()).max(0);
+    let out_width = width + 2 * border + shadow_offset.x().abs();
+    let out_height = height + 2 * border + shadow_offset.y().abs();
+
+    // The shape of the text, including its outline, as alpha values
+    let kernel: Vec<_> = (-border..=border)
+        .flat_map(|dy| (-border..=border).map(move |dx| (dx, dy)))
+        .filter(|(dx, dy)| dx * dx + dy * dy <= border * (border + 1))
+        .collect();
+    let mut shape = vec![0u8; (out_width * out_height) as usize];
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let (gx, gy) = (x - left, y - top);
+            shape[(y * out_width + x) as usize] = kernel
+                .iter()
+                .map(|(dx, dy)| glyph(gx + dx, gy + dy)[3])
+                .max()
+                .unwrap_or(0);
+        }
+    }
+    let shape_at = |x: i32, y: i32| -> u8 {
+        if x < 0 || y < 0 || x >= out_width || y >= out_height {
+            return 0;
+        }
+        shape[(y * out_width + x) as usize]
+    };
+
+    let mut pixels = vec![0u8; (out_width * out_height * 4) as usize];
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let pixel = &mut pixels[((y * out_width + x) * 4) as usize..][..4];
+            if let Some(shadow) = shadow {
+                let alpha = shape_at(x - shadow_offset.x(), y - shadow_offset.y());
+                blend_over(pixel, shadow.color, alpha);
+            }
+            if let Some(outline) = outline {
+                blend_over(pixel, outline.color, shape_at(x, y));
+            }
+            let [r, g, b, a] = glyph(x - left, y - top);
+            blend_over(pixel, Color::RGB(r, g, b), a);
+        }
+    }
+    let surface = Surface::from_data(
+        &mut pixels,
+        out_width as u32,
+        out_height as u32,
+        out_width as u32 * 4,
+        PixelFormatEnum::RGBA32,
+    )?
+    .convert_format(PixelFormatEnum::RGBA8888)?;
+    Ok((surface, Point::new(-left, -top)))
+}
+
+/// Draw `color`, with its alpha scaled by `coverage`, over an RGBA pixel.
+fn blend_over(pixel: &mut [u8], color: Color, coverage: u8) {
+    let src_alpha = color.a as f32 * coverage as f32 / (255.0 * 255.0);
+    if src_alpha == 0.0 {
+        return;
+    }
+    let dst_alpha = pixel[3] as f32 / 255.0;
+    let alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+    for (dst, src) in pixel[..3].iter_mut().zip([color.r, color.g, color.b]) {
+        let mixed = src as f32 * src_alpha + *dst as f32 * dst_alpha * (1.0 - src_alpha);
+        *dst = (mixed / alpha).round() as u8;
+    }
+    pixel[3] = (alpha * 255.0).round() as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test scaled text is measured and drawn at the new size, with the outline and shadow
+    /// around it.
+    #[cfg(feature = "unifont")]
+    #[test]
+    fn test_text_style() {
+        use crate::canvas::{SurfaceCanvas, TextAlign, TextBaseline, TextLayout};
+
+        let surface = Surface::new(64, 64, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_text_style(TextStyle::scaled(1.5));
+        assert_eq!(canvas.measure_text("ab"), Ok((24, 24)));
+        let layout = TextLayout::new(TextAlign::Left, TextBaseline::Baseline);
+        assert_eq!(canvas.measure_text_layout("a", &layout), Ok((12, 24)));
+
+        canvas.set_text_style(
+            TextStyle::scaled(2.0)
+                .outlined(Color::BLUE, 1)
+                .shadowed(Color::RED, (-3, 2)),
+        );
+        {
+            let (surface, offset) = render_text(&canvas.fonts, canvas.text_style(), "l").unwrap();
+            assert_eq!(offset, Point::new(-4, -1));
+            assert_eq!(surface.size(), (16 + 2 + 3, 32 + 2 + 2));
+        }
+
+        canvas.set_draw_color(Color::WHITE);
+        canvas.draw_text_surface("l", (8, 8)).unwrap();
+        let image = canvas.screenshot().unwrap();
+        let count = |color| {
+            (0..64)
+                .flat_map(|y| (0..64).map(move |x| (x, y)))
+                .filter(|&(x, y)| image.pixel(x, y) == Some(color))
+                .count()
+        };
+        assert!(count(Color::WHITE) > 0);
+        assert!(count(Color::BLUE) > 0);
+        assert!(count(Color::RED) > 0);
+        // Nothing sticks out further than the outline and shadow
+        assert_eq!(image.pixel(3, 8), Some(Color::RGBA(0, 0, 0, 0)));
+    }
+
+    /// Test blending colours over transparent and opaque pixels.
+    #[test]
+    fn test_blend_over() {
+        let mut pixel = [0, 0, 0, 0];
+        blend_over(&mut pixel, Color::RGBA(200, 100, 0, 255), 128);
+        assert_eq!(pixel, [200, 100, 0, 128]);
+        blend_over(&mut pixel, Color::RGB(0, 0, 200), 255);
+        assert_eq!(pixel, [0, 0, 200, 255]);
+    }
+}
@@ -0,0 +1,346 @@
+//! Drawing routines that respect the canvas's current [`Transform`].
+//!
+//! These have the same names and signatures as the equivalent methods on the sdl2 `Canvas`, so
+//! they're called instead of them via deref coersion. When no transform is active, they behave
+//! exactly the same as the sdl2 methods.
+
+use sdl2::{
+    rect::{Point, Rect},
+    render::{Canvas as SdlCanvas, RenderTarget, Texture},
+};
+
+use super::{style, Canvas};
+use crate::camera::Transform;
+
+impl<T: RenderTarget, U> Canvas<T, U> {
+    /// Returns the transform currently applied to everything drawn through this canvas.
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Apply `transform` to everything drawn from now on, on top of the current transform, until
+    /// [`pop_transform`][Self::pop_transform] is called.
+    pub fn push_transform(&mut self, transform: Transform) {
+        self.transform_stack.push(self.transform);
+        self.transform = self.transform * transform;
+    }
+
+    /// Restore the transform that was active before the last call to
+    /// [`push_transform`][Self::push_transform]. Does nothing if there's nothing to pop.
+    pub fn pop_transform(&mut self) {
+        if let Some(transform) = self.transform_stack.pop() {
+            self.transform = transform;
+        }
+    }
+
+    /// Push `transform`, call `f`, then pop it again.
+    pub fn with_transform<F, R>(&mut self, transform: Transform, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        self.push_transform(transform);
+        let result = f(self);
+        self.pop_transform();
+        result
+    }
+
+    /// Convert a point on the screen, such as the mouse cursor's position, to the coordinate
+    /// space of the current transform.
+    pub fn screen_to_world(&self, point: (f64, f64)) -> (f64, f64) {
+        self.transform.inverse().apply(point)
+    }
+
+    /// Convert a point in the coordinate space of the current transform to a point on the
+    /// screen.
+    pub fn world_to_screen(&self, point: (f64, f64)) -> (f64, f64) {
+        self.transform.apply(point)
+    }
+
+    /// Returns the corners of `rect` after transformation, in clockwise order.
+    pub(super) fn transform_corners(&self, rect: Rect) -> [(f64, f64); 4] {
+        let (left, top) = (rect.left() as f64, rect.top() as f64);
+        let (right, bottom) = (rect.right() as f64, rect.bottom() as f64);
+        [
+            self.transform.apply((left, top)),
+            self.transform.apply((right, top)),
+            self.transform.apply((right, bottom)),
+            self.transform.apply((left, bottom)),
+        ]
+    }
+
+    /// Draws a point, after applying the current transform.
+    pub fn draw_point<P: Into<Point>>(&mut self, point: P) -> Result<(), String> {
+        let point = self.transform.apply_point(point.into());
+        self.styled_draw(|inner| inner.draw_point(point))
+    }
+
+    /// Draws multiple points, after applying the current transform.
+    pub fn draw_points<'a, P: Into<&'a [Point]>>(&mut self, points: P) -> Result<(), String> {
+        let points = points.into();
+        if self.transform.is_identity() {
+            return self.styled_draw(|inner| inner.draw_points(points));
+        }
+        let points: Vec<_> = points
+            .iter()
+            .map(|p| self.transform.apply_point(*p))
+            .collect();
+        self.styled_draw(|inner| inner.draw_points(points.as_slice()))
+    }
+
+    /// Draws a line, after applying the current transform.
+    pub fn draw_line<P1: Into<Point>, P2: Into<Point>>(
+        &mut self,
+        start: P1,
+        end: P2,
+    ) -> Result<(), String> {
+        let start = self.transform.apply_point(start.into());
+        let end = self.transform.apply_point(end.into());
+        self.styled_draw(|inner| inner.draw_line(start, end))
+    }
+
+    /// Draws a series of connected lines, after applying the current transform.
+    pub fn draw_lines<'a, P: Into<&'a [Point]>>(&mut self, points: P) -> Result<(), String> {
+        let points = points.into();
+        if self.transform.is_identity() {
+            return self.styled_draw(|inner| inner.draw_lines(points));
+        }
+        let points: Vec<_> = points
+            .iter()
+            .map(|p| self.transform.apply_point(*p))
+            .collect();
+        self.styled_draw(|inner| inner.draw_lines(points.as_slice()))
+    }
+
+    /// Draws a rectangle outline, after applying the current transform. If the transform is
+    /// rotated, the rectangle is drawn rotated too.
+    pub fn draw_rect(&mut self, rect: Rect) -> Result<(), String> {
+        if self.transform.is_axis_aligned() {
+            let rect = self.transform.apply_rect(rect);
+            return self.styled_draw(|inner| inner.draw_rect(rect));
+        }
+        let corners = self.transform_corners(rect);
+        self.draw_polygon_raw(&corners)
+    }
+
+    /// Draws several rectangle outlines, after applying the current transform.
+    pub fn draw_rects(&mut self, rects: &[Rect]) -> Result<(), String> {
+        if self.transform.is_identity() {
+            return self.styled_draw(|inner| inner.draw_rects(rects));
+        }
+        rects.iter().try_for_each(|rect| self.draw_rect(*rect))
+    }
+
+    /// Fills a rectangle, after applying the current transform. If the transform is rotated,
+    /// the rectangle is drawn rotated too. `None` fills the entire render target, regardless of
+    /// the transform.
+    pub fn fill_rect<R: Into<Option<Rect>>>(&mut self, rect: R) -> Result<(), String> {
+        let rect = match rect.into() {
+            Some(rect) => rect,
+            None => return self.styled_draw(|inner| inner.fill_rect(None)),
+        };
+        if self.transform.is_axis_aligned() {
+            let rect = self.transform.apply_rect(rect);
+            return self.styled_draw(|inner| inner.fill_rect(rect));
+        }
+        let corners = self.transform_corners(rect);
+        self.fill_polygon_raw(&corners)
+    }
+
+    /// Fills several rectangles, after applying the current transform.
+    pub fn fill_rects(&mut self, rects: &[Rect]) -> Result<(), String> {
+        if self.transform.is_identity() {
+            return self.styled_draw(|inner| inner.fill_rects(rects));
+        }
+        rects.iter().try_for_each(|rect| self.fill_rect(*rect))
+    }
+
+    /// Draws the outline of a closed polygon, after applying the current transform.
+    pub fn draw_polygon(&mut self, points: &[Point]) -> Result<(), String> {
+        let points: Vec<_> = points
+            .iter()
+            .map(|p| self.transform.apply((p.x() as f64, p.y() as f64)))
+            .collect();
+        self.draw_polygon_raw(&points)
+    }
+
+    /// Fills a polygon using the even-odd rule, after applying the current transform.
+    pub fn fill_polygon(&mut self, points: &[Point]) -> Result<(), String> {
+        let points: Vec<_> = points
+            .iter()
+            .map(|p| self.transform.apply((p.x() as f64, p.y() as f64)))
+            .collect();
+        self.fill_polygon_raw(&points)
+    }
+
+    /// Draws the outline of a polygon that's already in screen coordinates.
+    fn draw_polygon_raw(&mut self, points: &[(f64, f64)]) -> Result<(), String> {
+        let mut points: Vec<_> = points
+            .iter()
+            .map(|&(x, y)| Point::new(x.round() as i32, y.round() as i32))
+            .collect();
+        if let Some(&first) = points.first() {
+            points.push(first);
+        }
+        self.styled_draw(|inner| inner.draw_lines(points.as_slice()))
+    }
+
+    /// Fills a polygon that's already in screen coordinates, one horizontal span at a time.
+    fn fill_polygon_raw(&mut self, points: &[(f64, f64)]) -> Result<(), String> {
+        self.styled_draw(|inner| inner.fill_rects(&polygon_spans(points)))
+    }
+
+    /// Copies a texture to the canvas, after applying the current transform. If the transform
+    /// is rotated, the texture is drawn rotated too. A `dst` of `None` stretches the texture
+    /// over the entire render target, regardless of the transform.
+    pub fn copy<R1, R2>(&mut self, texture: &Texture, src: R1, dst: R2) -> Result<(), String>
+    where
+        R1: Into<Option<Rect>>,
+        R2: Into<Option<Rect>>,
+    {
+        self.copy_ex(texture, src, dst, 0.0, None, false, false)
+    }
+
+    /// Copies a texture to the canvas, optionally rotating it by `angle` degrees around
+    /// `center` (relative to `dst`) and flipping it, after applying the current transform.
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_ex<R1, R2, P>(
+        &mut self,
+        texture: &Texture,
+        src: R1,
+        dst: R2,
+        angle: f64,
+        center: P,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+    ) -> Result<(), String>
+    where
+        R1: Into<Option<Rect>>,
+        R2: Into<Option<Rect>>,
+        P: Into<Option<Point>>,
+    {
+        let (canvas, transform) = (&mut self.inner, self.transform);
+        style::with_texture_style(texture, self.style, || {
+            copy_ex(
+                canvas,
+                transform,
+                texture,
+                src.into(),
+                dst.into(),
+                angle,
+                center.into(),
+                flip_horizontal,
+                flip_vertical,
+            )
+        })
+    }
+}
+
+/// Copies a texture to `canvas` after applying `transform`. This is separate from
+/// [`Canvas::copy_ex`] so it can be used while other fields of the canvas are borrowed.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn copy_ex<T: RenderTarget>(
+    canvas: &mut SdlCanvas<T>,
+    transform: Transform,
+    texture: &Texture,
+    src: Option<Rect>,
+    dst: Option<Rect>,
+    angle: f64,
+    center: Option<Point>,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+) -> Result<(), String> {
+    let plain = angle == 0.0 && !flip_horizontal && !flip_vertical;
+    let dst = match dst {
+        Some(dst) if !transform.is_identity() => dst,
+        dst if plain => return canvas.copy(texture, src, dst),
+        dst => {
+            return canvas.copy_ex(
+                texture,
+                src,
+                dst,
+                angle,
+                center,
+                flip_horizontal,
+                flip_vertical,
+            )
+        }
+    };
+    if plain && transform.is_axis_aligned() {
+        return canvas.copy(texture, src, transform.apply_rect(dst));
+    }
+    // Work out where the center of rotation ends up, then rebuild the destination around it
+    let scale = transform.scale.abs();
+    let pivot = center.map_or((dst.width() as f64 / 2.0, dst.height() as f64 / 2.0), |c| {
+        (c.x() as f64, c.y() as f64)
+    });
+    let (x, y) = transform.apply((dst.x() as f64 + pivot.0, dst.y() as f64 + pivot.1));
+    let pivot = (pivot.0 * scale, pivot.1 * scale);
+    let dst = Rect::new(
+        (x - pivot.0).round() as i32,
+        (y - pivot.1).round() as i32,
+        (dst.width() as f64 * scale).round() as u32,
+        (dst.height() as f64 * scale).round() as u32,
+    );
+    let center = center.map(|_| Point::new(pivot.0.round() as i32, pivot.1.round() as i32));
+    canvas.copy_ex(
+        texture,
+        src,
+        dst,
+        angle + transform.rotation,
+        center,
+        flip_horizontal,
+        flip_vertical,
+    )
+}
+
+/// Split a polygon into one-pixel-high rectangles covering every pixel whose center is inside
+/// it, using the even-odd rule. Polygons with a non-finite corner have no spans.
+pub(crate) fn polygon_spans(points: &[(f64, f64)]) -> Vec<Rect> {
+    let mut spans = Vec::new();
+    if points.len() < 3 || !points.iter().all(|p| p.0.is_finite() && p.1.is_finite()) {
+        return spans;
+    }
+    let top = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let bottom = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    let mut crossings = Vec::new();
+    for y in top.floor() as i32..bottom.ceil() as i32 {
+        let scan_y = y as f64 + 0.5;
+        crossings.clear();
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            if (y0 <= scan_y) != (y1 <= scan_y) {
+                crossings.push(x0 + (scan_y - y0) * (x1 - x0) / (y1 - y0));
+            }
+        }
+        crossings.sort_by(f64::total_cmp);
+        for pair in crossings.chunks_exact(2) {
+            let start = (pair[0] - 0.5).ceil() as i32;
+            let end = (pair[1] - 0.5).ceil() as i32;
+            if end > start {
+                spans.push(Rect::new(start, y, (end - start) as u32, 1));
+            }
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the spans of a filled polygon cover exactly the pixels inside it.
+    #[test]
+    fn test_polygon_spans() {
+        let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 2.0), (0.0, 2.0)];
+        assert_eq!(
+            polygon_spans(&square),
+            vec![Rect::new(0, 0, 4, 1), Rect::new(0, 1, 4, 1)]
+        );
+        let triangle = [(0.0, 0.0), (4.0, 4.0), (0.0, 4.0)];
+        let widths: Vec<_> = polygon_spans(&triangle).iter().map(|r| r.width()).collect();
+        assert_eq!(widths, vec![1, 2, 3]);
+        assert!(polygon_spans(&square[..2]).is_empty());
+        let broken = [(0.0, 0.0), (f64::NAN, 0.0), (2.0, f64::INFINITY)];
+        assert!(polygon_spans(&broken).is_empty());
+    }
+}
@@ -0,0 +1,188 @@
+//! TrueType and OpenType fonts, rasterised with `ab_glyph`.
+
+use std::{cell::RefCell, fs, path::Path, rc::Rc};
+
+use ab_glyph::{Font as _, FontVec, GlyphId, PxScale, ScaleFont};
+use fnv::FnvHashMap;
+
+use super::font::{Font, FontKind, Glyph};
+
+/// An outline font, which can be drawn at any size.
+#[derive(Clone)]
+pub(super) struct OutlineFont {
+    /// The parsed font file, shared between clones of the font.
+    font: Rc<FontVec>,
+    size: f32,
+    /// Glyphs rasterised at the current size.
+    cache: RefCell<FnvHashMap<char, Rc<Glyph>>>,
+}
+
+impl OutlineFont {
+    /// Returns the height of a line of text, in pixels.
+    pub(super) fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Change the height of a line of text, emptying the glyph cache.
+    pub(super) fn set_size(&mut self, size: f32) {
+        self.size = size;
+        self.cache.get_mut().clear();
+    }
+
+    /// Returns the height of a line of text, rounded up to whole pixels.
+    pub(super) fn line_height(&self) -> u32 {
+        self.font.as_scaled(self.scale()).height().ceil() as u32
+    }
+
+    /// Returns the distance from the top of a line to the baseline.
+    pub(super) fn baseline(&self) -> u32 {
+        self.font.as_scaled(self.scale()).ascent().round() as u32
+    }
+
+    /// Returns the glyph for `c` from the cache, rasterising it first if it isn't there yet, or
+    /// `None` if the font has no glyph for it.
+    pub(super) fn glyph(&self, c: char) -> Option<Rc<Glyph>> {
+        if let Some(glyph) = self.cache.borrow().get(&c) {
+            return Some(Rc::clone(glyph));
+        }
+        let glyph = Rc::new(self.rasterise(c)?);
+        self.cache.borrow_mut().insert(c, Rc::clone(&glyph));
+        Some(glyph)
+    }
+
+    /// Returns the kerning between two glyphs.
+    pub(super) fn kern(&self, first: &Glyph, second: &Glyph) -> f32 {
+        self.font
+            .as_scaled(self.scale())
+            .kern(GlyphId(first.id), GlyphId(second.id))
+    }
+
+    /// Returns the scale glyphs are drawn at.
+    fn scale(&self) -> PxScale {
+        PxScale::from(self.size.max(0.0))
+    }
+
+    /// Rasterise the glyph for `c`, with its origin at (0, 0).
+    fn rasterise(&self, c: char) -> Option<Glyph> {
+        let font = self.font.as_scaled(self.scale());
+        let id = font.glyph_id(c);
+        // Glyph 0 is the font's "missing glyph" box
+        if id.0 == 0 {
+            return None;
+        }
+        let mut glyph = Glyph {
+            id: id.0,
+            advance: font.h_advance(id),
+            left: 0,
+            top: 0,
+            width: 0,
+            height: 0,
+            coverage: Vec::new(),
+        };
+        // Glyphs such as spaces have no outline to draw
+        if let Some(outline) = self.font.outline_glyph(id.with_scale(self.scale())) {
+            let bounds = outline.px_bounds();
+            glyph.left = bounds.min.x as i32;
+            glyph.top = bounds.min.y as i32;
+            glyph.width = bounds.width() as u32;
+            glyph.height = bounds.height() as u32;
+            glyph.coverage = vec![0; (glyph.width * glyph.height) as usize];
+            let width = glyph.width;
+            let coverage = &mut glyph.coverage;
+            outline.draw(|x, y, c| {
+                coverage[(y * width + x) as usize] = (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+            });
+        }
+        Some(glyph)
+    }
+
+    /// Returns the number of glyphs in the cache.
+    #[cfg(test)]
+    pub(super) fn cached(&self) -> usize {
+        self.cache.borrow().len()
+    }
+}
+
+impl Font {
+    /// Load a TrueType or OpenType font from the contents of a `.ttf` or `.otf` file, with lines
+    /// `size` pixels tall. Returns a `String` describing the error if the data isn't a valid
+    /// font.
+    pub fn from_bytes(data: Vec<u8>, size: f32) -> Result<Self, String> {
+        let font = FontVec::try_from_vec(data).map_err(|e| e.to_string())?;
+        Ok(Self::new(
+            FontKind::Outline(OutlineFont {
+                font: Rc::new(font),
+                size,
+                cache: RefCell::new(FnvHashMap::default()),
+            }),
+            None,
+        ))
+    }
+
+    /// Load a TrueType or OpenType font from a `.ttf` or `.otf` file, with lines `size` pixels
+    /// tall. Returns a `String` describing the error if the file can't be read, or isn't a
+    /// valid font.
+    /// # Example
+    /// ```no_run
+    /// # use simple_game_engine::{canvas::Font, prelude::*};
+    /// # fn load(canvas: &mut WindowCanvas) -> Result<(), String> {
+    /// canvas.set_font(Some(Font::from_file("assets/font.ttf", 24.0)?));
+    /// canvas.draw_text("Press start", (100, 100))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P, size: f32) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_bytes(data, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::pixels::Color;
+
+    use super::*;
+
+    /// Load the test font, which only has glyphs for a space, `i` and `W`.
+    fn test_font(size: f32) -> Font {
+        let data = include_bytes!("../../tests/fixtures/test-font.ttf");
+        Font::from_bytes(data.to_vec(), size).unwrap()
+    }
+
+    /// Test measuring and drawing text, and caching glyphs.
+    #[test]
+    fn test_outline_font() {
+        let mut font = test_font(20.0);
+        assert!(Font::from_bytes(vec![1, 2, 3], 20.0).is_err());
+        let (narrow, wide) = (
+            font.measure_width("ii", false).unwrap(),
+            font.measure_width("WW", false).unwrap(),
+        );
+        assert!(narrow < wide);
+        assert_eq!(font.measure_width("WW", true), Ok(wide + 1));
+        assert!(font.measure_width("\u{10ffff}", false).is_err());
+        assert!(font.line_height() >= 20);
+        assert!(font.baseline() < font.line_height());
+
+        let surface = font.draw("W", Color::RED, false).unwrap();
+        assert_eq!(
+            surface.size(),
+            (font.measure_width("W", false).unwrap(), font.line_height())
+        );
+        let lit = surface
+            .without_lock()
+            .unwrap()
+            .chunks(4)
+            .any(|p| p != [0; 4]);
+        assert!(lit);
+        let cached = |font: &Font| match &font.kind {
+            FontKind::Outline(font) => font.cached(),
+            _ => unreachable!(),
+        };
+        assert_eq!(cached(&font), 2);
+        font.set_size(40.0);
+        assert_eq!(cached(&font), 0);
+        assert!(font.measure_width("WW", false).unwrap() > wide);
+    }
+}
@@ -0,0 +1,352 @@
+//! Colour utilities: conversion to and from HSV and HSL, interpolation, premultiplied alpha,
+//! hex strings and built-in palettes.
+//!
+//! [`Hsv`], [`Hsl`] and every palette colour convert into a [`Color`], so they can be passed
+//! straight to anything that accepts `Into<Color>`, such as
+//! [`Canvas::set_draw_color`][crate::canvas::Canvas::set_draw_color]. The rest of the
+//! utilities are methods on [`Color`] itself, provided by the [`ColorExt`] trait, which is
+//! included in the [prelude][crate::prelude].
+//! # Example
+//! ```no_run
+//! use simple_game_engine::{
+//!     color::{palette::Pico8, Hsv},
+//!     prelude::*,
+//! };
+//! # fn draw(canvas: &mut WindowCanvas, time: f64) -> Result<(), String> {
+//! // Cycle through the colours of the rainbow
+//! canvas.set_draw_color(Hsv::new(time * 90.0, 1.0, 1.0));
+//! canvas.clear();
+//!
+//! // Fade from one palette colour to another
+//! let fade = Color::from(Pico8::Red).lerp(Pico8::Yellow, (time % 1.0) as f32);
+//! canvas.set_draw_color(fade);
+//! canvas.fill_rect(Rect::new(10, 10, 50, 50))?;
+//!
+//! canvas.set_draw_color(Color::from_hex("#1d2b53")?);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod palette;
+
+use sdl2::pixels::Color;
+
+/// A colour in terms of hue, saturation and value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsv {
+    /// Hue, in degrees. Values outside of `0.0..360.0` wrap around.
+    pub h: f64,
+    /// Saturation, from `0.0` (grey) to `1.0` (fully saturated).
+    pub s: f64,
+    /// Value, from `0.0` (black) to `1.0` (full brightness).
+    pub v: f64,
+    /// Alpha component, as for [`Color`].
+    pub a: u8,
+}
+
+impl Hsv {
+    /// Create an opaque colour from its hue, saturation and value.
+    pub fn new(h: f64, s: f64, v: f64) -> Self {
+        Self { h, s, v, a: 255 }
+    }
+}
+
+impl From<Color> for Hsv {
+    fn from(color: Color) -> Self {
+        let (r, g, b) = unit_rgb(color);
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        Self {
+            h: hue(r, g, b, max, chroma),
+            s: if max == 0.0 { 0.0 } else { chroma / max },
+            v: max,
+            a: color.a,
+        }
+    }
+}
+
+impl From<Hsv> for Color {
+    fn from(hsv: Hsv) -> Self {
+        let v = hsv.v.clamp(0.0, 1.0);
+        let chroma = v * hsv.s.clamp(0.0, 1.0);
+        from_hue(hsv.h, chroma, v - chroma, hsv.a)
+    }
+}
+
+/// A colour in terms of hue, saturation and lightness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hsl {
+    /// Hue, in degrees. Values outside of `0.0..360.0` wrap around.
+    pub h: f64,
+    /// Saturation, from `0.0` (grey) to `1.0` (fully saturated).
+    pub s: f64,
+    /// Lightness, from `0.0` (black) to `1.0` (white).
+    pub l: f64,
+    /// Alpha component, as for [`Color`].
+    pub a: u8,
+}
+
+impl Hsl {
+    /// Create an opaque colour from its hue, saturation and lightness.
+    pub fn new(h: f64, s: f64, l: f64) -> Self {
+        Self { h, s, l, a: 255 }
+    }
+}
+
+impl From<Color> for Hsl {
+    fn from(color: Color) -> Self {
+        let (r, g, b) = unit_rgb(color);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let l = (max + min) / 2.0;
+        Self {
+            h: hue(r, g, b, max, chroma),
+            s: if chroma == 0.0 {
+                0.0
+            } else {
+                chroma / (1.0 - (2.0 * l - 1.0).abs())
+            },
+            l,
+            a: color.a,
+        }
+    }
+}
+
+impl From<Hsl> for Color {
+    fn from(hsl: Hsl) -> Self {
+        let l = hsl.l.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * hsl.s.clamp(0.0, 1.0);
+        from_hue(hsl.h, chroma, l - chroma / 2.0, hsl.a)
+    }
+}
+
+/// Returns the red, green and blue components of a colour, from `0.0` to `1.0`.
+fn unit_rgb(color: Color) -> (f64, f64, f64) {
+    (
+        color.r as f64 / 255.0,
+        color.g as f64 / 255.0,
+        color.b as f64 / 255.0,
+    )
+}
+
+/// Returns the hue shared by HSV and HSL, in degrees.
+fn hue(r: f64, g: f64, b: f64, max: f64, chroma: f64) -> f64 {
+    if chroma == 0.0 {
+        return 0.0;
+    }
+    let sector = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    sector * 60.0
+}
+
+/// Build a colour from a hue, its chroma, and the amount to add to every component to match
+/// the value or lightness.
+fn from_hue(h: f64, chroma: f64, m: f64, a: u8) -> Color {
+    let sector = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+    Color::RGBA(channel(r), channel(g), channel(b), a)
+}
+
+/// Convert an sRGB component to linear light, from `0.0` to `1.0`.
+fn to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a component in linear light back to sRGB.
+fn from_linear(c: f32) -> u8 {
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+/// Interpolate between two components, clamping `t` to `0.0..=1.0`.
+fn lerp_channel(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + (b as f32 - a as f32) * t.clamp(0.0, 1.0)).round() as u8
+}
+
+/// Extra methods for [`Color`].
+pub trait ColorExt: Sized {
+    /// Parse a colour from a hex string, in `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` format.
+    /// The `#` is optional. Returns a `String` describing the error if the string isn't a valid
+    /// colour.
+    fn from_hex(hex: &str) -> Result<Self, String>;
+
+    /// Format this colour as a lowercase `#rrggbb` string, or `#rrggbbaa` if it isn't opaque.
+    fn to_hex(&self) -> String;
+
+    /// Returns this colour with its alpha component replaced.
+    fn with_alpha(self, a: u8) -> Self;
+
+    /// Interpolate between this colour and `other`, where a `t` of `0.0` gives this colour and
+    /// `1.0` gives `other`. Each component is interpolated separately, which is fast, but can
+    /// look muddy in the middle of a fade between very different colours.
+    fn lerp<C: Into<Color>>(self, other: C, t: f32) -> Self;
+
+    /// Like [`lerp`][Self::lerp], but interpolates in linear light rather than sRGB, which looks
+    /// more natural at the cost of some speed. Alpha is still interpolated linearly.
+    fn lerp_linear<C: Into<Color>>(self, other: C, t: f32) -> Self;
+
+    /// Returns this colour with its red, green and blue components multiplied by its alpha, as
+    /// expected by textures using a premultiplied-alpha blend mode.
+    fn premultiplied(self) -> Self;
+
+    /// Undo [`premultiplied`][Self::premultiplied]. Fully transparent colours become black, as
+    /// their original colour can't be recovered.
+    fn unpremultiplied(self) -> Self;
+}
+
+impl ColorExt for Color {
+    fn from_hex(hex: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid colour {:?}", hex);
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let n = u32::from_str_radix(digits, 16).map_err(|_| invalid())?;
+        // Expand a short-form digit, such as `f`, to a full component, such as `ff`
+        let short = |shift: u32| ((n >> shift) & 0xf) as u8 * 0x11;
+        let long = |shift: u32| (n >> shift) as u8;
+        match digits.len() {
+            3 => Ok(Color::RGB(short(8), short(4), short(0))),
+            4 => Ok(Color::RGBA(short(12), short(8), short(4), short(0))),
+            6 => Ok(Color::RGB(long(16), long(8), long(0))),
+            8 => Ok(Color::RGBA(long(24), long(16), long(8), long(0))),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    fn with_alpha(self, a: u8) -> Self {
+        Color { a, ..self }
+    }
+
+    fn lerp<C: Into<Color>>(self, other: C, t: f32) -> Self {
+        let other = other.into();
+        Color::RGBA(
+            lerp_channel(self.r, other.r, t),
+            lerp_channel(self.g, other.g, t),
+            lerp_channel(self.b, other.b, t),
+            lerp_channel(self.a, other.a, t),
+        )
+    }
+
+    fn lerp_linear<C: Into<Color>>(self, other: C, t: f32) -> Self {
+        let other = other.into();
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| {
+            let (a, b) = (to_linear(a), to_linear(b));
+            from_linear(a + (b - a) * t)
+        };
+        Color::RGBA(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            lerp_channel(self.a, other.a, t),
+        )
+    }
+
+    fn premultiplied(self) -> Self {
+        let channel = |c: u8| ((c as u32 * self.a as u32 + 127) / 255) as u8;
+        Color::RGBA(channel(self.r), channel(self.g), channel(self.b), self.a)
+    }
+
+    fn unpremultiplied(self) -> Self {
+        if self.a == 0 {
+            return Color::RGBA(0, 0, 0, 0);
+        }
+        let a = self.a as u32;
+        let channel = |c: u8| ((c as u32 * 255 + a / 2) / a).min(255) as u8;
+        Color::RGBA(channel(self.r), channel(self.g), channel(self.b), self.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test converting to and from HSV and HSL.
+    #[test]
+    fn test_hsv_hsl() {
+        assert_eq!(Color::from(Hsv::new(0.0, 1.0, 1.0)), Color::RED);
+        assert_eq!(Color::from(Hsv::new(480.0, 1.0, 1.0)), Color::GREEN);
+        assert_eq!(Color::from(Hsl::new(240.0, 1.0, 0.5)), Color::BLUE);
+        assert_eq!(Color::from(Hsl::new(0.0, 0.0, 1.0)), Color::WHITE);
+        let color = Color::RGBA(30, 144, 255, 100);
+        assert_eq!(Color::from(Hsv::from(color)), color);
+        assert_eq!(Color::from(Hsl::from(color)), color);
+        let hsv = Hsv::from(Color::CYAN);
+        assert_eq!((hsv.h, hsv.s, hsv.v), (180.0, 1.0, 1.0));
+    }
+
+    /// Test parsing and formatting hex strings.
+    #[test]
+    fn test_hex() {
+        assert_eq!(Color::from_hex("#f80"), Ok(Color::RGB(255, 136, 0)));
+        assert_eq!(Color::from_hex("f808"), Ok(Color::RGBA(255, 136, 0, 136)));
+        assert_eq!(Color::from_hex("#1d2b53"), Ok(Color::RGB(29, 43, 83)));
+        assert_eq!(
+            Color::from_hex("#1d2b5380"),
+            Ok(Color::RGBA(29, 43, 83, 128))
+        );
+        assert!(Color::from_hex("#12345").is_err());
+        assert!(Color::from_hex("#+12").is_err());
+        assert_eq!(Color::RGB(29, 43, 83).to_hex(), "#1d2b53");
+        assert_eq!(Color::RGBA(29, 43, 83, 128).to_hex(), "#1d2b5380");
+    }
+
+    /// Test interpolation and premultiplied alpha.
+    #[test]
+    fn test_blending() {
+        assert_eq!(
+            Color::BLACK.lerp(Color::WHITE, 0.5),
+            Color::RGB(128, 128, 128)
+        );
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, 2.0), Color::WHITE);
+        // Half way in linear light is much brighter in sRGB
+        assert_eq!(
+            Color::BLACK.lerp_linear(Color::WHITE, 0.5),
+            Color::RGB(188, 188, 188)
+        );
+        let color = Color::RGBA(200, 100, 0, 128);
+        assert_eq!(color.premultiplied(), Color::RGBA(100, 50, 0, 128));
+        // Premultiplying loses some precision
+        assert_eq!(
+            color.premultiplied().unpremultiplied(),
+            Color::RGBA(199, 100, 0, 128)
+        );
+        assert_eq!(
+            Color::RGBA(9, 9, 9, 0).unpremultiplied(),
+            Color::RGBA(0, 0, 0, 0)
+        );
+    }
+}
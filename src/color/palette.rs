@@ -0,0 +1,209 @@
+//! Built-in palettes. Every palette colour converts into a [`Color`], so it can be used anywhere
+//! a colour is accepted.
+
+use sdl2::pixels::Color;
+
+/// The named colours from the [Pixel Game Engine](https://github.com/OneLoneCoder/olcPixelGameEngine).
+/// `RED`, `GREEN`, `BLUE` and so on are the same as the constants on [`Color`], but `GREY` is
+/// lighter.
+pub mod pge {
+    use sdl2::pixels::Color;
+
+    /// Light grey.
+    pub const GREY: Color = Color::RGB(192, 192, 192);
+    /// Mid grey.
+    pub const DARK_GREY: Color = Color::RGB(128, 128, 128);
+    /// Dark grey.
+    pub const VERY_DARK_GREY: Color = Color::RGB(64, 64, 64);
+    /// Pure red.
+    pub const RED: Color = Color::RGB(255, 0, 0);
+    /// Half-brightness red.
+    pub const DARK_RED: Color = Color::RGB(128, 0, 0);
+    /// Quarter-brightness red.
+    pub const VERY_DARK_RED: Color = Color::RGB(64, 0, 0);
+    /// Pure yellow.
+    pub const YELLOW: Color = Color::RGB(255, 255, 0);
+    /// Half-brightness yellow.
+    pub const DARK_YELLOW: Color = Color::RGB(128, 128, 0);
+    /// Quarter-brightness yellow.
+    pub const VERY_DARK_YELLOW: Color = Color::RGB(64, 64, 0);
+    /// Pure green.
+    pub const GREEN: Color = Color::RGB(0, 255, 0);
+    /// Half-brightness green.
+    pub const DARK_GREEN: Color = Color::RGB(0, 128, 0);
+    /// Quarter-brightness green.
+    pub const VERY_DARK_GREEN: Color = Color::RGB(0, 64, 0);
+    /// Pure cyan.
+    pub const CYAN: Color = Color::RGB(0, 255, 255);
+    /// Half-brightness cyan.
+    pub const DARK_CYAN: Color = Color::RGB(0, 128, 128);
+    /// Quarter-brightness cyan.
+    pub const VERY_DARK_CYAN: Color = Color::RGB(0, 64, 64);
+    /// Pure blue.
+    pub const BLUE: Color = Color::RGB(0, 0, 255);
+    /// Half-brightness blue.
+    pub const DARK_BLUE: Color = Color::RGB(0, 0, 128);
+    /// Quarter-brightness blue.
+    pub const VERY_DARK_BLUE: Color = Color::RGB(0, 0, 64);
+    /// Pure magenta.
+    pub const MAGENTA: Color = Color::RGB(255, 0, 255);
+    /// Half-brightness magenta.
+    pub const DARK_MAGENTA: Color = Color::RGB(128, 0, 128);
+    /// Quarter-brightness magenta.
+    pub const VERY_DARK_MAGENTA: Color = Color::RGB(64, 0, 64);
+    /// White.
+    pub const WHITE: Color = Color::RGB(255, 255, 255);
+    /// Black.
+    pub const BLACK: Color = Color::RGB(0, 0, 0);
+    /// Fully transparent.
+    pub const BLANK: Color = Color::RGBA(0, 0, 0, 0);
+}
+
+/// The 16 colours of the [PICO-8](https://www.lexaloffle.com/pico-8.php) fantasy console.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum Pico8 {
+    Black,
+    DarkBlue,
+    DarkPurple,
+    DarkGreen,
+    Brown,
+    DarkGrey,
+    LightGrey,
+    White,
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Lavender,
+    Pink,
+    LightPeach,
+}
+
+impl Pico8 {
+    /// Every colour in the palette, in PICO-8's order.
+    pub const PALETTE: [Color; 16] = [
+        Color::RGB(0x00, 0x00, 0x00),
+        Color::RGB(0x1d, 0x2b, 0x53),
+        Color::RGB(0x7e, 0x25, 0x53),
+        Color::RGB(0x00, 0x87, 0x51),
+        Color::RGB(0xab, 0x52, 0x36),
+        Color::RGB(0x5f, 0x57, 0x4f),
+        Color::RGB(0xc2, 0xc3, 0xc7),
+        Color::RGB(0xff, 0xf1, 0xe8),
+        Color::RGB(0xff, 0x00, 0x4d),
+        Color::RGB(0xff, 0xa3, 0x00),
+        Color::RGB(0xff, 0xec, 0x27),
+        Color::RGB(0x00, 0xe4, 0x36),
+        Color::RGB(0x29, 0xad, 0xff),
+        Color::RGB(0x83, 0x76, 0x9c),
+        Color::RGB(0xff, 0x77, 0xa8),
+        Color::RGB(0xff, 0xcc, 0xaa),
+    ];
+}
+
+impl From<Pico8> for Color {
+    fn from(color: Pico8) -> Self {
+        Pico8::PALETTE[color as usize]
+    }
+}
+
+/// The 16 colours of the IBM CGA text modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum Cga {
+    Black,
+    Blue,
+    Green,
+    Cyan,
+    Red,
+    Magenta,
+    Brown,
+    LightGrey,
+    DarkGrey,
+    LightBlue,
+    LightGreen,
+    LightCyan,
+    LightRed,
+    LightMagenta,
+    Yellow,
+    White,
+}
+
+impl Cga {
+    /// Every colour in the palette, in CGA's order.
+    pub const PALETTE: [Color; 16] = [
+        Color::RGB(0x00, 0x00, 0x00),
+        Color::RGB(0x00, 0x00, 0xaa),
+        Color::RGB(0x00, 0xaa, 0x00),
+        Color::RGB(0x00, 0xaa, 0xaa),
+        Color::RGB(0xaa, 0x00, 0x00),
+        Color::RGB(0xaa, 0x00, 0xaa),
+        Color::RGB(0xaa, 0x55, 0x00),
+        Color::RGB(0xaa, 0xaa, 0xaa),
+        Color::RGB(0x55, 0x55, 0x55),
+        Color::RGB(0x55, 0x55, 0xff),
+        Color::RGB(0x55, 0xff, 0x55),
+        Color::RGB(0x55, 0xff, 0xff),
+        Color::RGB(0xff, 0x55, 0x55),
+        Color::RGB(0xff, 0x55, 0xff),
+        Color::RGB(0xff, 0xff, 0x55),
+        Color::RGB(0xff, 0xff, 0xff),
+    ];
+}
+
+impl From<Cga> for Color {
+    fn from(color: Cga) -> Self {
+        Cga::PALETTE[color as usize]
+    }
+}
+
+/// One of the 64 colours an IBM EGA card can display, where the 6 bits of the index are, from
+/// most to least significant, the low red, green and blue bits, then the high red, green and
+/// blue bits. Indices above 63 wrap around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ega(pub u8);
+
+impl Ega {
+    /// The 16 colours an EGA card shows by default, which match the [`Cga`] palette.
+    pub const DEFAULT: [Ega; 16] = [
+        Ega(0),
+        Ega(1),
+        Ega(2),
+        Ega(3),
+        Ega(4),
+        Ega(5),
+        Ega(20),
+        Ega(7),
+        Ega(56),
+        Ega(57),
+        Ega(58),
+        Ega(59),
+        Ega(60),
+        Ega(61),
+        Ega(62),
+        Ega(63),
+    ];
+}
+
+impl From<Ega> for Color {
+    fn from(Ega(index): Ega) -> Self {
+        let channel = |high: u8, low: u8| (index >> high & 1) * 0xaa + (index >> low & 1) * 0x55;
+        Color::RGB(channel(2, 5), channel(1, 4), channel(0, 3))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the EGA palette's default colours match CGA.
+    #[test]
+    fn test_ega() {
+        let ega: Vec<Color> = Ega::DEFAULT.iter().map(|&c| c.into()).collect();
+        assert_eq!(ega, Cga::PALETTE);
+        assert_eq!(Color::from(Cga::Brown), Color::RGB(0xaa, 0x55, 0x00));
+        assert_eq!(Color::from(Pico8::LightPeach), Color::RGB(0xff, 0xcc, 0xaa));
+    }
+}
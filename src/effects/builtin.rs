@@ -0,0 +1,365 @@
+//! The effects that come with the engine.
+
+use fnv::FnvHashMap;
+use sdl2::pixels::Color;
+
+use super::Effect;
+use crate::canvas::Image;
+
+/// Darkens every few rows of pixels, like the gaps between the scanlines of a CRT monitor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scanlines {
+    /// Darken one row in every `spacing` rows.
+    pub spacing: u32,
+    /// How much to darken each scanline, from `0.0` (not at all) to `1.0` (black).
+    pub darkness: f32,
+}
+
+impl Default for Scanlines {
+    fn default() -> Self {
+        Self {
+            spacing: 2,
+            darkness: 0.3,
+        }
+    }
+}
+
+impl Effect for Scanlines {
+    fn apply(&mut self, frame: &mut Image, _elapsed_time: f64) {
+        let spacing = self.spacing.max(1);
+        let keep = 1.0 - self.darkness.clamp(0.0, 1.0);
+        let row_len = frame.width as usize * 4;
+        for (y, row) in frame.pixels.chunks_exact_mut(row_len).enumerate() {
+            if y as u32 % spacing == spacing - 1 {
+                for pixel in row.chunks_exact_mut(4) {
+                    scale_rgb(pixel, keep);
+                }
+            }
+        }
+    }
+}
+
+/// Darkens the edges and corners of the frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vignette {
+    /// How dark the corners get, from `0.0` (not at all) to `1.0` (black).
+    pub strength: f32,
+    /// Where the darkening starts, as a fraction of the distance from the center to a corner.
+    pub radius: f32,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self {
+            strength: 0.6,
+            radius: 0.5,
+        }
+    }
+}
+
+impl Effect for Vignette {
+    fn apply(&mut self, frame: &mut Image, _elapsed_time: f64) {
+        let (cx, cy) = (frame.width as f32 / 2.0, frame.height as f32 / 2.0);
+        let corner = cx.hypot(cy).max(1.0);
+        let radius = self.radius.clamp(0.0, 1.0);
+        let width = frame.width as usize;
+        for (i, pixel) in frame.pixels.chunks_exact_mut(4).enumerate() {
+            let (x, y) = ((i % width) as f32 + 0.5, (i / width) as f32 + 0.5);
+            let distance = (x - cx).hypot(y - cy) / corner;
+            if distance <= radius {
+                continue;
+            }
+            // Smoothstep from the radius to the corners
+            let t = ((distance - radius) / (1.0 - radius)).min(1.0);
+            let fade = t * t * (3.0 - 2.0 * t);
+            scale_rgb(pixel, 1.0 - self.strength * fade);
+        }
+    }
+}
+
+/// Makes bright parts of the frame bleed light into their surroundings, a cheap imitation of
+/// bloom.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glow {
+    /// How bright a pixel must be to glow, from 0 to 255.
+    pub threshold: u8,
+    /// How far the glow spreads, in pixels.
+    pub radius: u32,
+    /// How bright the glow is, where `1.0` adds the full brightness of the glowing pixels.
+    pub intensity: f32,
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Self {
+            threshold: 200,
+            radius: 4,
+            intensity: 0.8,
+        }
+    }
+}
+
+impl Effect for Glow {
+    fn apply(&mut self, frame: &mut Image, _elapsed_time: f64) {
+        let (width, height) = (frame.width as usize, frame.height as usize);
+        // Keep only the bright pixels
+        let mut glow: Vec<[f32; 3]> = frame
+            .pixels
+            .chunks_exact(4)
+            .map(|p| {
+                if luma(p) >= self.threshold {
+                    [p[0] as f32, p[1] as f32, p[2] as f32]
+                } else {
+                    [0.0; 3]
+                }
+            })
+            .collect();
+        let radius = self.radius as usize;
+        box_blur(&mut glow, width, height, 1, width, radius);
+        box_blur(&mut glow, height, width, width, 1, radius);
+        for (pixel, glow) in frame.pixels.chunks_exact_mut(4).zip(glow) {
+            for (c, g) in pixel.iter_mut().zip(glow.iter()) {
+                *c = (*c as f32 + g * self.intensity).min(255.0) as u8;
+            }
+        }
+    }
+}
+
+/// Blur lines of pixels in one direction. There are `lines` lines of `len` pixels, where
+/// neighbouring pixels are `step` apart, and neighbouring lines start `line_step` apart.
+fn box_blur(
+    pixels: &mut [[f32; 3]],
+    len: usize,
+    lines: usize,
+    step: usize,
+    line_step: usize,
+    radius: usize,
+) {
+    if radius == 0 || len == 0 {
+        return;
+    }
+    let mut line = vec![[0.0; 3]; len];
+    let window = (2 * radius + 1) as f32;
+    for l in 0..lines {
+        let start = l * line_step;
+        for (i, pixel) in line.iter_mut().enumerate() {
+            *pixel = pixels[start + i * step];
+        }
+        // A running sum over the window, treating pixels past the edges as black
+        let mut sum = [0.0; 3];
+        for pixel in &line[..radius.min(len)] {
+            add(&mut sum, pixel, 1.0);
+        }
+        for i in 0..len {
+            if i + radius < len {
+                add(&mut sum, &line[i + radius], 1.0);
+            }
+            if i > radius {
+                add(&mut sum, &line[i - radius - 1], -1.0);
+            }
+            pixels[start + i * step] = [sum[0] / window, sum[1] / window, sum[2] / window];
+        }
+    }
+}
+
+fn add(sum: &mut [f32; 3], pixel: &[f32; 3], sign: f32) {
+    for (s, p) in sum.iter_mut().zip(pixel) {
+        *s += p * sign;
+    }
+}
+
+/// Replaces every colour with the nearest colour from a palette, for a retro look.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteReduction {
+    /// The colours allowed in the frame.
+    pub palette: Vec<Color>,
+}
+
+impl PaletteReduction {
+    /// Create an effect that reduces the frame to the given colours, such as one of the
+    /// [built-in palettes][crate::color::palette].
+    pub fn new<I>(palette: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Color>,
+    {
+        Self {
+            palette: palette.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the palette colour closest to `rgb`.
+    fn nearest(&self, rgb: [u8; 3]) -> Option<Color> {
+        let distance = |c: &&Color| {
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(c.r, rgb[0]) + d(c.g, rgb[1]) + d(c.b, rgb[2])
+        };
+        self.palette.iter().min_by_key(distance).copied()
+    }
+}
+
+impl Effect for PaletteReduction {
+    fn apply(&mut self, frame: &mut Image, _elapsed_time: f64) {
+        // Frames usually have far fewer distinct colours than pixels
+        let mut cache = FnvHashMap::default();
+        for pixel in frame.pixels.chunks_exact_mut(4) {
+            let rgb = [pixel[0], pixel[1], pixel[2]];
+            let nearest = *cache.entry(rgb).or_insert_with(|| self.nearest(rgb));
+            if let Some(color) = nearest {
+                pixel[..3].copy_from_slice(&[color.r, color.g, color.b]);
+            }
+        }
+    }
+}
+
+/// Shakes the whole frame, for impacts and explosions. Call [`shake`][Self::shake] to start
+/// shaking, which dies down over time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenShake {
+    /// The furthest the frame can move in each direction, in pixels.
+    pub max_offset: f32,
+    /// How much [`trauma`][Self::trauma] wears off per second.
+    pub decay: f32,
+    trauma: f32,
+    seed: u32,
+}
+
+impl ScreenShake {
+    /// Create a screen shake effect, which doesn't shake until [`shake`][Self::shake] is called.
+    pub fn new(max_offset: f32, decay: f32) -> Self {
+        Self {
+            max_offset,
+            decay,
+            trauma: 0.0,
+            seed: 0x9e37_79b9,
+        }
+    }
+
+    /// Add `amount` of trauma, from `0.0` to `1.0`. The frame shakes more violently the more
+    /// trauma there is, up to a maximum of `1.0`.
+    pub fn shake(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Returns the current amount of trauma.
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Returns a pseudo-random number from -1.0 to 1.0, using xorshift.
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+impl Effect for ScreenShake {
+    fn apply(&mut self, frame: &mut Image, elapsed_time: f64) {
+        if self.trauma <= 0.0 {
+            return;
+        }
+        // Squaring the trauma makes small shakes subtle and big ones violent
+        let amount = self.max_offset * self.trauma * self.trauma;
+        let dx = (self.random() * amount).round() as i64;
+        let dy = (self.random() * amount).round() as i64;
+        self.trauma = (self.trauma - self.decay * elapsed_time as f32).max(0.0);
+        let (width, height) = (frame.width as i64, frame.height as i64);
+        let source = frame.pixels.clone();
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = (x - dx, y - dy);
+                let offset = ((y * width + x) * 4) as usize;
+                let pixel = &mut frame.pixels[offset..offset + 4];
+                if sx < 0 || sy < 0 || sx >= width || sy >= height {
+                    pixel.copy_from_slice(&[0, 0, 0, 255]);
+                } else {
+                    let source_offset = ((sy * width + sx) * 4) as usize;
+                    pixel.copy_from_slice(&source[source_offset..source_offset + 4]);
+                }
+            }
+        }
+    }
+}
+
+/// Multiply the red, green and blue components of an RGBA pixel by `factor`.
+fn scale_rgb(pixel: &mut [u8], factor: f32) {
+    for c in &mut pixel[..3] {
+        *c = (*c as f32 * factor).round() as u8;
+    }
+}
+
+/// Returns the perceived brightness of an RGBA pixel, from 0 to 255.
+fn luma(pixel: &[u8]) -> u8 {
+    (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::palette::Cga;
+
+    fn image(width: u32, height: u32, color: Color) -> Image {
+        Image {
+            width,
+            height,
+            pixels: [color.r, color.g, color.b, color.a]
+                .iter()
+                .copied()
+                .cycle()
+                .take((width * height * 4) as usize)
+                .collect(),
+        }
+    }
+
+    /// Test the simple per-pixel effects.
+    #[test]
+    fn test_pixel_effects() {
+        let mut frame = image(2, 4, Color::WHITE);
+        Scanlines::default().apply(&mut frame, 0.0);
+        assert_eq!(frame.pixel(0, 0), Some(Color::WHITE));
+        assert_eq!(frame.pixel(1, 3), Some(Color::RGB(179, 179, 179)));
+
+        let mut frame = image(2, 2, Color::RGB(200, 90, 10));
+        PaletteReduction::new(Cga::PALETTE.iter().copied()).apply(&mut frame, 0.0);
+        assert_eq!(frame.pixel(1, 1), Some(Cga::Brown.into()));
+
+        let mut frame = image(9, 9, Color::WHITE);
+        Vignette::default().apply(&mut frame, 0.0);
+        assert_eq!(frame.pixel(4, 4), Some(Color::WHITE));
+        assert!(frame.pixel(0, 0).unwrap().r < 128);
+    }
+
+    /// Test glow spreads from bright pixels.
+    #[test]
+    fn test_glow() {
+        let mut frame = image(5, 5, Color::BLACK);
+        frame.pixels[(2 * 5 + 2) * 4..][..3].copy_from_slice(&[255, 255, 255]);
+        Glow {
+            threshold: 128,
+            radius: 1,
+            intensity: 1.0,
+        }
+        .apply(&mut frame, 0.0);
+        // The pixel is spread over a 3x3 square
+        assert_eq!(frame.pixel(1, 1), Some(Color::RGB(28, 28, 28)));
+        assert_eq!(frame.pixel(0, 0), Some(Color::BLACK));
+    }
+
+    /// Test screen shake moves the frame, then settles down.
+    #[test]
+    fn test_screen_shake() {
+        let mut shake = ScreenShake::new(4.0, 1.0);
+        let mut frame = image(8, 8, Color::WHITE);
+        shake.apply(&mut frame, 0.5);
+        assert_eq!(frame, image(8, 8, Color::WHITE));
+        shake.shake(2.0);
+        assert_eq!(shake.trauma(), 1.0);
+        shake.apply(&mut frame, 0.5);
+        assert_ne!(frame, image(8, 8, Color::WHITE));
+        assert_eq!(shake.trauma(), 0.5);
+        shake.apply(&mut frame, 1.0);
+        assert_eq!(shake.trauma(), 0.0);
+    }
+}
@@ -0,0 +1,161 @@
+//! Post-processing effects, applied to the whole frame just before it's shown.
+//!
+//! Effects run on the CPU, on a copy of the frame read back from the canvas, so they work the
+//! same with any renderer, and can be tested without a window. Add effects to the canvas's
+//! [`EffectChain`] with [`Canvas::effects_mut`][crate::canvas::Canvas::effects_mut], and the
+//! engine applies them at the end of every frame. Reading back the frame is slow on some
+//! renderers, so keep the window small when using effects.
+//! # Example
+//! ```no_run
+//! use simple_game_engine::{
+//!     effects::{ScreenShake, Scanlines, Vignette},
+//!     prelude::*,
+//! };
+//! # fn create(canvas: &mut WindowCanvas) {
+//! canvas.effects_mut().push(Scanlines::default());
+//! canvas.effects_mut().push(Vignette::default());
+//! canvas.effects_mut().push(ScreenShake::new(8.0, 1.5));
+//! # }
+//! # fn update(canvas: &mut WindowCanvas) {
+//! // Later, when something explodes
+//! if let Some(shake) = canvas.effects_mut().get_mut::<ScreenShake>() {
+//!     shake.shake(0.5);
+//! }
+//! # }
+//! ```
+
+mod builtin;
+pub use builtin::{Glow, PaletteReduction, Scanlines, ScreenShake, Vignette};
+
+use crate::canvas::Image;
+
+mod private {
+    use std::any::Any;
+
+    /// Lets [`EffectChain`][super::EffectChain] find effects by type. Implemented for every
+    /// type automatically.
+    pub trait AsAny: Any {
+        /// Returns `self` as an `Any`.
+        fn as_any(&self) -> &dyn Any;
+        /// Returns `self` as a mutable `Any`.
+        fn as_any_mut(&mut self) -> &mut dyn Any;
+    }
+
+    impl<T: Any> AsAny for T {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+}
+
+/// A post-processing effect, which changes the pixels of a whole frame.
+pub trait Effect: private::AsAny {
+    /// Apply the effect to `frame`. `elapsed_time` is the time since the last frame in seconds,
+    /// for effects that change over time.
+    fn apply(&mut self, frame: &mut Image, elapsed_time: f64);
+}
+
+/// A list of [`Effect`]s, applied in the order they were added.
+#[derive(Default)]
+pub struct EffectChain {
+    effects: Vec<Box<dyn Effect>>,
+}
+
+impl EffectChain {
+    /// Create an empty effect chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an effect to the end of the chain.
+    pub fn push<E: Effect>(&mut self, effect: E) {
+        self.effects.push(Box::new(effect));
+    }
+
+    /// Returns the first effect of type `E` in the chain, if any.
+    pub fn get<E: Effect>(&self) -> Option<&E> {
+        self.effects
+            .iter()
+            .find_map(|e| (**e).as_any().downcast_ref())
+    }
+
+    /// Returns the first effect of type `E` in the chain, if any, so it can be changed.
+    pub fn get_mut<E: Effect>(&mut self) -> Option<&mut E> {
+        self.effects
+            .iter_mut()
+            .find_map(|e| (**e).as_any_mut().downcast_mut())
+    }
+
+    /// Remove every effect of type `E` from the chain. Returns `true` if any were removed.
+    pub fn remove<E: Effect>(&mut self) -> bool {
+        let len = self.effects.len();
+        self.effects.retain(|e| !(**e).as_any().is::<E>());
+        self.effects.len() != len
+    }
+
+    /// Remove every effect.
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    /// Returns the number of effects in the chain.
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Returns `true` if there are no effects in the chain.
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Apply every effect to `frame`, in order.
+    pub fn apply(&mut self, frame: &mut Image, elapsed_time: f64) {
+        for effect in &mut self.effects {
+            effect.apply(frame, elapsed_time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::Color, pixels::PixelFormatEnum, surface::Surface};
+
+    use super::*;
+    use crate::canvas::SurfaceCanvas;
+
+    /// Inverts every pixel.
+    struct Invert;
+
+    impl Effect for Invert {
+        fn apply(&mut self, frame: &mut Image, _elapsed_time: f64) {
+            for pixel in frame.pixels.chunks_exact_mut(4) {
+                pixel[..3].iter_mut().for_each(|c| *c = 255 - *c);
+            }
+        }
+    }
+
+    /// Test finding effects by type, and applying a custom effect to a canvas.
+    #[test]
+    fn test_effect_chain() {
+        let mut chain = EffectChain::new();
+        chain.push(Scanlines::default());
+        chain.push(Invert);
+        assert!(chain.get::<Invert>().is_some());
+        assert!(chain.get_mut::<Vignette>().is_none());
+        chain.get_mut::<Scanlines>().unwrap().darkness = 0.0;
+        assert!(chain.remove::<Scanlines>());
+        assert_eq!(chain.len(), 1);
+
+        let surface = Surface::new(2, 2, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_draw_color(Color::RED);
+        canvas.clear();
+        *canvas.effects_mut() = chain;
+        canvas.apply_effects(0.0).unwrap();
+        assert_eq!(canvas.get_pixel((1, 1)), Ok(Color::CYAN));
+    }
+}
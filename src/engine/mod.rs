@@ -4,12 +4,18 @@
 mod fps;
 use fps::FpsCounter;
 
-use std::error::Error;
+use std::{error::Error, ops::Range, path::PathBuf};
 
-use sdl2::event::Event;
+use sdl2::{
+    event::{Event, WindowEvent},
+    render::RenderTarget,
+};
 
 use crate::{
-    input::{InputState, KeyboardState, MouseState},
+    audio::Audio,
+    canvas::Canvas,
+    input::{InputState, KeyboardState, MouseState, Scancode},
+    record::Recorder,
     Application, WindowCanvas,
 };
 
@@ -21,6 +27,30 @@ pub struct Engine<'a> {
     height: u32,
     /// Whether the FPS should be calculated and displayed in the titlebar of the window.
     pub show_fps: bool,
+    /// A key that saves a screenshot of the current frame when pressed, or `None` to disable
+    /// screenshots. See [`Canvas::save_screenshot`][crate::canvas::Canvas::save_screenshot].
+    /// If a screenshot can't be saved, the error is printed to standard error and the game
+    /// carries on. The key only works with a window to type into, so headless runs, such as
+    /// tests, can't take screenshots this way; draw to a
+    /// [`SurfaceCanvas`][crate::canvas::SurfaceCanvas] and call
+    /// [`screenshot`][crate::canvas::Canvas::screenshot] on it instead.
+    pub screenshot_key: Option<Scancode>,
+    /// Directory that screenshots are saved in.
+    pub screenshot_dir: PathBuf,
+    /// Whether to run in retained mode, for tools and turn-based games that rarely change. The
+    /// screen keeps its contents between frames (see
+    /// [`Canvas::set_retained`][crate::canvas::Canvas::set_retained]), and the screen is only
+    /// presented when part of it has been marked dirty. When nothing is dirty, the engine
+    /// sleeps until an event arrives, rather than calling
+    /// [`on_update`][crate::Application::on_update] as fast as it can, so an idle application
+    /// uses almost no CPU. The elapsed time passed to `on_update` after sleeping includes the
+    /// time spent asleep.
+    pub retained: bool,
+    /// Whether to play the [`audio`][Self::audio] mixer through the default audio device while
+    /// the engine runs. If the device can't be opened, [`start`][Self::start] returns the error.
+    pub play_audio: bool,
+    recording: Option<(Recorder, Range<u64>)>,
+    audio: Audio,
     ctx: sdl2::Sdl,
 }
 
@@ -54,10 +84,33 @@ impl<'a> Engine<'a> {
             width,
             height,
             show_fps: true,
+            screenshot_key: None,
+            screenshot_dir: PathBuf::from("."),
+            retained: false,
+            play_audio: false,
+            recording: None,
+            audio: Audio::new(),
             ctx: sdl2::init()?,
         })
     }
 
+    /// Record some of the frames shown once the engine starts. `frames` is the range of frames
+    /// to record, where frame 0 is the first frame passed to
+    /// [`Application::on_update`][crate::Application::on_update].
+    ///
+    /// While recording, the elapsed time passed to `on_update` is always the recorder's
+    /// [`frame_time`][Recorder::frame_time], rather than how long the frame really took, so the
+    /// recording plays back smoothly even if drawing and saving each frame is slow.
+    pub fn record(&mut self, recorder: Recorder, frames: Range<u64>) {
+        self.recording = Some((recorder, frames));
+    }
+
+    /// Returns the mixer passed to the application's callbacks, to start music playing before
+    /// the engine starts. See the [`audio`][crate::audio] module for an example.
+    pub fn audio(&self) -> &Audio {
+        &self.audio
+    }
+
     /// Create and show the window and start the main event loop.
     /// # Parameters
     /// * `present_vsync`: Whether to limit the frame rate of the application to the frame rate of
@@ -87,6 +140,13 @@ impl<'a> Engine<'a> {
             canvas = canvas.present_vsync();
         }
         let mut canvas = WindowCanvas::new(canvas.build()?);
+        // Audio plays until this is dropped when the engine stops
+        let _audio_output = if self.play_audio {
+            Some(self.audio.open(&self.ctx.audio()?)?)
+        } else {
+            None
+        };
+        canvas.set_retained(self.retained)?;
         // Event handling
         let mut event_pump = self.ctx.event_pump()?;
         // Input state
@@ -97,14 +157,37 @@ impl<'a> Engine<'a> {
 
         // Call the app.on_create() function so the user can perform one-time initialisation of
         // their application.
-        if !self.app.on_create(&mut canvas, &input)? {
+        if !self.app.on_create(&mut canvas, &input, &self.audio)? {
             return self.app.on_quit();
         }
 
         // These variables are used to determine the elapsed time between frames, to allow for
         // time-regulated things like animation and to calculate average frame rates
+        let mut frame = 0;
         loop {
-            let elapsed_time = fps_counter.update(self.show_fps);
+            // Handle events, sleeping until one arrives if nothing needs redrawing
+            let first = if self.retained && !canvas.is_dirty() {
+                Some(event_pump.wait_event())
+            } else {
+                None
+            };
+            for event in first.into_iter().chain(event_pump.poll_iter()) {
+                if !handle_event(&event, &mut canvas)? {
+                    return self.app.on_quit();
+                }
+            }
+            // Refresh the input state, exactly once per frame so no presses are missed
+            input
+                .keyboard
+                .update(event_pump.keyboard_state().scancodes());
+            input.mouse.update(event_pump.mouse_state());
+
+            let mut elapsed_time = fps_counter.update(self.show_fps);
+            if let Some((recorder, frames)) = &self.recording {
+                if frames.contains(&frame) {
+                    elapsed_time = recorder.frame_time();
+                }
+            }
             if self.show_fps && fps_counter.time_acc() >= 1.0 {
                 let fps = fps_counter.fps();
                 let title = format!("{} ({} FPS)", self.title, fps.round() as u32);
@@ -114,27 +197,111 @@ impl<'a> Engine<'a> {
             }
 
             // Process next frame and exit if `Ok(false)` is returned
-            if !self.app.on_update(&mut canvas, &input, elapsed_time)? {
+            if !self
+                .app
+                .on_update(&mut canvas, &input, &self.audio, elapsed_time)?
+            {
                 return self.app.on_quit();
             }
 
-            // Handle events
-            for event in event_pump.poll_iter() {
-                match event {
-                    Event::Quit { .. } => {
-                        return self.app.on_quit();
-                    }
-                    _ => {}
-                }
+            let screenshot = screenshot_wanted(&mut canvas, &input, self.screenshot_key)?;
+            if self.retained && !canvas.is_dirty() {
+                frame += 1;
+                continue;
             }
-            // Refresh the input state
-            input
-                .keyboard
-                .update(event_pump.keyboard_state().scancodes());
-            input.mouse.update(event_pump.mouse_state());
 
             // Flip the double buffer
+            canvas.composite_layers()?;
+            self.app.on_draw_overlay(&mut canvas)?;
+            canvas.apply_effects(elapsed_time)?;
+            // A failed screenshot shouldn't end the game
+            if screenshot {
+                if let Err(e) = canvas.save_screenshot(&self.screenshot_dir) {
+                    eprintln!("Couldn't save screenshot: {}", e);
+                }
+            }
+            if let Some((recorder, frames)) = &mut self.recording {
+                if frames.contains(&frame) {
+                    recorder.capture(&canvas)?;
+                }
+                if frame + 1 >= frames.end {
+                    if let Some((recorder, _)) = self.recording.take() {
+                        recorder.finish()?;
+                    }
+                }
+            }
             canvas.present();
+            frame += 1;
         }
     }
 }
+
+/// Returns whether `key` was pressed this frame, so a screenshot should be saved. In retained
+/// mode, the whole screen is marked dirty, so the frame is drawn and captured even if nothing
+/// else changed.
+fn screenshot_wanted<T: RenderTarget, U>(
+    canvas: &mut Canvas<T, U>,
+    input: &InputState,
+    key: Option<Scancode>,
+) -> Result<bool, String> {
+    let wanted = key.is_some_and(|key| input.keyboard.pressed(key));
+    if wanted && canvas.is_retained() {
+        canvas.mark_all_dirty()?;
+    }
+    Ok(wanted)
+}
+
+/// Handle an event that the engine itself cares about. Returns `false` if the application
+/// should quit.
+fn handle_event(event: &Event, canvas: &mut WindowCanvas) -> Result<bool, String> {
+    match event {
+        Event::Quit { .. } => return Ok(false),
+        // The window's contents have been lost, so they need showing again
+        Event::Window {
+            win_event: WindowEvent::Exposed,
+            ..
+        } if canvas.is_retained() => canvas.mark_all_dirty()?,
+        _ => (),
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{mouse::MouseState as SdlMouseState, pixels::PixelFormatEnum, surface::Surface};
+
+    use super::*;
+    use crate::canvas::SurfaceCanvas;
+
+    /// Test the screenshot key forces a frame to be drawn in retained mode.
+    #[test]
+    fn test_screenshot_wanted() {
+        let surface = Surface::new(4, 4, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_retained(true).unwrap();
+        canvas.present();
+        let mut input = InputState {
+            keyboard: KeyboardState::new(std::iter::once((Scancode::F12, false))),
+            mouse: MouseState::new(SdlMouseState::from_sdl_state(0)),
+        };
+        let key = Some(Scancode::F12);
+        assert!(!screenshot_wanted(&mut canvas, &input, key).unwrap());
+        assert!(!canvas.is_dirty());
+
+        input
+            .keyboard
+            .update(key.into_iter().map(|key| (key, true)));
+        assert!(!screenshot_wanted(&mut canvas, &input, None).unwrap());
+        assert!(!canvas.is_dirty());
+        assert!(screenshot_wanted(&mut canvas, &input, key).unwrap());
+        assert!(canvas.is_dirty());
+
+        // Only the frame it was pressed on
+        canvas.present();
+        input
+            .keyboard
+            .update(key.into_iter().map(|key| (key, true)));
+        assert!(!screenshot_wanted(&mut canvas, &input, key).unwrap());
+        assert!(!canvas.is_dirty());
+    }
+}
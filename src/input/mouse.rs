@@ -3,6 +3,7 @@
 use sdl2::mouse::MouseState as SdlMouseState;
 
 use super::{ButtonState, MouseButton};
+use crate::camera::Camera2D;
 
 /// The cursor position and state of the mouse buttons.
 pub struct MouseState {
@@ -24,6 +25,11 @@ impl MouseState {
         }
     }
 
+    /// Returns the point in the world under the mouse cursor, as seen through `camera`.
+    pub fn world_position(&self, camera: &Camera2D) -> (f64, f64) {
+        camera.screen_to_world((self.x as f64, self.y as f64))
+    }
+
     /// Update the existing state from the current `sdl2::mouse::MouseState`. This is called
     /// internally by the engine on every frame.
     pub(crate) fn update(&mut self, state: SdlMouseState) {
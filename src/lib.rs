@@ -8,20 +8,23 @@
 //! * **Very simple to use:** Just implement the [`Application` trait][Application] on a type of your choice, then pass an instance of this type to [`Engine::new`].
 //! * **Powerful:** Anything you can do with sdl2 from Rust, you can do with this library, and we provide thin abstractions over some of the more convoluted sdl2 interfaces.
 //! * **Built-in text rendering:** No need to find a TTF font and distribute it with your application, just call the [`Canvas::draw_text`][canvas::Canvas::draw_text] method. ([see below](#caveats-with-text-rendering))
+//! * **Sound:** Play sound effects and streamed music on a software mixer with the [`audio`] module. WAV files are supported out of the box, and OGG Vorbis files with the "ogg" cargo feature.
+//! * **Scenes:** Split menus, levels and pause screens into separate [`Scene`s][scene::Scene] on a [`SceneStack`][scene::SceneStack], with fade and wipe transitions between them.
 //! ## Caveats With Text Rendering
 //! This crate uses the [GNU Unifont][unifont] for built-in text rendering. As such, if you wish to use this feature, you
 //! must distribute your project under the [GPL][gpl]. As this is not desirable for many projects, this feature is only
 //! enabled if this crate is built with the "unifont" cargo feature.
 //! ```toml
 //! [dependencies.simple-game-engine]
-//! version = "0.8.2"
+//! version = "0.9.0"
 //! features = ["unifont"]
 //! ```
-//! If you'd like to render text without using this font, consider checking out the [SDL2 TTF module][sdl2-ttf].
+//! If you'd like to render text without using this font, load a bitmap font of your choice from a BDF, PSF or font sheet
+//! image with `canvas::Font`, or enable the "ttf" cargo feature to load TrueType and OpenType fonts too, and select it
+//! with `Canvas::set_font`. All of the canvas's text methods then draw with that font.
 //!
 //! [unifont]: <http://unifoundry.com/unifont/index.html>
 //! [gpl]: <https://www.gnu.org/licenses/old-licenses/gpl-2.0-standalone.html>
-//! [sdl2-ttf]: <https://docs.rs/sdl2/latest/sdl2/ttf/index.html>
 //! # Example
 //! The simplest SGE program looks like this:
 //! ```no_run
@@ -35,6 +38,7 @@
 //!         &mut self,
 //!         canvas: &mut WindowCanvas,
 //!         input: &InputState,
+//!         audio: &Audio,
 //!     ) -> sge::ApplicationResult {
 //!         // Do one-time initialisation here
 //!         Ok(true) // `true` indicates to continue running the application
@@ -44,9 +48,10 @@
 //!         &mut self,
 //!         canvas: &mut WindowCanvas,
 //!         input: &InputState,
+//!         audio: &Audio,
 //!         elapsed_time: f64,
 //!     ) -> sge::ApplicationResult {
-//!         // Handle user input, update the canvas, and perform any other tasks to be ran on each frame
+//!         // Handle user input, update the canvas, play sounds, and perform any other tasks to be ran on each frame
 //!         Ok(true) // `true` indicates to continue running the application
 //!     }
 //! }
@@ -68,11 +73,23 @@
 
 #![warn(missing_docs)]
 
+pub mod assets;
+pub mod atlas;
+pub mod audio;
+pub mod camera;
+pub mod color;
+pub mod effects;
 mod engine;
 pub use engine::Engine;
 pub mod canvas;
 pub use canvas::WindowCanvas;
 pub mod input;
+pub mod record;
+pub mod scene;
+pub mod sprite;
+#[cfg(test)]
+mod test_util;
+pub mod tilemap;
 
 use std::error::Error;
 
@@ -91,7 +108,13 @@ pub trait Application<Canvas = WindowCanvas> {
     /// * `canvas`: A draw target representing the visible window.
     /// * `input`: a struct containing info about the state of input devices, such as the keyboard
     ///   and mouse.
-    fn on_create(&mut self, _canvas: &mut Canvas, _input: &input::InputState) -> ApplicationResult {
+    /// * `audio`: The mixer that sounds and music are played on. See the [`audio`] module.
+    fn on_create(
+        &mut self,
+        _canvas: &mut Canvas,
+        _input: &input::InputState,
+        _audio: &audio::Audio,
+    ) -> ApplicationResult {
         Ok(true)
     }
     /// Called once per frame.
@@ -99,16 +122,25 @@ pub trait Application<Canvas = WindowCanvas> {
     /// * `canvas`: A draw target representing the visible window.
     /// * `input`: a struct containing info about the state of input devices, such as the keyboard
     ///   and mouse.
+    /// * `audio`: The mixer that sounds and music are played on. See the [`audio`] module.
     /// * `elapsed_time`: Duration (in seconds) since the last frame. This can be used to keep
     ///   time-sensative routines, such as animation, running at a constant speed.
     fn on_update(
         &mut self,
         _canvas: &mut Canvas,
         _input: &input::InputState,
+        _audio: &audio::Audio,
         _elapsed_time: f64,
     ) -> ApplicationResult {
         Ok(true)
     }
+    /// Called once per frame, after the canvas's [layers][canvas::Canvas::add_layer] have been
+    /// composited, to draw over everything else, such as transitions between
+    /// [scenes][scene]. Anything drawn here only lasts for one frame in retained mode. Does
+    /// nothing by default.
+    fn on_draw_overlay(&mut self, _canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
     /// Called when the window's close button is clicked.
     /// Be aware that this isn't called on `std::process::exit`, so do any essential
     /// cleanup in a `Drop` implementation instead.
@@ -121,6 +153,8 @@ pub trait Application<Canvas = WindowCanvas> {
 pub mod prelude {
     //! Commonly used types.
     pub use crate::{
+        audio::Audio,
+        color::ColorExt,
         input::{InputState, MouseButton, Scancode},
         Color, Point, Rect, WindowCanvas,
     };
@@ -0,0 +1,269 @@
+//! Recording a sequence of frames to numbered image files, or to an animated GIF.
+//!
+//! Pass a [`Recorder`] to [`Engine::record`][crate::Engine::record] to record frames as they're
+//! shown, or call [`Recorder::capture`] yourself at the end of each frame when drawing without
+//! the engine, such as to a [`SurfaceCanvas`][crate::canvas::SurfaceCanvas].
+//! # Example
+//! ```no_run
+//! # struct App;
+//! # impl simple_game_engine::Application for App {}
+//! use simple_game_engine::{record::Recorder, Engine};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut app = App {};
+//! let mut engine = Engine::new(&mut app, "Window Title", 640, 480)?;
+//! // Record the first 5 seconds at a smooth 30 frames per second, however long each frame
+//! // really takes to draw
+//! engine.record(Recorder::images("recording", 1.0 / 30.0)?, 0..150);
+//! engine.start(true)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fs, path::PathBuf};
+
+use sdl2::render::RenderTarget;
+
+use crate::canvas::Canvas;
+
+/// Where a [`Recorder`] writes its frames.
+enum Output {
+    /// Numbered image files in a directory.
+    Images(PathBuf),
+    /// An animated GIF, which is created when the first frame is captured, as that's when its
+    /// size is known.
+    #[cfg(feature = "gif")]
+    Gif {
+        path: PathBuf,
+        encoder: Option<gif::Encoder<std::io::BufWriter<fs::File>>>,
+    },
+}
+
+/// Captures frames from a [`Canvas`] and saves them, along with the fixed time each frame should
+/// be shown for.
+pub struct Recorder {
+    output: Output,
+    frame_time: f64,
+    frames: u32,
+}
+
+impl Recorder {
+    /// Record frames as numbered image files in `dir`, named `frame-00000.png`,
+    /// `frame-00001.png` and so on. The files are PNGs if the "png" feature is enabled, or BMPs
+    /// otherwise. `frame_time` is the time between frames, in seconds. Returns a `String`
+    /// describing the error if the directory couldn't be created.
+    pub fn images<P: Into<PathBuf>>(dir: P, frame_time: f64) -> Result<Self, String> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        Ok(Self {
+            output: Output::Images(dir),
+            frame_time,
+            frames: 0,
+        })
+    }
+
+    /// Record frames to an animated GIF at `path`, which loops forever. `frame_time` is the
+    /// time between frames, in seconds, though GIFs can only store it to the nearest hundredth
+    /// of a second.
+    ///
+    /// Every frame of a GIF is limited to 256 colours. Frames with more colours than that are
+    /// reduced to a fixed palette, which suits pixel art and flat colours far better than
+    /// photos or smooth gradients.
+    #[cfg(feature = "gif")]
+    pub fn gif<P: Into<PathBuf>>(path: P, frame_time: f64) -> Self {
+        Self {
+            output: Output::Gif {
+                path: path.into(),
+                encoder: None,
+            },
+            frame_time,
+            frames: 0,
+        }
+    }
+
+    /// Returns the time between frames, in seconds.
+    pub fn frame_time(&self) -> f64 {
+        self.frame_time
+    }
+
+    /// Returns the number of frames captured so far.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Capture everything drawn to `canvas`'s current render target, and save it as the next
+    /// frame. Returns a `String` describing the error if the frame couldn't be read or saved.
+    pub fn capture<T: RenderTarget, U>(&mut self, canvas: &Canvas<T, U>) -> Result<(), String> {
+        let image = canvas.screenshot()?;
+        match &mut self.output {
+            Output::Images(dir) => {
+                let path = dir.join(format!("frame-{:05}", self.frames));
+                save_image(&image, path)?;
+            }
+            #[cfg(feature = "gif")]
+            Output::Gif { path, encoder } => {
+                let encoder = match encoder {
+                    Some(encoder) => encoder,
+                    empty => empty.insert(gif_encoder(path, image.width, image.height)?),
+                };
+                let (palette, indices) = quantize(&image.pixels);
+                let frame = gif::Frame {
+                    width: image.width as u16,
+                    height: image.height as u16,
+                    delay: (self.frame_time * 100.0).round() as u16,
+                    palette: Some(palette),
+                    buffer: indices.into(),
+                    ..gif::Frame::default()
+                };
+                encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Finish the recording, making sure everything has been written. Dropping the recorder
+    /// does the same, but ignores any errors.
+    pub fn finish(self) -> Result<(), String> {
+        match self.output {
+            Output::Images(_) => Ok(()),
+            #[cfg(feature = "gif")]
+            Output::Gif { encoder, .. } => match encoder {
+                Some(encoder) => {
+                    use std::io::Write;
+                    let mut writer = encoder.into_inner().map_err(|e| e.to_string())?;
+                    writer.flush().map_err(|e| e.to_string())
+                }
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+/// Save a frame as a PNG if possible, or a BMP otherwise, adding the right extension to `path`.
+fn save_image(image: &crate::canvas::Image, mut path: PathBuf) -> Result<(), String> {
+    #[cfg(feature = "png")]
+    {
+        path.set_extension("png");
+        image.save_png(&path)
+    }
+    #[cfg(not(feature = "png"))]
+    {
+        path.set_extension("bmp");
+        image.save_bmp(&path)
+    }
+}
+
+/// Create a GIF file that loops forever.
+#[cfg(feature = "gif")]
+fn gif_encoder(
+    path: &std::path::Path,
+    width: u32,
+    height: u32,
+) -> Result<gif::Encoder<std::io::BufWriter<fs::File>>, String> {
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(format!("{}x{} is too big for a GIF", width, height));
+    }
+    let file = fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = gif::Encoder::new(
+        std::io::BufWriter::new(file),
+        width as u16,
+        height as u16,
+        &[],
+    )
+    .map_err(|e| e.to_string())?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|e| e.to_string())?;
+    Ok(encoder)
+}
+
+/// Reduce RGBA pixels to at most 256 colours, returning the palette as RGB triples and the
+/// palette index of each pixel. If there are too many colours to keep them all, every pixel is
+/// rounded to the nearest colour in a 6x6x6 colour cube.
+#[cfg(feature = "gif")]
+fn quantize(pixels: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut palette = Vec::new();
+    let mut lookup = fnv::FnvHashMap::default();
+    let mut indices = Vec::with_capacity(pixels.len() / 4);
+    for pixel in pixels.chunks_exact(4) {
+        let rgb = [pixel[0], pixel[1], pixel[2]];
+        let next = lookup.len();
+        if next == 256 && !lookup.contains_key(&rgb) {
+            return quantize_cube(pixels);
+        }
+        let index = *lookup.entry(rgb).or_insert_with(|| {
+            palette.extend_from_slice(&rgb);
+            next as u8
+        });
+        indices.push(index);
+    }
+    (palette, indices)
+}
+
+/// Reduce RGBA pixels to the 216 colours of a 6x6x6 colour cube.
+#[cfg(feature = "gif")]
+fn quantize_cube(pixels: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let level = |c: u8| (c as u16 * 5 + 127) / 255;
+    let palette = (0..216u16)
+        .flat_map(|i| [i / 36, i / 6 % 6, i % 6])
+        .map(|l| (l * 51) as u8)
+        .collect();
+    let indices = pixels
+        .chunks_exact(4)
+        .map(|p| (level(p[0]) * 36 + level(p[1]) * 6 + level(p[2])) as u8)
+        .collect();
+    (palette, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::Color, pixels::PixelFormatEnum, rect::Rect, surface::Surface};
+
+    use super::*;
+    use crate::{canvas::SurfaceCanvas, test_util::test_dir};
+
+    /// Test reducing images to 256 colours.
+    #[cfg(feature = "gif")]
+    #[test]
+    fn test_quantize() {
+        let pixels = [255, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0, 255];
+        assert_eq!(
+            quantize(&pixels),
+            (vec![255, 0, 0, 0, 0, 255], vec![0, 1, 0])
+        );
+        // A gradient with too many colours falls back to the colour cube
+        let pixels: Vec<u8> = (0..300u32)
+            .flat_map(|i| [(i % 256) as u8, (i / 256) as u8, 0, 255])
+            .collect();
+        let (palette, indices) = quantize(&pixels);
+        assert_eq!(palette.len(), 216 * 3);
+        assert_eq!(indices[255], 5 * 36);
+    }
+
+    /// Test recording frames as numbered images.
+    #[test]
+    fn test_record_images() {
+        let dir = test_dir("record-images");
+        let mut recorder = Recorder::images(&dir, 0.5).unwrap();
+        let surface = Surface::new(4, 4, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        for x in 0..3 {
+            canvas.set_draw_color(Color::RED);
+            canvas.fill_rect(Rect::new(x, 0, 1, 1)).unwrap();
+            recorder.capture(&canvas).unwrap();
+        }
+        assert_eq!(recorder.frames(), 3);
+        recorder.finish().unwrap();
+        let mut files: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        let extension = if cfg!(feature = "png") { "png" } else { "bmp" };
+        let expected: Vec<_> = (0..3)
+            .map(|i| format!("frame-{:05}.{}", i, extension))
+            .collect();
+        assert_eq!(files, expected);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
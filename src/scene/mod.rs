@@ -0,0 +1,491 @@
+//! Splitting an application into scenes, such as menus, levels and pause screens, which are
+//! kept on a stack.
+//!
+//! Each [`Scene`] only handles its own part of the game. The scene on top of a [`SceneStack`]
+//! is updated every frame, and can ask for another scene to be pushed on top of it, for itself
+//! to be popped, revealing the scene beneath, or to be replaced. Scenes can change straight
+//! away, or with a [`Transition`] drawn between them. Pass the stack to [`Engine::new`] in
+//! place of an application.
+//! # Example
+//! ```no_run
+//! use simple_game_engine::{
+//!     self as sge,
+//!     prelude::*,
+//!     scene::{Request, Scene, SceneResult, SceneStack, Transition},
+//! };
+//! use std::error::Error;
+//!
+//! struct Menu;
+//!
+//! impl Scene for Menu {
+//!     fn on_update(
+//!         &mut self,
+//!         _canvas: &mut WindowCanvas,
+//!         input: &InputState,
+//!         _audio: &Audio,
+//!         _elapsed_time: f64,
+//!     ) -> SceneResult {
+//!         if input.keyboard.pressed(Scancode::Return) {
+//!             return Ok(Request::Replace(Box::new(Level), Transition::fade(0.5)));
+//!         }
+//!         Ok(Request::Continue)
+//!     }
+//!
+//!     fn on_draw(&mut self, canvas: &mut WindowCanvas) -> Result<(), Box<dyn Error>> {
+//!         canvas.set_draw_color(Color::BLUE);
+//!         canvas.clear();
+//!         Ok(())
+//!     }
+//! }
+//!
+//! struct Level;
+//!
+//! impl Scene for Level {
+//!     fn on_update(
+//!         &mut self,
+//!         _canvas: &mut WindowCanvas,
+//!         input: &InputState,
+//!         _audio: &Audio,
+//!         _elapsed_time: f64,
+//!     ) -> SceneResult {
+//!         if input.keyboard.pressed(Scancode::Escape) {
+//!             return Ok(Request::Push(Box::new(Paused), Transition::None));
+//!         }
+//!         Ok(Request::Continue)
+//!     }
+//! }
+//!
+//! /// Drawn over the paused level.
+//! struct Paused;
+//!
+//! impl Scene for Paused {
+//!     fn on_update(
+//!         &mut self,
+//!         _canvas: &mut WindowCanvas,
+//!         input: &InputState,
+//!         _audio: &Audio,
+//!         _elapsed_time: f64,
+//!     ) -> SceneResult {
+//!         if input.keyboard.pressed(Scancode::Escape) {
+//!             return Ok(Request::Pop(Transition::None));
+//!         }
+//!         Ok(Request::Continue)
+//!     }
+//!
+//!     fn is_opaque(&self) -> bool {
+//!         false
+//!     }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!     let mut scenes = SceneStack::new(Menu);
+//!     let mut engine = sge::Engine::new(&mut scenes, "Scenes", 640, 480)?;
+//!     engine.start(true)
+//! }
+//! ```
+//!
+//! [`Engine::new`]: crate::Engine::new
+
+mod transition;
+pub use transition::{Transition, WipeDirection};
+
+use std::error::Error;
+
+use sdl2::render::RenderTarget;
+
+use crate::{
+    audio::Audio, canvas::Canvas, input::InputState, Application, ApplicationResult, WindowCanvas,
+};
+
+/// The return type of [`Scene::on_update`].
+pub type SceneResult<Canvas = WindowCanvas> = Result<Request<Canvas>, Box<dyn Error>>;
+
+/// What a scene asks its [`SceneStack`] to do after it's updated.
+pub enum Request<Canvas = WindowCanvas> {
+    /// Carry on with this scene.
+    Continue,
+    /// Pause this scene, and put another on top of it.
+    Push(Box<dyn Scene<Canvas>>, Transition),
+    /// Remove this scene, and resume the one beneath it. Popping the last scene quits.
+    Pop(Transition),
+    /// Remove this scene, and put another in its place.
+    Replace(Box<dyn Scene<Canvas>>, Transition),
+    /// Quit the application.
+    Quit,
+}
+
+/// One part of an application, such as a menu, a level or a pause screen, kept on a
+/// [`SceneStack`]. Every method does nothing by default.
+pub trait Scene<Canvas = WindowCanvas> {
+    /// Called once, when the scene is put on the stack.
+    fn on_create(
+        &mut self,
+        _canvas: &mut Canvas,
+        _input: &InputState,
+        _audio: &Audio,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called once per frame while this is the top scene, and no transition is running.
+    /// Returns what the stack should do next. Drawing should be done in
+    /// [`on_draw`][Self::on_draw] instead, so the scene can still be seen beneath other scenes
+    /// and during transitions.
+    /// # Parameters
+    /// * `canvas`: A draw target representing the visible window.
+    /// * `input`: a struct containing info about the state of input devices, such as the keyboard
+    ///   and mouse.
+    /// * `audio`: The mixer that sounds and music are played on.
+    /// * `elapsed_time`: Duration (in seconds) since the last frame.
+    fn on_update(
+        &mut self,
+        _canvas: &mut Canvas,
+        _input: &InputState,
+        _audio: &Audio,
+        _elapsed_time: f64,
+    ) -> SceneResult<Canvas> {
+        Ok(Request::Continue)
+    }
+
+    /// Called once per frame to draw the scene, whenever it can be seen.
+    fn on_draw(&mut self, _canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Called when another scene is pushed on top of this one.
+    fn on_pause(&mut self) {}
+
+    /// Called when this is the top scene again, after the scene above it was popped.
+    fn on_resume(&mut self) {}
+
+    /// Returns whether the scene covers the whole screen. If not, the scene beneath it is drawn
+    /// first, so it shows through, which suits pause screens and dialogs. Scenes are opaque by
+    /// default.
+    fn is_opaque(&self) -> bool {
+        true
+    }
+}
+
+/// A change to the stack of scenes.
+enum Change<Canvas> {
+    Push(Box<dyn Scene<Canvas>>),
+    Pop,
+    Replace(Box<dyn Scene<Canvas>>),
+}
+
+/// A transition that's running, and the change it makes once the screen is covered.
+struct Running<Canvas> {
+    transition: Transition,
+    time: f64,
+    change: Option<Change<Canvas>>,
+}
+
+/// A stack of [`Scene`]s, which runs as an [`Application`]. Only the top scene is updated, and
+/// scenes are drawn from the top-most [opaque][Scene::is_opaque] one upwards. See the
+/// [module documentation][self] for an example.
+pub struct SceneStack<Canvas = WindowCanvas> {
+    scenes: Vec<Box<dyn Scene<Canvas>>>,
+    running: Option<Running<Canvas>>,
+}
+
+impl<Canvas> SceneStack<Canvas> {
+    /// Create a stack holding `scene`, which is created when the application starts.
+    pub fn new<S: Scene<Canvas> + 'static>(scene: S) -> Self {
+        Self {
+            scenes: vec![Box::new(scene)],
+            running: None,
+        }
+    }
+
+    /// Returns the number of scenes on the stack.
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    /// Returns `true` if there are no scenes left on the stack.
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    /// Returns whether a transition is running.
+    pub fn is_transitioning(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Change the stack, creating, pausing and resuming scenes as needed.
+    fn apply(
+        &mut self,
+        change: Change<Canvas>,
+        canvas: &mut Canvas,
+        input: &InputState,
+        audio: &Audio,
+    ) -> Result<(), Box<dyn Error>> {
+        match change {
+            Change::Push(mut scene) => {
+                if let Some(top) = self.scenes.last_mut() {
+                    top.on_pause();
+                }
+                scene.on_create(canvas, input, audio)?;
+                self.scenes.push(scene);
+            }
+            Change::Pop => {
+                self.scenes.pop();
+                if let Some(top) = self.scenes.last_mut() {
+                    top.on_resume();
+                }
+            }
+            Change::Replace(mut scene) => {
+                self.scenes.pop();
+                scene.on_create(canvas, input, audio)?;
+                self.scenes.push(scene);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: RenderTarget, U> Application<Canvas<T, U>> for SceneStack<Canvas<T, U>> {
+    fn on_create(
+        &mut self,
+        canvas: &mut Canvas<T, U>,
+        input: &InputState,
+        audio: &Audio,
+    ) -> ApplicationResult {
+        for scene in &mut self.scenes {
+            scene.on_create(canvas, input, audio)?;
+        }
+        Ok(!self.scenes.is_empty())
+    }
+
+    fn on_update(
+        &mut self,
+        canvas: &mut Canvas<T, U>,
+        input: &InputState,
+        audio: &Audio,
+        elapsed_time: f64,
+    ) -> ApplicationResult {
+        if let Some(running) = &mut self.running {
+            running.time += elapsed_time;
+            let duration = running.transition.duration();
+            // Change scenes once the screen is covered, half way through
+            let change = if running.time >= duration / 2.0 {
+                running.change.take()
+            } else {
+                None
+            };
+            if running.time >= duration {
+                self.running = None;
+            }
+            if let Some(change) = change {
+                self.apply(change, canvas, input, audio)?;
+            }
+        } else if let Some(scene) = self.scenes.last_mut() {
+            let (change, transition) = match scene.on_update(canvas, input, audio, elapsed_time)? {
+                Request::Continue => (None, Transition::None),
+                Request::Push(scene, transition) => (Some(Change::Push(scene)), transition),
+                Request::Pop(transition) => (Some(Change::Pop), transition),
+                Request::Replace(scene, transition) => (Some(Change::Replace(scene)), transition),
+                Request::Quit => return Ok(false),
+            };
+            match change {
+                Some(change) if transition.duration() > 0.0 => {
+                    self.running = Some(Running {
+                        transition,
+                        time: 0.0,
+                        change: Some(change),
+                    });
+                }
+                Some(change) => self.apply(change, canvas, input, audio)?,
+                None => (),
+            }
+        }
+        if self.scenes.is_empty() {
+            return Ok(false);
+        }
+
+        let first = self
+            .scenes
+            .iter()
+            .rposition(|scene| scene.is_opaque())
+            .unwrap_or(0);
+        for scene in &mut self.scenes[first..] {
+            scene.on_draw(canvas)?;
+        }
+        if self.running.is_some() && canvas.is_retained() {
+            canvas.mark_all_dirty()?;
+        }
+        Ok(true)
+    }
+
+    fn on_draw_overlay(&mut self, canvas: &mut Canvas<T, U>) -> Result<(), Box<dyn Error>> {
+        if let Some(running) = &self.running {
+            running.transition.draw(canvas, running.time)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+    use sdl2::{mouse::MouseState as SdlMouseState, pixels::PixelFormatEnum, surface::Surface};
+
+    use super::*;
+    use crate::{
+        canvas::SurfaceCanvas,
+        input::{KeyboardState, MouseState},
+        Color,
+    };
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// Records what happens to it in a log, and makes the requests it's given in order.
+    struct TestScene {
+        name: &'static str,
+        log: Log,
+        requests: VecDeque<Request<SurfaceCanvas<'static>>>,
+        opaque: bool,
+    }
+
+    impl TestScene {
+        fn new(name: &'static str, log: &Log) -> Self {
+            Self {
+                name,
+                log: Rc::clone(log),
+                requests: VecDeque::new(),
+                opaque: true,
+            }
+        }
+
+        fn then(mut self, request: Request<SurfaceCanvas<'static>>) -> Self {
+            self.requests.push_back(request);
+            self
+        }
+
+        fn record(&self, event: &str) {
+            self.log
+                .borrow_mut()
+                .push(format!("{} {}", self.name, event));
+        }
+    }
+
+    impl Scene<SurfaceCanvas<'static>> for TestScene {
+        fn on_create(
+            &mut self,
+            _canvas: &mut SurfaceCanvas<'static>,
+            _input: &InputState,
+            _audio: &Audio,
+        ) -> Result<(), Box<dyn Error>> {
+            self.record("create");
+            Ok(())
+        }
+
+        fn on_update(
+            &mut self,
+            _canvas: &mut SurfaceCanvas<'static>,
+            _input: &InputState,
+            _audio: &Audio,
+            _elapsed_time: f64,
+        ) -> SceneResult<SurfaceCanvas<'static>> {
+            self.record("update");
+            Ok(self.requests.pop_front().unwrap_or(Request::Continue))
+        }
+
+        fn on_draw(&mut self, _canvas: &mut SurfaceCanvas<'static>) -> Result<(), Box<dyn Error>> {
+            self.record("draw");
+            Ok(())
+        }
+
+        fn on_pause(&mut self) {
+            self.record("pause");
+        }
+
+        fn on_resume(&mut self) {
+            self.record("resume");
+        }
+
+        fn is_opaque(&self) -> bool {
+            self.opaque
+        }
+    }
+
+    /// Returns everything logged since the last call.
+    fn take(log: &Log) -> Vec<String> {
+        log.borrow_mut().drain(..).collect()
+    }
+
+    /// Test pushing, popping and replacing scenes, with and without transitions.
+    #[test]
+    fn test_scene_stack() {
+        let surface = Surface::new(4, 4, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        let input = InputState {
+            keyboard: KeyboardState::new(std::iter::empty()),
+            mouse: MouseState::new(SdlMouseState::from_sdl_state(0)),
+        };
+        let audio = Audio::new();
+        let log = Log::default();
+        let mut paused = TestScene::new("paused", &log).then(Request::Continue);
+        paused.opaque = false;
+        paused = paused.then(Request::Pop(Transition::fade(1.0)));
+        let game =
+            TestScene::new("game", &log).then(Request::Push(Box::new(paused), Transition::None));
+        let mut scenes = SceneStack::new(game);
+
+        assert!(scenes.on_create(&mut canvas, &input, &audio).unwrap());
+        assert_eq!(take(&log), ["game create"]);
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.1).unwrap());
+        assert_eq!(
+            take(&log),
+            [
+                "game update",
+                "game pause",
+                "paused create",
+                "game draw",
+                "paused draw"
+            ]
+        );
+        assert_eq!(scenes.len(), 2);
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.1).unwrap());
+        assert_eq!(take(&log), ["paused update", "game draw", "paused draw"]);
+
+        // The scene is only popped once the screen has faded out, and nothing is updated
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.1).unwrap());
+        assert!(scenes.is_transitioning());
+        assert_eq!(scenes.len(), 2);
+        take(&log);
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.3).unwrap());
+        assert_eq!(take(&log), ["game draw", "paused draw"]);
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.2).unwrap());
+        assert_eq!(take(&log), ["game resume", "game draw"]);
+        assert_eq!(scenes.len(), 1);
+        canvas.set_draw_color(Color::WHITE);
+        canvas.clear();
+        scenes.on_draw_overlay(&mut canvas).unwrap();
+        assert_eq!(canvas.get_pixel((0, 0)), Ok(Color::BLACK));
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.5).unwrap());
+        assert!(!scenes.is_transitioning());
+        take(&log);
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.1).unwrap());
+        assert_eq!(take(&log), ["game update", "game draw"]);
+
+        let title = TestScene::new("title", &log).then(Request::Quit);
+        let menu =
+            TestScene::new("menu", &log).then(Request::Replace(Box::new(title), Transition::None));
+        let mut scenes = SceneStack::new(menu);
+        assert!(scenes.on_create(&mut canvas, &input, &audio).unwrap());
+        assert!(scenes.on_update(&mut canvas, &input, &audio, 0.1).unwrap());
+        assert_eq!(
+            take(&log),
+            ["menu create", "menu update", "title create", "title draw"]
+        );
+        assert!(!scenes.on_update(&mut canvas, &input, &audio, 0.1).unwrap());
+
+        let last = TestScene::new("last", &log).then(Request::Pop(Transition::None));
+        let mut scenes = SceneStack::new(last);
+        assert!(scenes.on_create(&mut canvas, &input, &audio).unwrap());
+        assert!(!scenes.on_update(&mut canvas, &input, &audio, 0.1).unwrap());
+        assert!(scenes.is_empty());
+    }
+}
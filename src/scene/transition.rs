@@ -0,0 +1,196 @@
+//! Transitions drawn over the screen while one scene changes to another.
+
+use sdl2::{
+    pixels::Color,
+    rect::Rect,
+    render::{BlendMode, Canvas as SdlCanvas, RenderTarget},
+};
+
+use crate::canvas::Canvas;
+
+/// The way a wipe moves across the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WipeDirection {
+    /// From the right edge to the left.
+    Left,
+    /// From the left edge to the right.
+    Right,
+    /// From the bottom edge to the top.
+    Up,
+    /// From the top edge to the bottom.
+    Down,
+}
+
+/// How the screen changes from one scene to another. Every transition covers the old scene
+/// for the first half of its duration, changes scenes once the screen is covered, then
+/// uncovers the new scene for the second half.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Transition {
+    /// Change scenes straight away.
+    #[default]
+    None,
+    /// Fade out to a colour, then fade in from it.
+    Fade {
+        /// How long the whole transition takes, in seconds.
+        duration: f64,
+        /// The colour faded through.
+        color: Color,
+    },
+    /// Sweep a colour across the screen, then sweep it off again in the same direction.
+    Wipe {
+        /// How long the whole transition takes, in seconds.
+        duration: f64,
+        /// The colour swept across the screen.
+        color: Color,
+        /// The way the colour moves.
+        direction: WipeDirection,
+    },
+}
+
+impl Transition {
+    /// Create a transition that fades through black, taking `duration` seconds.
+    pub fn fade(duration: f64) -> Self {
+        Self::Fade {
+            duration,
+            color: Color::BLACK,
+        }
+    }
+
+    /// Create a transition that sweeps black across the screen in `direction`, taking
+    /// `duration` seconds.
+    pub fn wipe(duration: f64, direction: WipeDirection) -> Self {
+        Self::Wipe {
+            duration,
+            color: Color::BLACK,
+            direction,
+        }
+    }
+
+    /// Returns how long the whole transition takes, in seconds.
+    pub fn duration(&self) -> f64 {
+        match *self {
+            Self::None => 0.0,
+            Self::Fade { duration, .. } | Self::Wipe { duration, .. } => duration.max(0.0),
+        }
+    }
+
+    /// Returns how much of the screen is covered `time` seconds into the transition, from `0.0`
+    /// to `1.0`.
+    fn coverage(&self, time: f64) -> f64 {
+        let half = self.duration() / 2.0;
+        if half <= 0.0 {
+            return 0.0;
+        }
+        let coverage = if time < half {
+            time / half
+        } else {
+            1.0 - (time - half) / half
+        };
+        coverage.clamp(0.0, 1.0)
+    }
+
+    /// Draw the transition as it is `time` seconds in, over the whole screen. Returns a `String`
+    /// describing the error if sdl fails.
+    pub(super) fn draw<T: RenderTarget, U>(
+        &self,
+        canvas: &mut Canvas<T, U>,
+        time: f64,
+    ) -> Result<(), String> {
+        let coverage = self.coverage(time);
+        if coverage <= 0.0 {
+            return Ok(());
+        }
+        let (width, height) = canvas.output_size()?;
+        let (color, rect) = match *self {
+            Self::None => return Ok(()),
+            Self::Fade { color, .. } => {
+                let alpha = (color.a as f64 * coverage).round() as u8;
+                (Color::RGBA(color.r, color.g, color.b, alpha), None)
+            }
+            Self::Wipe {
+                color, direction, ..
+            } => {
+                let horizontal = matches!(direction, WipeDirection::Left | WipeDirection::Right);
+                let length = if horizontal { width } else { height };
+                let covered = (length as f64 * coverage).round() as u32;
+                if covered == 0 {
+                    return Ok(());
+                }
+                // Measured from the edge the wipe starts at. The colour arrives from the start,
+                // and leaves towards the end
+                let start = if time < self.duration() / 2.0 {
+                    0
+                } else {
+                    length - covered
+                };
+                let start = match direction {
+                    WipeDirection::Right | WipeDirection::Down => start,
+                    WipeDirection::Left | WipeDirection::Up => length - start - covered,
+                } as i32;
+                let rect = if horizontal {
+                    Rect::new(start, 0, covered, height)
+                } else {
+                    Rect::new(0, start, width, covered)
+                };
+                (color, Some(rect))
+            }
+        };
+        // Drawn straight to the screen, ignoring the canvas's transform, clipping rectangle and
+        // draw style, which are put back afterwards
+        let inner: &mut SdlCanvas<T> = canvas;
+        let (old_color, old_blend, old_clip) =
+            (inner.draw_color(), inner.blend_mode(), inner.clip_rect());
+        inner.set_clip_rect(None);
+        inner.set_blend_mode(BlendMode::Blend);
+        inner.set_draw_color(color);
+        let result = inner.fill_rect(rect);
+        inner.set_draw_color(old_color);
+        inner.set_blend_mode(old_blend);
+        inner.set_clip_rect(old_clip);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{pixels::PixelFormatEnum, surface::Surface};
+
+    use super::*;
+    use crate::canvas::SurfaceCanvas;
+
+    /// Returns the colours of the top row of pixels after drawing `transition` at `time` over
+    /// a white canvas.
+    fn draw(transition: Transition, time: f64) -> Vec<Color> {
+        let surface = Surface::new(4, 2, PixelFormatEnum::RGBA8888).unwrap();
+        let mut canvas = SurfaceCanvas::new(surface.into_canvas().unwrap());
+        canvas.set_draw_color(Color::WHITE);
+        canvas.clear();
+        transition.draw(&mut canvas, time).unwrap();
+        assert_eq!(canvas.draw_color(), Color::WHITE);
+        (0..4).map(|x| canvas.get_pixel((x, 0)).unwrap()).collect()
+    }
+
+    /// Test how much of the screen fades and wipes cover over time.
+    #[test]
+    fn test_transitions() {
+        let (white, black) = (Color::WHITE, Color::BLACK);
+        let fade = Transition::fade(2.0);
+        assert_eq!(fade.duration(), 2.0);
+        assert_eq!(draw(fade, 0.0), [white; 4]);
+        assert_eq!(draw(fade, 1.0), [black; 4]);
+        assert_eq!(draw(fade, 1.5)[0], Color::RGB(127, 127, 127));
+        assert_eq!(draw(fade, 2.0), [white; 4]);
+        assert_eq!(draw(Transition::None, 0.0), [white; 4]);
+
+        let right = Transition::wipe(1.0, WipeDirection::Right);
+        assert_eq!(draw(right, 0.25), [black, black, white, white]);
+        assert_eq!(draw(right, 0.5), [black; 4]);
+        assert_eq!(draw(right, 0.75), [white, white, black, black]);
+        let left = Transition::wipe(1.0, WipeDirection::Left);
+        assert_eq!(draw(left, 0.125), [white, white, white, black]);
+        assert_eq!(draw(left, 0.875), [black, white, white, white]);
+        let down = Transition::wipe(1.0, WipeDirection::Down);
+        assert_eq!(draw(down, 0.25), [black; 4]);
+        assert_eq!(draw(down, 0.75), [white; 4]);
+    }
+}
@@ -0,0 +1,293 @@
+//! Contains the [`Animation`] type, which advances through the frames of a [`SpriteSheet`] over
+//! time.
+
+use super::{SpriteSheet, TagDirection};
+
+/// How an [`Animation`] behaves once it reaches its last frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayMode {
+    /// Jump back to the first frame and keep playing.
+    Loop,
+    /// Reverse direction at either end and keep playing.
+    PingPong,
+    /// Stop on the last frame and report that the animation has finished.
+    Once,
+}
+
+/// What happened during a call to [`Animation::update`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AnimationEvents {
+    /// Did the animation move on to another frame during this update? This is set even if it
+    /// ended up back on the frame it started on, such as after a whole loop.
+    pub frame_changed: bool,
+    /// Did the animation finish during this update? This is only ever set once, and only for
+    /// [`PlayMode::Once`] animations.
+    pub finished: bool,
+}
+
+/// Plays a sequence of frames, each shown for a set duration.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    /// Sprite sheet frame indices, in play order.
+    frames: Vec<usize>,
+    /// Duration (in seconds) of each entry in `frames`.
+    durations: Vec<f64>,
+    mode: PlayMode,
+    /// Position within `frames`.
+    current: usize,
+    /// Only used in ping-pong mode.
+    forwards: bool,
+    /// Time (in seconds) the current frame has been shown for.
+    timer: f64,
+    finished: bool,
+}
+
+impl Animation {
+    /// Create an animation that shows each of `frames` (indices into a [`SpriteSheet`]) for
+    /// `frame_duration` seconds.
+    /// # Panics
+    /// If `frames` is empty or `frame_duration` is not positive.
+    pub fn new<I>(frames: I, frame_duration: f64, mode: PlayMode) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        Self::with_durations(frames.into_iter().map(|f| (f, frame_duration)), mode)
+    }
+
+    /// Create an animation from `(frame, duration)` pairs, so each frame can be shown for a
+    /// different length of time.
+    /// # Panics
+    /// If `frames` is empty or any duration is not positive.
+    pub fn with_durations<I>(frames: I, mode: PlayMode) -> Self
+    where
+        I: IntoIterator<Item = (usize, f64)>,
+    {
+        let (frames, durations): (Vec<_>, Vec<_>) = frames.into_iter().unzip();
+        assert!(!frames.is_empty(), "an animation needs at least one frame");
+        assert!(
+            durations.iter().all(|&d| d > 0.0),
+            "frame durations must be positive"
+        );
+        Self {
+            frames,
+            durations,
+            mode,
+            current: 0,
+            forwards: true,
+            timer: 0.0,
+            finished: false,
+        }
+    }
+
+    /// Create an animation from a named [`FrameTag`][super::FrameTag] in `sheet`. Frames without
+    /// a positive duration in the sheet are shown for `default_duration` seconds. Tags with the
+    /// [`PingPong`][TagDirection::PingPong] direction always use [`PlayMode::PingPong`];
+    /// otherwise `mode` is used. Returns a `String` describing the problem if there's no such
+    /// tag, or `default_duration` is needed but isn't positive.
+    pub fn from_tag(
+        sheet: &SpriteSheet,
+        name: &str,
+        default_duration: f64,
+        mode: PlayMode,
+    ) -> Result<Self, String> {
+        let tag = sheet
+            .tag(name)
+            .ok_or_else(|| format!("No frame tag named {:?}", name))?;
+        let duration = |i: usize| {
+            sheet
+                .frame(i)
+                .and_then(|f| f.duration)
+                .filter(|&d| d > 0.0)
+                .unwrap_or(default_duration)
+        };
+        let mut frames: Vec<_> = (tag.from..=tag.to).map(|i| (i, duration(i))).collect();
+        if frames.iter().any(|&(_, d)| d <= 0.0) {
+            return Err("Frame durations must be positive".to_string());
+        }
+        let mode = match tag.direction {
+            TagDirection::Forward => mode,
+            TagDirection::Reverse => {
+                frames.reverse();
+                mode
+            }
+            TagDirection::PingPong => PlayMode::PingPong,
+        };
+        Ok(Self::with_durations(frames, mode))
+    }
+
+    /// Advance the animation by `elapsed_time` seconds, usually the value passed to
+    /// [`Application::on_update`][crate::Application::on_update]. Several frames may be skipped
+    /// if `elapsed_time` is longer than the current frame's duration.
+    pub fn update(&mut self, elapsed_time: f64) -> AnimationEvents {
+        let mut events = AnimationEvents::default();
+        if self.finished {
+            return events;
+        }
+        self.timer += elapsed_time;
+        while self.timer >= self.durations[self.current] {
+            self.timer -= self.durations[self.current];
+            if !self.advance() {
+                self.timer = 0.0;
+                self.finished = true;
+                events.finished = true;
+                break;
+            }
+            events.frame_changed = true;
+        }
+        events
+    }
+
+    /// Move to the next frame according to the play mode. Returns `false` if there is no next
+    /// frame.
+    fn advance(&mut self) -> bool {
+        let last = self.frames.len() - 1;
+        match self.mode {
+            PlayMode::Loop => {
+                self.current = if self.current == last {
+                    0
+                } else {
+                    self.current + 1
+                }
+            }
+            PlayMode::Once if self.current == last => return false,
+            PlayMode::Once => self.current += 1,
+            PlayMode::PingPong if last == 0 => {}
+            PlayMode::PingPong => {
+                if self.forwards && self.current == last {
+                    self.forwards = false;
+                } else if !self.forwards && self.current == 0 {
+                    self.forwards = true;
+                }
+                if self.forwards {
+                    self.current += 1;
+                } else {
+                    self.current -= 1;
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns the [`SpriteSheet`] index of the frame that should currently be shown.
+    pub fn frame(&self) -> usize {
+        self.frames[self.current]
+    }
+
+    /// Returns the position of the current frame within this animation's sequence.
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    /// Returns the play mode of this animation.
+    pub fn mode(&self) -> PlayMode {
+        self.mode
+    }
+
+    /// Change the play mode of this animation. This doesn't restart it.
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.mode = mode;
+    }
+
+    /// Returns `true` if a [`PlayMode::Once`] animation has reached its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Rewind the animation to its first frame.
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.forwards = true;
+        self.timer = 0.0;
+        self.finished = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::rect::Rect;
+
+    use super::*;
+
+    /// Collect the frame shown after each of `steps` updates.
+    fn play(anim: &mut Animation, elapsed_time: f64, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                anim.update(elapsed_time);
+                anim.frame()
+            })
+            .collect()
+    }
+
+    /// Test looping animations wrap back to the start.
+    #[test]
+    fn test_loop() {
+        let mut anim = Animation::new(vec![4, 5, 6], 0.5, PlayMode::Loop);
+        assert_eq!(anim.frame(), 4);
+        assert_eq!(play(&mut anim, 0.5, 4), vec![5, 6, 4, 5]);
+    }
+
+    /// Test ping-pong animations reverse at both ends.
+    #[test]
+    fn test_ping_pong() {
+        let mut anim = Animation::new(0..3, 1.0, PlayMode::PingPong);
+        assert_eq!(play(&mut anim, 1.0, 6), vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    /// Test one-shot animations stop on the last frame and only report finishing once.
+    #[test]
+    fn test_once() {
+        let mut anim = Animation::new(0..2, 1.0, PlayMode::Once);
+        assert_eq!(
+            anim.update(1.0),
+            AnimationEvents {
+                frame_changed: true,
+                finished: false
+            }
+        );
+        assert_eq!(
+            anim.update(1.0),
+            AnimationEvents {
+                frame_changed: false,
+                finished: true
+            }
+        );
+        assert_eq!(anim.update(1.0), AnimationEvents::default());
+        assert_eq!(anim.frame(), 1);
+        assert!(anim.is_finished());
+        anim.reset();
+        assert_eq!(anim.frame(), 0);
+        assert!(!anim.is_finished());
+    }
+
+    /// Test per-frame durations, and skipping frames on a long update.
+    #[test]
+    fn test_durations() {
+        let mut anim =
+            Animation::with_durations(vec![(0, 0.1), (1, 1.0), (2, 0.1)], PlayMode::Loop);
+        assert!(!anim.update(0.05).frame_changed);
+        assert!(anim.update(0.05).frame_changed);
+        assert_eq!(anim.frame(), 1);
+        anim.update(1.15);
+        assert_eq!(anim.frame(), 0);
+        // A whole loop back to the same frame is still a change
+        assert!(anim.update(1.2).frame_changed);
+        assert_eq!(anim.frame(), 0);
+    }
+
+    /// Test creating animations from sprite sheet tags.
+    #[test]
+    fn test_from_tag() {
+        let mut sheet = SpriteSheet::from_grid(64, 16, 16, 16);
+        sheet.add_tag("walk", 1, 3, TagDirection::Reverse).unwrap();
+        let mut anim = Animation::from_tag(&sheet, "walk", 0.1, PlayMode::Loop).unwrap();
+        assert_eq!(anim.frame(), 3);
+        assert_eq!(play(&mut anim, 0.1, 3), vec![2, 1, 3]);
+        assert!(Animation::from_tag(&sheet, "run", 0.1, PlayMode::Loop).is_err());
+        assert!(Animation::from_tag(&sheet, "walk", 0.0, PlayMode::Loop).is_err());
+
+        sheet.add_frame(Rect::new(0, 16, 16, 16), Some(0.0));
+        sheet.add_tag("hit", 4, 4, TagDirection::Forward).unwrap();
+        let mut anim = Animation::from_tag(&sheet, "hit", 0.1, PlayMode::Once).unwrap();
+        assert!(anim.update(0.1).finished);
+    }
+}
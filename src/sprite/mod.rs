@@ -0,0 +1,12 @@
+//! Sprite sheets and frame-based animation.
+//!
+//! A [`SpriteSheet`] slices a texture into frames, either as a uniform grid or from a JSON atlas
+//! exported by tools such as [Aseprite](https://www.aseprite.org). An [`Animation`] then steps
+//! through a sequence of those frames over time. Neither type owns the texture itself, so they
+//! can be shared between any number of sprites and canvases; pass the texture and the current
+//! frame to [`Canvas::draw_frame`][crate::canvas::Canvas::draw_frame] to draw it.
+
+mod animation;
+pub use animation::{Animation, AnimationEvents, PlayMode};
+mod sheet;
+pub use sheet::{Frame, FrameTag, SpriteSheet, TagDirection};
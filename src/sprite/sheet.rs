@@ -0,0 +1,318 @@
+//! Contains the [`SpriteSheet`] type, which describes where each frame of a sprite lives within a
+//! texture.
+
+use fnv::FnvHashMap;
+use sdl2::rect::Rect;
+
+/// A single frame within a [`SpriteSheet`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    /// The region of the texture containing this frame.
+    pub rect: Rect,
+    /// How long (in seconds) this frame should be shown for, if the sheet specifies it.
+    pub duration: Option<f64>,
+}
+
+/// The direction in which the frames of a [`FrameTag`] should be played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagDirection {
+    /// Play from the first frame to the last.
+    Forward,
+    /// Play from the last frame to the first.
+    Reverse,
+    /// Play forwards, then backwards, then forwards again.
+    PingPong,
+}
+
+/// A named, inclusive range of frames within a [`SpriteSheet`], such as "walk" or "jump".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameTag {
+    /// Index of the first frame.
+    pub from: usize,
+    /// Index of the last frame (inclusive).
+    pub to: usize,
+    /// The direction the frames should be played in.
+    pub direction: TagDirection,
+}
+
+/// Slices a texture into a list of frames, which can optionally be looked up by name.
+#[derive(Clone, Debug, Default)]
+pub struct SpriteSheet {
+    frames: Vec<Frame>,
+    names: FnvHashMap<String, usize>,
+    tags: FnvHashMap<String, FrameTag>,
+}
+
+impl SpriteSheet {
+    /// Create an empty sprite sheet. Frames can be added with [`add_frame`][Self::add_frame].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Slice a texture of the given size into a uniform grid of `frame_width` by
+    /// `frame_height` frames. Frames are numbered from left to right, then top to bottom, and any
+    /// partial frames at the right or bottom edges are ignored.
+    /// # Example
+    /// ```
+    /// use simple_game_engine::sprite::SpriteSheet;
+    ///
+    /// let sheet = SpriteSheet::from_grid(64, 32, 16, 16);
+    /// assert_eq!(sheet.len(), 8);
+    /// ```
+    pub fn from_grid(
+        texture_width: u32,
+        texture_height: u32,
+        frame_width: u32,
+        frame_height: u32,
+    ) -> Self {
+        let mut sheet = Self::new();
+        if frame_width == 0 || frame_height == 0 {
+            return sheet;
+        }
+        for row in 0..texture_height / frame_height {
+            for col in 0..texture_width / frame_width {
+                sheet.add_frame(
+                    Rect::new(
+                        (col * frame_width) as i32,
+                        (row * frame_height) as i32,
+                        frame_width,
+                        frame_height,
+                    ),
+                    None,
+                );
+            }
+        }
+        sheet
+    }
+
+    /// Load a sprite sheet from a JSON atlas in the format exported by Aseprite (and
+    /// TexturePacker's "JSON (Hash)" and "JSON (Array)" formats). Frame durations and frame tags
+    /// are read if present. Returns a `String` describing the problem if the JSON couldn't be
+    /// parsed.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let atlas: json::Atlas = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut sheet = Self::new();
+        let frames: Vec<(String, json::AtlasFrame)> = match atlas.frames {
+            json::Frames::Hash(frames) => frames
+                .into_iter()
+                .map(|(name, f)| Ok((name, serde_json::from_value(f)?)))
+                .collect::<Result<_, serde_json::Error>>()
+                .map_err(|e| e.to_string())?,
+            json::Frames::Array(frames) => frames
+                .into_iter()
+                .map(|f| (f.filename.clone().unwrap_or_default(), f))
+                .collect(),
+        };
+        for (name, frame) in frames {
+            let rect = Rect::new(frame.frame.x, frame.frame.y, frame.frame.w, frame.frame.h);
+            // Aseprite writes 0 for frames without a duration
+            let duration = frame.duration.filter(|&ms| ms > 0.0).map(|ms| ms / 1000.0);
+            if name.is_empty() {
+                sheet.add_frame(rect, duration);
+            } else {
+                sheet.add_named_frame(name, rect, duration);
+            }
+        }
+        for tag in atlas.meta.frame_tags {
+            let direction = match tag.direction.as_str() {
+                "reverse" => TagDirection::Reverse,
+                "pingpong" => TagDirection::PingPong,
+                _ => TagDirection::Forward,
+            };
+            sheet.add_tag(tag.name, tag.from, tag.to, direction)?;
+        }
+        Ok(sheet)
+    }
+
+    /// Append a frame to the sheet, returning its index.
+    pub fn add_frame(&mut self, rect: Rect, duration: Option<f64>) -> usize {
+        self.frames.push(Frame { rect, duration });
+        self.frames.len() - 1
+    }
+
+    /// Append a frame to the sheet that can also be looked up by name with
+    /// [`index_of`][Self::index_of], returning its index.
+    pub fn add_named_frame<S: Into<String>>(
+        &mut self,
+        name: S,
+        rect: Rect,
+        duration: Option<f64>,
+    ) -> usize {
+        let index = self.add_frame(rect, duration);
+        self.names.insert(name.into(), index);
+        index
+    }
+
+    /// Name an inclusive range of frames, so an [`Animation`][super::Animation] can be created
+    /// from it with [`Animation::from_tag`][super::Animation::from_tag]. Returns a `String`
+    /// describing the problem if `from` is after `to`, or `to` is past the last frame.
+    pub fn add_tag<S: Into<String>>(
+        &mut self,
+        name: S,
+        from: usize,
+        to: usize,
+        direction: TagDirection,
+    ) -> Result<(), String> {
+        let name = name.into();
+        if from > to || to >= self.len() {
+            return Err(format!("Frame tag {:?} is out of range", name));
+        }
+        self.tags.insert(
+            name,
+            FrameTag {
+                from,
+                to,
+                direction,
+            },
+        );
+        Ok(())
+    }
+
+    /// Get a frame by index.
+    pub fn frame(&self, index: usize) -> Option<&Frame> {
+        self.frames.get(index)
+    }
+
+    /// Get a frame by name.
+    pub fn named_frame(&self, name: &str) -> Option<&Frame> {
+        self.index_of(name).and_then(|i| self.frame(i))
+    }
+
+    /// Get the index of a named frame.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    /// Get a frame tag by name.
+    pub fn tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.get(name)
+    }
+
+    /// Returns all the frames in this sheet.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Returns the number of frames in this sheet.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if this sheet contains no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+#[cfg(feature = "json")]
+mod json {
+    //! Serde representation of Aseprite / TexturePacker JSON atlases.
+
+    use serde::Deserialize;
+    use serde_json::Map;
+
+    #[derive(Deserialize)]
+    pub struct Atlas {
+        pub frames: Frames,
+        #[serde(default)]
+        pub meta: Meta,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub enum Frames {
+        Hash(Map<String, serde_json::Value>),
+        Array(Vec<AtlasFrame>),
+    }
+
+    #[derive(Deserialize)]
+    pub struct AtlasFrame {
+        pub filename: Option<String>,
+        pub frame: AtlasRect,
+        pub duration: Option<f64>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct AtlasRect {
+        pub x: i32,
+        pub y: i32,
+        pub w: u32,
+        pub h: u32,
+    }
+
+    #[derive(Default, Deserialize)]
+    pub struct Meta {
+        #[serde(default, rename = "frameTags")]
+        pub frame_tags: Vec<AtlasTag>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct AtlasTag {
+        pub name: String,
+        pub from: usize,
+        pub to: usize,
+        #[serde(default)]
+        pub direction: String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test partial frames at the edges of a grid are skipped.
+    #[test]
+    fn test_grid() {
+        let sheet = SpriteSheet::from_grid(40, 20, 16, 16);
+        assert_eq!(sheet.len(), 2);
+        assert_eq!(sheet.frame(1).unwrap().rect, Rect::new(16, 0, 16, 16));
+        assert!(SpriteSheet::from_grid(40, 20, 0, 16).is_empty());
+    }
+
+    /// Test frame tags must be within the sheet.
+    #[test]
+    fn test_add_tag() {
+        let mut sheet = SpriteSheet::from_grid(48, 16, 16, 16);
+        assert!(sheet.add_tag("all", 0, 2, TagDirection::Forward).is_ok());
+        assert!(sheet.add_tag("past", 1, 3, TagDirection::Forward).is_err());
+        assert!(sheet
+            .add_tag("backwards", 2, 1, TagDirection::Forward)
+            .is_err());
+        assert!(sheet.tag("past").is_none());
+    }
+
+    /// Test loading an Aseprite-style atlas, preserving frame order.
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_json() {
+        let sheet = SpriteSheet::from_json(
+            r#"{
+                "frames": {
+                    "hero 10": {"frame": {"x": 16, "y": 0, "w": 16, "h": 16}, "duration": 100},
+                    "hero 2": {"frame": {"x": 0, "y": 0, "w": 16, "h": 16}, "duration": 250},
+                    "hero 3": {"frame": {"x": 32, "y": 0, "w": 16, "h": 16}, "duration": 0}
+                },
+                "meta": {
+                    "frameTags": [{"name": "idle", "from": 0, "to": 1, "direction": "pingpong"}]
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(sheet.index_of("hero 10"), Some(0));
+        assert_eq!(sheet.named_frame("hero 2").unwrap().duration, Some(0.25));
+        assert_eq!(sheet.named_frame("hero 3").unwrap().duration, None);
+        assert_eq!(
+            sheet.tag("idle"),
+            Some(&FrameTag {
+                from: 0,
+                to: 1,
+                direction: TagDirection::PingPong
+            })
+        );
+        assert!(SpriteSheet::from_json(
+            r#"{"frames": [], "meta": {"frameTags": [{"name": "x", "from": 0, "to": 0}]}}"#
+        )
+        .is_err());
+    }
+}
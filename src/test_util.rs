@@ -0,0 +1,23 @@
+//! Helpers shared by the unit tests.
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Returns a new, empty directory for a test to write files to. The directory's name includes
+/// the process ID and a counter, so tests running at the same time, or in other test binaries,
+/// never share one.
+pub(crate) fn test_dir(name: &str) -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNT.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!(
+        "sge-test-{}-{}-{}",
+        name,
+        std::process::id(),
+        count
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
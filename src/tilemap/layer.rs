@@ -0,0 +1,273 @@
+//! Types for the tile and object layers of a [`Tilemap`][super::Tilemap].
+
+use super::Properties;
+
+/// Bit set in a Tiled global tile ID if the tile is flipped horizontally.
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+/// Bit set in a Tiled global tile ID if the tile is flipped vertically.
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+/// Bit set in a Tiled global tile ID if the tile is flipped diagonally.
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+/// All of Tiled's flag bits, including the hexagonal rotation bit which is ignored.
+const FLAGS: u32 = 0xF000_0000;
+
+/// A tile placed on a [`TileLayer`] or attached to a [`MapObject`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tile {
+    /// The global ID of the tile (see [`Tileset::first_gid`][super::Tileset::first_gid]).
+    pub gid: u32,
+    /// Is the tile mirrored left to right?
+    pub flip_horizontal: bool,
+    /// Is the tile mirrored top to bottom?
+    pub flip_vertical: bool,
+    /// Is the tile mirrored along its top-left to bottom-right diagonal? This is applied before
+    /// the other flips, and combined with them allows tiles to be rotated in steps of 90 degrees.
+    pub flip_diagonal: bool,
+}
+
+impl Tile {
+    /// Create an unflipped tile.
+    pub fn new(gid: u32) -> Self {
+        Self {
+            gid,
+            ..Self::default()
+        }
+    }
+
+    /// Decode a global tile ID in Tiled's format, where the highest bits store the flip flags.
+    /// Returns `None` for 0, which means "no tile".
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        let gid = raw & !FLAGS;
+        if gid == 0 {
+            return None;
+        }
+        Some(Self {
+            gid,
+            flip_horizontal: raw & FLIPPED_HORIZONTALLY != 0,
+            flip_vertical: raw & FLIPPED_VERTICALLY != 0,
+            flip_diagonal: raw & FLIPPED_DIAGONALLY != 0,
+        })
+    }
+
+    /// Returns the rotation (in degrees, clockwise) and horizontal and vertical flips to pass to
+    /// [`copy_ex`][sdl2::render::Canvas::copy_ex] to draw this tile the right way round.
+    pub fn transform(&self) -> (f64, bool, bool) {
+        if self.flip_diagonal {
+            // A diagonal flip is the same as a vertical flip followed by a quarter turn
+            (90.0, self.flip_vertical, !self.flip_horizontal)
+        } else {
+            (0.0, self.flip_horizontal, self.flip_vertical)
+        }
+    }
+}
+
+/// A grid of tiles.
+#[derive(Clone, Debug, Default)]
+pub struct TileLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// Width of the layer, in tiles.
+    pub width: u32,
+    /// Height of the layer, in tiles.
+    pub height: u32,
+    /// The tiles in this layer, from left to right then top to bottom.
+    pub tiles: Vec<Option<Tile>>,
+    /// Whether the layer should be drawn.
+    pub visible: bool,
+    /// Opacity of the layer, from 0 to 1.
+    pub opacity: f64,
+    /// Horizontal offset of the layer, in pixels.
+    pub offset_x: i32,
+    /// Vertical offset of the layer, in pixels.
+    pub offset_y: i32,
+    /// Custom properties of the layer.
+    pub properties: Properties,
+}
+
+impl TileLayer {
+    /// Create a visible layer with no tiles.
+    /// # Panics
+    /// If the number of tiles doesn't fit in a `usize`.
+    pub fn new<S: Into<String>>(name: S, width: u32, height: u32) -> Self {
+        let len = tile_count(width, height).expect("too many tiles for one layer");
+        Self {
+            name: name.into(),
+            width,
+            height,
+            tiles: vec![None; len],
+            visible: true,
+            opacity: 1.0,
+            ..Self::default()
+        }
+    }
+
+    /// Get the tile at a grid position, if there is one.
+    pub fn get(&self, x: u32, y: u32) -> Option<Tile> {
+        if x < self.width && y < self.height {
+            self.tiles
+                .get(y as usize * self.width as usize + x as usize)
+                .copied()
+                .flatten()
+        } else {
+            None
+        }
+    }
+
+    /// Place or remove the tile at a grid position. Does nothing if the position is outside the
+    /// layer.
+    pub fn set(&mut self, x: u32, y: u32, tile: Option<Tile>) {
+        if x < self.width && y < self.height {
+            if let Some(cell) = self
+                .tiles
+                .get_mut(y as usize * self.width as usize + x as usize)
+            {
+                *cell = tile;
+            }
+        }
+    }
+}
+
+/// Returns the number of tiles in a `width` by `height` layer, or `None` if it doesn't fit in a
+/// `usize`.
+pub(super) fn tile_count(width: u32, height: u32) -> Option<usize> {
+    (width as usize).checked_mul(height as usize)
+}
+
+/// The shape of a [`MapObject`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ObjectShape {
+    /// A rectangle, or a tile if the object has one.
+    #[default]
+    Rectangle,
+    /// An ellipse filling the object's bounds.
+    Ellipse,
+    /// A single point.
+    Point,
+    /// A closed polygon, with points relative to the object's position.
+    Polygon(Vec<(f64, f64)>),
+    /// An open line, with points relative to the object's position.
+    Polyline(Vec<(f64, f64)>),
+}
+
+/// A freely positioned object, such as a spawn point, trigger area or collision shape.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MapObject {
+    /// Unique ID of the object.
+    pub id: u32,
+    /// Name of the object.
+    pub name: String,
+    /// The object's user-defined type (or "class").
+    pub kind: String,
+    /// *X* coordinate of the object, in pixels.
+    pub x: f64,
+    /// *Y* coordinate of the object, in pixels.
+    pub y: f64,
+    /// Width of the object, in pixels.
+    pub width: f64,
+    /// Height of the object, in pixels.
+    pub height: f64,
+    /// Clockwise rotation of the object, in degrees.
+    pub rotation: f64,
+    /// The tile drawn for this object, if any.
+    pub tile: Option<Tile>,
+    /// Whether the object should be shown.
+    pub visible: bool,
+    /// The shape of the object.
+    pub shape: ObjectShape,
+    /// Custom properties of the object.
+    pub properties: Properties,
+}
+
+/// A group of [`MapObject`]s.
+#[derive(Clone, Debug, Default)]
+pub struct ObjectLayer {
+    /// Name of the layer.
+    pub name: String,
+    /// The objects in this layer.
+    pub objects: Vec<MapObject>,
+    /// Whether the layer should be drawn.
+    pub visible: bool,
+    /// Opacity of the layer, from 0 to 1.
+    pub opacity: f64,
+    /// Horizontal offset of the layer, in pixels.
+    pub offset_x: i32,
+    /// Vertical offset of the layer, in pixels.
+    pub offset_y: i32,
+    /// Custom properties of the layer.
+    pub properties: Properties,
+}
+
+impl ObjectLayer {
+    /// Find an object by name.
+    pub fn object(&self, name: &str) -> Option<&MapObject> {
+        self.objects.iter().find(|o| o.name == name)
+    }
+}
+
+/// A single layer of a [`Tilemap`][super::Tilemap].
+#[derive(Clone, Debug)]
+pub enum Layer {
+    /// A grid of tiles.
+    Tiles(TileLayer),
+    /// A group of freely positioned objects.
+    Objects(ObjectLayer),
+}
+
+impl Layer {
+    /// Returns the name of the layer.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Tiles(layer) => &layer.name,
+            Self::Objects(layer) => &layer.name,
+        }
+    }
+
+    /// Returns the custom properties of the layer.
+    pub fn properties(&self) -> &Properties {
+        match self {
+            Self::Tiles(layer) => &layer.properties,
+            Self::Objects(layer) => &layer.properties,
+        }
+    }
+
+    /// Returns the tile layer, if this is one.
+    pub fn as_tiles(&self) -> Option<&TileLayer> {
+        match self {
+            Self::Tiles(layer) => Some(layer),
+            Self::Objects(_) => None,
+        }
+    }
+
+    /// Returns the object layer, if this is one.
+    pub fn as_objects(&self) -> Option<&ObjectLayer> {
+        match self {
+            Self::Objects(layer) => Some(layer),
+            Self::Tiles(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test decoding Tiled's flip flags.
+    #[test]
+    fn test_from_raw() {
+        assert_eq!(Tile::from_raw(0), None);
+        assert_eq!(Tile::from_raw(FLIPPED_HORIZONTALLY), None);
+        assert_eq!(
+            Tile::from_raw(0xA000_0005),
+            Some(Tile {
+                gid: 5,
+                flip_horizontal: true,
+                flip_vertical: false,
+                flip_diagonal: true,
+            })
+        );
+        assert_eq!(
+            Tile::from_raw(0xA000_0005).unwrap().transform(),
+            (90.0, false, false)
+        );
+    }
+}
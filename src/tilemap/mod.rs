@@ -0,0 +1,186 @@
+//! Tile-based maps with multiple layers, flipped and animated tiles, and object layers.
+//!
+//! A [`Tilemap`] can be built by hand, or imported from the [Tiled](https://www.mapeditor.org)
+//! map editor's `.tmx` and `.json` formats when this crate is built with the "tiled" cargo
+//! feature. Maps don't own any textures; pass one texture per [`Tileset`] to
+//! [`Canvas::draw_tilemap`][crate::canvas::Canvas::draw_tilemap] to draw the visible part of a
+//! map.
+
+mod layer;
+pub use layer::{Layer, MapObject, ObjectLayer, ObjectShape, Tile, TileLayer};
+mod tileset;
+pub use tileset::{TileAnimationFrame, TileData, Tileset};
+#[cfg(feature = "tiled")]
+mod tiled;
+
+use std::ops::Range;
+
+use fnv::FnvHashMap;
+use sdl2::{pixels::Color, rect::Rect};
+
+/// The value of a custom property set on a map, layer, object or tile.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Property {
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Int(i64),
+    /// A floating point number.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// A colour.
+    Color(Color),
+    /// A path to a file, relative to the map.
+    File(String),
+    /// The ID of an object on the map, or 0 for none.
+    Object(u32),
+}
+
+/// A set of named custom properties.
+pub type Properties = FnvHashMap<String, Property>;
+
+/// A map made up of layers of tiles and objects.
+#[derive(Clone, Debug, Default)]
+pub struct Tilemap {
+    /// Width of the map, in tiles.
+    pub width: u32,
+    /// Height of the map, in tiles.
+    pub height: u32,
+    /// Width of each grid cell, in pixels.
+    pub tile_width: u32,
+    /// Height of each grid cell, in pixels.
+    pub tile_height: u32,
+    /// The tilesets used by this map, ordered by their first global tile ID.
+    pub tilesets: Vec<Tileset>,
+    /// The layers of this map, from bottom to top.
+    pub layers: Vec<Layer>,
+    /// Custom properties of the map.
+    pub properties: Properties,
+    /// Time (in seconds) used to pick the current frame of animated tiles.
+    time: f64,
+}
+
+impl Tilemap {
+    /// Create an empty map with no tilesets or layers.
+    pub fn new(width: u32, height: u32, tile_width: u32, tile_height: u32) -> Self {
+        Self {
+            width,
+            height,
+            tile_width,
+            tile_height,
+            ..Self::default()
+        }
+    }
+
+    /// Advance animated tiles by `elapsed_time` seconds.
+    pub fn update(&mut self, elapsed_time: f64) {
+        self.time += elapsed_time;
+    }
+
+    /// Find the tileset containing the given global tile ID, returning its index in
+    /// [`tilesets`][Self::tilesets] and the tile's ID within it.
+    pub fn tileset_for(&self, gid: u32) -> Option<(usize, u32)> {
+        let (index, tileset) = self
+            .tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.first_gid <= gid)
+            .max_by_key(|(_, t)| t.first_gid)?;
+        let id = gid - tileset.first_gid;
+        if id < tileset.tile_count {
+            Some((index, id))
+        } else {
+            None
+        }
+    }
+
+    /// Work out which tileset and which area of its texture should currently be drawn for a
+    /// tile, taking animation into account.
+    pub fn tile_source(&self, tile: Tile) -> Option<(usize, Rect)> {
+        let (index, id) = self.tileset_for(tile.gid)?;
+        let tileset = &self.tilesets[index];
+        let id = tileset.animated_tile(id, self.time);
+        Some((index, tileset.tile_rect(id)))
+    }
+
+    /// Returns the range of columns and rows that overlap `view`, a rectangle in map pixel
+    /// coordinates.
+    pub fn visible_range(&self, view: Rect) -> (Range<u32>, Range<u32>) {
+        let range = |start: i32, end: i32, size: u32, count: u32| {
+            if size == 0 {
+                return 0..0;
+            }
+            let size = size as i32;
+            let start = start.div_euclid(size).max(0) as u32;
+            let end = (end + size - 1).div_euclid(size).max(0) as u32;
+            start.min(count)..end.min(count)
+        };
+        (
+            range(view.left(), view.right(), self.tile_width, self.width),
+            range(view.top(), view.bottom(), self.tile_height, self.height),
+        )
+    }
+
+    /// Get a layer by name.
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name() == name)
+    }
+
+    /// Get a mutable reference to a layer by name.
+    pub fn layer_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|l| l.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test finding tilesets by global ID.
+    #[test]
+    fn test_tileset_for() {
+        let mut map = Tilemap::new(4, 4, 16, 16);
+        map.tilesets.push(Tileset::new(1, 16, 16, 4, 8));
+        map.tilesets.push(Tileset::new(9, 16, 16, 2, 2));
+        assert_eq!(map.tileset_for(0), None);
+        assert_eq!(map.tileset_for(1), Some((0, 0)));
+        assert_eq!(map.tileset_for(8), Some((0, 7)));
+        assert_eq!(map.tileset_for(10), Some((1, 1)));
+        assert_eq!(map.tileset_for(11), None);
+    }
+
+    /// Test camera culling only visits tiles overlapping the view.
+    #[test]
+    fn test_visible_range() {
+        let map = Tilemap::new(10, 5, 16, 16);
+        assert_eq!(map.visible_range(Rect::new(0, 0, 32, 32)), (0..2, 0..2));
+        assert_eq!(map.visible_range(Rect::new(8, 8, 32, 32)), (0..3, 0..3));
+        assert_eq!(map.visible_range(Rect::new(-20, 60, 40, 100)), (0..2, 3..5));
+        assert_eq!(map.visible_range(Rect::new(200, 0, 40, 40)), (10..10, 0..3));
+    }
+
+    /// Test animated tiles follow the map's clock.
+    #[test]
+    fn test_animated_tile() {
+        let mut map = Tilemap::new(1, 1, 16, 16);
+        let mut tileset = Tileset::new(1, 16, 16, 4, 4);
+        tileset.tiles.entry(0).or_default().animation = vec![
+            TileAnimationFrame {
+                tile: 2,
+                duration: 0.5,
+            },
+            TileAnimationFrame {
+                tile: 3,
+                duration: 0.25,
+            },
+        ];
+        map.tilesets.push(tileset);
+        let tile = Tile::new(1);
+        assert_eq!(map.tile_source(tile), Some((0, Rect::new(32, 0, 16, 16))));
+        map.update(0.6);
+        assert_eq!(map.tile_source(tile), Some((0, Rect::new(48, 0, 16, 16))));
+        map.update(0.2);
+        assert_eq!(map.tile_source(tile), Some((0, Rect::new(32, 0, 16, 16))));
+    }
+}
@@ -0,0 +1,757 @@
+//! Importers for maps created with the [Tiled](https://www.mapeditor.org) map editor, in both its
+//! XML (`.tmx` / `.tsx`) and JSON (`.tmj` / `.tsj` / `.json`) formats.
+
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use base64::Engine as _;
+use roxmltree::{Document, Node};
+use sdl2::pixels::Color;
+use serde_json::Value;
+
+use super::{
+    layer::tile_count, Layer, MapObject, ObjectLayer, ObjectShape, Properties, Property, Tile,
+    TileAnimationFrame, TileData, TileLayer, Tilemap, Tileset,
+};
+
+/// Reads the contents of a file a map refers to, such as an external tileset. Errors start
+/// with the path.
+pub(crate) type ReadFile<'a> = dyn FnMut(&Path) -> Result<String, String> + 'a;
+
+impl Tilemap {
+    /// Load a Tiled map from a file, choosing the format from the file extension (`.tmx` for
+    /// XML, anything else for JSON). External tilesets are loaded relative to the map. Returns a
+    /// `String` describing the problem if the map couldn't be read or parsed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = read_to_string(path)?;
+        let base_dir = path.parent();
+        let result = if path.extension().is_some_and(|ext| ext == "tmx") {
+            Self::from_tmx(&contents, base_dir)
+        } else {
+            Self::from_tiled_json(&contents, base_dir)
+        };
+        result.map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse a map in Tiled's XML format. External tilesets and image paths are resolved
+    /// relative to `base_dir`, and maps with external tilesets can't be loaded without it.
+    pub fn from_tmx(xml: &str, base_dir: Option<&Path>) -> Result<Self, String> {
+        Self::from_tmx_with(xml, base_dir, &mut read_to_string)
+    }
+
+    /// Parse a map in Tiled's XML format, reading external tilesets with `read` rather than
+    /// from files.
+    pub(crate) fn from_tmx_with(
+        xml: &str,
+        base_dir: Option<&Path>,
+        read: &mut ReadFile,
+    ) -> Result<Self, String> {
+        let doc = Document::parse(xml).map_err(|e| e.to_string())?;
+        let root = doc.root_element();
+        if !root.has_tag_name("map") {
+            return Err("Expected a <map> element".to_string());
+        }
+        check_map(
+            root.attribute("orientation").unwrap_or("orthogonal"),
+            root.attribute("infinite") == Some("1"),
+        )?;
+        let mut map = Tilemap::new(
+            attr(root, "width")?,
+            attr(root, "height")?,
+            attr(root, "tilewidth")?,
+            attr(root, "tileheight")?,
+        );
+        map.properties = xml_properties(root)?;
+        for node in root.children().filter(|n| n.has_tag_name("tileset")) {
+            let first_gid = attr(node, "firstgid")?;
+            let tileset = match node.attribute("source") {
+                Some(source) => load_tileset(source, base_dir, first_gid, read)?,
+                None => xml_tileset(node, base_dir, first_gid)?,
+            };
+            map.tilesets.push(tileset);
+        }
+        map.tilesets.sort_by_key(|t| t.first_gid);
+        xml_layers(root, &mut map.layers, (0, 0))?;
+        Ok(map)
+    }
+
+    /// Parse a map in Tiled's JSON format. External tilesets and image paths are resolved
+    /// relative to `base_dir`, and maps with external tilesets can't be loaded without it.
+    pub fn from_tiled_json(json: &str, base_dir: Option<&Path>) -> Result<Self, String> {
+        Self::from_tiled_json_with(json, base_dir, &mut read_to_string)
+    }
+
+    /// Parse a map in Tiled's JSON format, reading external tilesets with `read` rather than
+    /// from files.
+    pub(crate) fn from_tiled_json_with(
+        json: &str,
+        base_dir: Option<&Path>,
+        read: &mut ReadFile,
+    ) -> Result<Self, String> {
+        let root: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        check_map(
+            root["orientation"].as_str().unwrap_or("orthogonal"),
+            root["infinite"].as_bool().unwrap_or(false),
+        )?;
+        let mut map = Tilemap::new(
+            json_u32(&root, "width")?,
+            json_u32(&root, "height")?,
+            json_u32(&root, "tilewidth")?,
+            json_u32(&root, "tileheight")?,
+        );
+        map.properties = json_properties(&root)?;
+        for value in json_array(&root, "tilesets") {
+            let first_gid = json_u32(value, "firstgid")?;
+            let tileset = match value["source"].as_str() {
+                Some(source) => load_tileset(source, base_dir, first_gid, read)?,
+                None => json_tileset(value, base_dir, first_gid)?,
+            };
+            map.tilesets.push(tileset);
+        }
+        map.tilesets.sort_by_key(|t| t.first_gid);
+        json_layers(&root, &mut map.layers, (0, 0))?;
+        Ok(map)
+    }
+}
+
+fn read_to_string(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn check_map(orientation: &str, infinite: bool) -> Result<(), String> {
+    if orientation != "orthogonal" {
+        return Err(format!("{} maps aren't supported", orientation));
+    }
+    if infinite {
+        return Err("Infinite maps aren't supported".to_string());
+    }
+    Ok(())
+}
+
+/// Load an external tileset, read with `read`, choosing the format from the file extension.
+fn load_tileset(
+    source: &str,
+    base_dir: Option<&Path>,
+    first_gid: u32,
+    read: &mut ReadFile,
+) -> Result<Tileset, String> {
+    let base_dir = base_dir
+        .ok_or_else(|| format!("Can't load external tileset {:?} without a path", source))?;
+    let path = base_dir.join(source);
+    let contents = read(&path)?;
+    let tileset_dir = path.parent();
+    let result = if path.extension().is_some_and(|ext| ext == "tsx") {
+        Document::parse(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|doc| xml_tileset(doc.root_element(), tileset_dir, first_gid))
+    } else {
+        serde_json::from_str(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|value| json_tileset(&value, tileset_dir, first_gid))
+    };
+    result.map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn image_path(source: &str, base_dir: Option<&Path>) -> PathBuf {
+    match base_dir {
+        Some(dir) => dir.join(source),
+        None => PathBuf::from(source),
+    }
+}
+
+/// Parse a Tiled colour, in `#RRGGBB` or `#AARRGGBB` format.
+fn parse_color(value: &str) -> Result<Color, String> {
+    let hex = value.trim_start_matches('#');
+    let n = u32::from_str_radix(hex, 16).map_err(|_| format!("Invalid colour {:?}", value))?;
+    match hex.len() {
+        6 => Ok(Color::RGB((n >> 16) as u8, (n >> 8) as u8, n as u8)),
+        8 => Ok(Color::RGBA(
+            (n >> 16) as u8,
+            (n >> 8) as u8,
+            n as u8,
+            (n >> 24) as u8,
+        )),
+        _ => Err(format!("Invalid colour {:?}", value)),
+    }
+}
+
+/// Convert a property value in its string form to a [`Property`].
+fn parse_property(kind: &str, value: &str) -> Result<Option<Property>, String> {
+    let invalid = || format!("Invalid {} property value {:?}", kind, value);
+    Ok(Some(match kind {
+        "string" => Property::String(value.to_string()),
+        "int" => Property::Int(value.parse().map_err(|_| invalid())?),
+        "float" => Property::Float(value.parse().map_err(|_| invalid())?),
+        "bool" => Property::Bool(value.parse().map_err(|_| invalid())?),
+        "color" if value.is_empty() => return Ok(None),
+        "color" => Property::Color(parse_color(value)?),
+        "file" => Property::File(value.to_string()),
+        "object" => Property::Object(value.parse().map_err(|_| invalid())?),
+        // Custom class properties aren't supported
+        _ => return Ok(None),
+    }))
+}
+
+/// Decode the contents of a tile layer's `data`, given its encoding and compression.
+fn decode_tiles(
+    data: &str,
+    encoding: Option<&str>,
+    compression: Option<&str>,
+) -> Result<Vec<Option<Tile>>, String> {
+    let raw: Vec<u32> = match encoding {
+        Some("csv") => data
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().map_err(|_| format!("Invalid tile {:?}", s)))
+            .collect::<Result<_, _>>()?,
+        Some("base64") => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data.trim())
+                .map_err(|e| e.to_string())?;
+            let mut decompressed = Vec::new();
+            let bytes = match compression.unwrap_or("") {
+                "" => bytes,
+                "zlib" => {
+                    flate2::read::ZlibDecoder::new(bytes.as_slice())
+                        .read_to_end(&mut decompressed)
+                        .map_err(|e| e.to_string())?;
+                    decompressed
+                }
+                "gzip" => {
+                    flate2::read::GzDecoder::new(bytes.as_slice())
+                        .read_to_end(&mut decompressed)
+                        .map_err(|e| e.to_string())?;
+                    decompressed
+                }
+                other => return Err(format!("{} compression isn't supported", other)),
+            };
+            bytes
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect()
+        }
+        Some(other) => return Err(format!("{} encoding isn't supported", other)),
+        None => return Err("Tile data has no encoding".to_string()),
+    };
+    Ok(raw.into_iter().map(Tile::from_raw).collect())
+}
+
+fn new_tile_layer(
+    name: String,
+    width: u32,
+    height: u32,
+    tiles: Vec<Option<Tile>>,
+) -> Result<TileLayer, String> {
+    let count =
+        tile_count(width, height).ok_or_else(|| format!("Layer {:?} has too many tiles", name))?;
+    if tiles.len() != count {
+        return Err(format!(
+            "Layer {:?} should have {} tiles, but has {}",
+            name,
+            count,
+            tiles.len()
+        ));
+    }
+    let mut layer = TileLayer::new(name, width, height);
+    layer.tiles = tiles;
+    Ok(layer)
+}
+
+// XML
+
+/// Read and parse a required attribute.
+fn attr<T: std::str::FromStr>(node: Node, name: &str) -> Result<T, String> {
+    let value = node.attribute(name).ok_or_else(|| {
+        format!(
+            "<{}> is missing the {:?} attribute",
+            node.tag_name().name(),
+            name
+        )
+    })?;
+    value.parse().map_err(|_| {
+        format!(
+            "<{}> has an invalid {:?} attribute: {:?}",
+            node.tag_name().name(),
+            name,
+            value
+        )
+    })
+}
+
+/// Read and parse an optional attribute.
+fn attr_or<T: std::str::FromStr>(node: Node, name: &str, default: T) -> Result<T, String> {
+    if node.has_attribute(name) {
+        attr(node, name)
+    } else {
+        Ok(default)
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn xml_properties(node: Node) -> Result<Properties, String> {
+    let mut properties = Properties::default();
+    if let Some(props) = child(node, "properties") {
+        for prop in props.children().filter(|n| n.has_tag_name("property")) {
+            let kind = prop.attribute("type").unwrap_or("string");
+            // Multi-line strings are stored as text rather than an attribute
+            let value = prop
+                .attribute("value")
+                .or_else(|| prop.text())
+                .unwrap_or("");
+            if let Some(value) = parse_property(kind, value)? {
+                properties.insert(attr(prop, "name")?, value);
+            }
+        }
+    }
+    Ok(properties)
+}
+
+fn xml_tileset(node: Node, base_dir: Option<&Path>, first_gid: u32) -> Result<Tileset, String> {
+    let mut tileset = Tileset::new(
+        first_gid,
+        attr(node, "tilewidth")?,
+        attr(node, "tileheight")?,
+        attr_or(node, "columns", 0)?,
+        attr_or(node, "tilecount", 0)?,
+    );
+    tileset.name = node.attribute("name").unwrap_or_default().to_string();
+    tileset.spacing = attr_or(node, "spacing", 0)?;
+    tileset.margin = attr_or(node, "margin", 0)?;
+    tileset.properties = xml_properties(node)?;
+    tileset.image = child(node, "image")
+        .and_then(|image| image.attribute("source"))
+        .map(|source| image_path(source, base_dir));
+    for tile in node.children().filter(|n| n.has_tag_name("tile")) {
+        let mut data = TileData {
+            kind: tile
+                .attribute("type")
+                .or_else(|| tile.attribute("class"))
+                .unwrap_or_default()
+                .to_string(),
+            properties: xml_properties(tile)?,
+            ..TileData::default()
+        };
+        if let Some(animation) = child(tile, "animation") {
+            for frame in animation.children().filter(|n| n.has_tag_name("frame")) {
+                data.animation.push(TileAnimationFrame {
+                    tile: attr(frame, "tileid")?,
+                    duration: attr::<f64>(frame, "duration")? / 1000.0,
+                });
+            }
+        }
+        tileset.tiles.insert(attr(tile, "id")?, data);
+    }
+    Ok(tileset)
+}
+
+/// Read every layer that's a child of `node`, flattening groups and adding their offsets.
+fn xml_layers(node: Node, layers: &mut Vec<Layer>, offset: (i32, i32)) -> Result<(), String> {
+    for child_node in node.children().filter(|n| n.is_element()) {
+        let name = child_node.attribute("name").unwrap_or_default().to_string();
+        let visible = child_node.attribute("visible") != Some("0");
+        let opacity = attr_or(child_node, "opacity", 1.0)?;
+        let offset_x = offset.0 + attr_or::<f64>(child_node, "offsetx", 0.0)? as i32;
+        let offset_y = offset.1 + attr_or::<f64>(child_node, "offsety", 0.0)? as i32;
+        let properties = xml_properties(child_node)?;
+        match child_node.tag_name().name() {
+            "layer" => {
+                let data = child(child_node, "data")
+                    .ok_or_else(|| format!("Layer {:?} has no data", name))?;
+                let tiles = match data.attribute("encoding") {
+                    None => data
+                        .children()
+                        .filter(|n| n.has_tag_name("tile"))
+                        .map(|tile| attr_or(tile, "gid", 0).map(Tile::from_raw))
+                        .collect::<Result<_, _>>()?,
+                    encoding => decode_tiles(
+                        data.text().unwrap_or_default(),
+                        encoding,
+                        data.attribute("compression"),
+                    )?,
+                };
+                let mut layer = new_tile_layer(
+                    name,
+                    attr(child_node, "width")?,
+                    attr(child_node, "height")?,
+                    tiles,
+                )?;
+                layer.visible = visible;
+                layer.opacity = opacity;
+                layer.offset_x = offset_x;
+                layer.offset_y = offset_y;
+                layer.properties = properties;
+                layers.push(Layer::Tiles(layer));
+            }
+            "objectgroup" => {
+                let objects = child_node
+                    .children()
+                    .filter(|n| n.has_tag_name("object"))
+                    .map(xml_object)
+                    .collect::<Result<_, _>>()?;
+                layers.push(Layer::Objects(ObjectLayer {
+                    name,
+                    objects,
+                    visible,
+                    opacity,
+                    offset_x,
+                    offset_y,
+                    properties,
+                }));
+            }
+            "group" => xml_layers(child_node, layers, (offset_x, offset_y))?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn xml_points(node: Node) -> Result<Vec<(f64, f64)>, String> {
+    attr::<String>(node, "points")?
+        .split_whitespace()
+        .map(|point| {
+            let mut coords = point.split(',').map(str::parse::<f64>);
+            match (coords.next(), coords.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => Ok((x, y)),
+                _ => Err(format!("Invalid point {:?}", point)),
+            }
+        })
+        .collect()
+}
+
+fn xml_object(node: Node) -> Result<MapObject, String> {
+    let shape = if child(node, "ellipse").is_some() {
+        ObjectShape::Ellipse
+    } else if child(node, "point").is_some() {
+        ObjectShape::Point
+    } else if let Some(polygon) = child(node, "polygon") {
+        ObjectShape::Polygon(xml_points(polygon)?)
+    } else if let Some(polyline) = child(node, "polyline") {
+        ObjectShape::Polyline(xml_points(polyline)?)
+    } else {
+        ObjectShape::Rectangle
+    };
+    Ok(MapObject {
+        id: attr_or(node, "id", 0)?,
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        kind: node
+            .attribute("type")
+            .or_else(|| node.attribute("class"))
+            .unwrap_or_default()
+            .to_string(),
+        x: attr_or(node, "x", 0.0)?,
+        y: attr_or(node, "y", 0.0)?,
+        width: attr_or(node, "width", 0.0)?,
+        height: attr_or(node, "height", 0.0)?,
+        rotation: attr_or(node, "rotation", 0.0)?,
+        tile: Tile::from_raw(attr_or(node, "gid", 0)?),
+        visible: node.attribute("visible") != Some("0"),
+        shape,
+        properties: xml_properties(node)?,
+    })
+}
+
+// JSON
+
+fn json_u32(value: &Value, key: &str) -> Result<u32, String> {
+    value[key]
+        .as_u64()
+        .map(|n| n as u32)
+        .ok_or_else(|| format!("Missing or invalid {:?}", key))
+}
+
+fn json_u32_or(value: &Value, key: &str, default: u32) -> u32 {
+    value[key].as_u64().map_or(default, |n| n as u32)
+}
+
+fn json_f64_or(value: &Value, key: &str, default: f64) -> f64 {
+    value[key].as_f64().unwrap_or(default)
+}
+
+fn json_str<'a>(value: &'a Value, key: &str) -> &'a str {
+    value[key].as_str().unwrap_or_default()
+}
+
+fn json_array<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    value[key].as_array().into_iter().flatten()
+}
+
+fn json_properties(value: &Value) -> Result<Properties, String> {
+    let mut properties = Properties::default();
+    for prop in json_array(value, "properties") {
+        let kind = prop["type"].as_str().unwrap_or("string");
+        let value = match &prop["value"] {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if let Some(value) = parse_property(kind, &value)? {
+            properties.insert(json_str(prop, "name").to_string(), value);
+        }
+    }
+    Ok(properties)
+}
+
+fn json_tileset(value: &Value, base_dir: Option<&Path>, first_gid: u32) -> Result<Tileset, String> {
+    let mut tileset = Tileset::new(
+        first_gid,
+        json_u32(value, "tilewidth")?,
+        json_u32(value, "tileheight")?,
+        json_u32_or(value, "columns", 0),
+        json_u32_or(value, "tilecount", 0),
+    );
+    tileset.name = json_str(value, "name").to_string();
+    tileset.spacing = json_u32_or(value, "spacing", 0);
+    tileset.margin = json_u32_or(value, "margin", 0);
+    tileset.properties = json_properties(value)?;
+    tileset.image = value["image"]
+        .as_str()
+        .map(|source| image_path(source, base_dir));
+    for tile in json_array(value, "tiles") {
+        let kind = tile["type"].as_str().or_else(|| tile["class"].as_str());
+        let mut data = TileData {
+            kind: kind.unwrap_or_default().to_string(),
+            properties: json_properties(tile)?,
+            ..TileData::default()
+        };
+        for frame in json_array(tile, "animation") {
+            data.animation.push(TileAnimationFrame {
+                tile: json_u32(frame, "tileid")?,
+                duration: json_f64_or(frame, "duration", 0.0) / 1000.0,
+            });
+        }
+        tileset.tiles.insert(json_u32(tile, "id")?, data);
+    }
+    Ok(tileset)
+}
+
+fn json_points(value: &Value) -> Vec<(f64, f64)> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|p| (json_f64_or(p, "x", 0.0), json_f64_or(p, "y", 0.0)))
+        .collect()
+}
+
+fn json_layers(value: &Value, layers: &mut Vec<Layer>, offset: (i32, i32)) -> Result<(), String> {
+    for layer in json_array(value, "layers") {
+        let name = json_str(layer, "name").to_string();
+        let visible = layer["visible"].as_bool().unwrap_or(true);
+        let opacity = json_f64_or(layer, "opacity", 1.0);
+        let offset_x = offset.0 + json_f64_or(layer, "offsetx", 0.0) as i32;
+        let offset_y = offset.1 + json_f64_or(layer, "offsety", 0.0) as i32;
+        let properties = json_properties(layer)?;
+        match json_str(layer, "type") {
+            "tilelayer" => {
+                let tiles = match &layer["data"] {
+                    Value::Array(data) => data
+                        .iter()
+                        .map(|gid| {
+                            gid.as_u64()
+                                .map(|gid| Tile::from_raw(gid as u32))
+                                .ok_or_else(|| format!("Invalid tile {}", gid))
+                        })
+                        .collect::<Result<_, _>>()?,
+                    Value::String(data) => decode_tiles(
+                        data,
+                        layer["encoding"].as_str(),
+                        layer["compression"].as_str(),
+                    )?,
+                    _ => return Err(format!("Layer {:?} has no data", name)),
+                };
+                let mut tile_layer = new_tile_layer(
+                    name,
+                    json_u32(layer, "width")?,
+                    json_u32(layer, "height")?,
+                    tiles,
+                )?;
+                tile_layer.visible = visible;
+                tile_layer.opacity = opacity;
+                tile_layer.offset_x = offset_x;
+                tile_layer.offset_y = offset_y;
+                tile_layer.properties = properties;
+                layers.push(Layer::Tiles(tile_layer));
+            }
+            "objectgroup" => {
+                let objects = json_array(layer, "objects")
+                    .map(json_object)
+                    .collect::<Result<_, _>>()?;
+                layers.push(Layer::Objects(ObjectLayer {
+                    name,
+                    objects,
+                    visible,
+                    opacity,
+                    offset_x,
+                    offset_y,
+                    properties,
+                }));
+            }
+            "group" => json_layers(layer, layers, (offset_x, offset_y))?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn json_object(value: &Value) -> Result<MapObject, String> {
+    let shape = if value["ellipse"].as_bool() == Some(true) {
+        ObjectShape::Ellipse
+    } else if value["point"].as_bool() == Some(true) {
+        ObjectShape::Point
+    } else if value["polygon"].is_array() {
+        ObjectShape::Polygon(json_points(&value["polygon"]))
+    } else if value["polyline"].is_array() {
+        ObjectShape::Polyline(json_points(&value["polyline"]))
+    } else {
+        ObjectShape::Rectangle
+    };
+    let kind = value["type"].as_str().or_else(|| value["class"].as_str());
+    Ok(MapObject {
+        id: json_u32_or(value, "id", 0),
+        name: json_str(value, "name").to_string(),
+        kind: kind.unwrap_or_default().to_string(),
+        x: json_f64_or(value, "x", 0.0),
+        y: json_f64_or(value, "y", 0.0),
+        width: json_f64_or(value, "width", 0.0),
+        height: json_f64_or(value, "height", 0.0),
+        rotation: json_f64_or(value, "rotation", 0.0),
+        tile: Tile::from_raw(json_u32_or(value, "gid", 0)),
+        visible: value["visible"].as_bool().unwrap_or(true),
+        shape,
+        properties: json_properties(value)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TMX: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="3" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <properties>
+  <property name="music" type="file" value="level1.ogg"/>
+  <property name="gravity" type="float" value="9.8"/>
+ </properties>
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" spacing="1" tilecount="8" columns="4">
+  <image source="terrain.png" width="67" height="33"/>
+  <tile id="2">
+   <properties><property name="solid" type="bool" value="true"/></properties>
+   <animation><frame tileid="2" duration="100"/><frame tileid="3" duration="100"/></animation>
+  </tile>
+ </tileset>
+ <layer id="1" name="ground" width="3" height="2">
+  <data encoding="csv">1,2,3,
+0,2147483653,0</data>
+ </layer>
+ <group name="things" offsetx="4">
+  <objectgroup id="2" name="spawns" offsety="2">
+   <object id="1" name="player" type="spawn" x="8" y="24"><point/></object>
+   <object id="2" name="zone" x="0" y="0" width="10" height="10">
+    <properties><property name="tint" type="color" value="#80ff0000"/></properties>
+    <polygon points="0,0 10,0 5,8"/>
+   </object>
+  </objectgroup>
+ </group>
+</map>"##;
+
+    /// Test loading a map in Tiled's XML format.
+    #[test]
+    fn test_tmx() {
+        let map = Tilemap::from_tmx(TMX, Some(Path::new("maps"))).unwrap();
+        assert_eq!((map.width, map.height, map.tile_width), (3, 2, 16));
+        assert_eq!(
+            map.properties["music"],
+            Property::File("level1.ogg".to_string())
+        );
+        assert_eq!(map.properties["gravity"], Property::Float(9.8));
+        let tileset = &map.tilesets[0];
+        assert_eq!(tileset.image, Some(PathBuf::from("maps/terrain.png")));
+        assert_eq!(tileset.tiles[&2].properties["solid"], Property::Bool(true));
+        assert_eq!(tileset.tiles[&2].animation.len(), 2);
+        let ground = map.layer("ground").unwrap().as_tiles().unwrap();
+        assert_eq!(ground.get(2, 0), Some(Tile::new(3)));
+        assert_eq!(ground.get(0, 1), None);
+        assert!(ground.get(1, 1).unwrap().flip_horizontal);
+        let spawns = map.layer("spawns").unwrap().as_objects().unwrap();
+        assert_eq!((spawns.offset_x, spawns.offset_y), (4, 2));
+        let player = spawns.object("player").unwrap();
+        assert_eq!(
+            (player.kind.as_str(), player.shape.clone()),
+            ("spawn", ObjectShape::Point)
+        );
+        let zone = spawns.object("zone").unwrap();
+        assert_eq!(
+            zone.properties["tint"],
+            Property::Color(Color::RGBA(255, 0, 0, 128))
+        );
+        assert_eq!(
+            zone.shape,
+            ObjectShape::Polygon(vec![(0.0, 0.0), (10.0, 0.0), (5.0, 8.0)])
+        );
+    }
+
+    /// Test loading a map in Tiled's JSON format, with base64 tile data.
+    #[test]
+    fn test_json() {
+        let map = Tilemap::from_tiled_json(
+            r#"{
+                "orientation": "orthogonal", "infinite": false,
+                "width": 2, "height": 1, "tilewidth": 8, "tileheight": 8,
+                "properties": [{"name": "lives", "type": "int", "value": 3}],
+                "tilesets": [{"firstgid": 1, "name": "t", "tilewidth": 8, "tileheight": 8,
+                              "tilecount": 4, "columns": 2, "image": "t.png"}],
+                "layers": [
+                    {"type": "tilelayer", "name": "a", "width": 2, "height": 1,
+                     "encoding": "base64", "data": "AgAAAAAAAAA="},
+                    {"type": "objectgroup", "name": "b", "objects": [
+                        {"id": 7, "name": "door", "x": 4, "y": 2, "ellipse": true,
+                         "properties": [{"name": "to", "type": "string", "value": "level2"}]}
+                    ]}
+                ]
+            }"#,
+            None,
+        )
+        .unwrap();
+        assert_eq!(map.properties["lives"], Property::Int(3));
+        assert_eq!(map.tilesets[0].image, Some(PathBuf::from("t.png")));
+        let a = map.layer("a").unwrap().as_tiles().unwrap();
+        assert_eq!(a.tiles, vec![Some(Tile::new(2)), None]);
+        let door = map
+            .layer("b")
+            .unwrap()
+            .as_objects()
+            .unwrap()
+            .object("door")
+            .unwrap();
+        assert_eq!((door.id, door.shape.clone()), (7, ObjectShape::Ellipse));
+        assert_eq!(
+            door.properties["to"],
+            Property::String("level2".to_string())
+        );
+    }
+
+    /// Test problems are reported rather than panicking.
+    #[test]
+    fn test_errors() {
+        assert!(Tilemap::from_tmx("<map/>", None).is_err());
+        assert!(Tilemap::from_tiled_json(
+            r#"{"width": 1, "height": 1, "tilewidth": 8, "tileheight": 8,
+                "tilesets": [{"firstgid": 1, "source": "t.tsj"}]}"#,
+            None
+        )
+        .is_err());
+        assert!(Tilemap::from_tiled_json(
+            r#"{"width": 2, "height": 1, "tilewidth": 8, "tileheight": 8,
+                "layers": [{"type": "tilelayer", "name": "a", "width": 2, "height": 1, "data": [1]}]}"#,
+            None
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! Contains the [`Tileset`] type, which describes how a texture is divided into tiles.
+
+use std::path::PathBuf;
+
+use fnv::FnvHashMap;
+use sdl2::rect::Rect;
+
+use super::Properties;
+
+/// One frame of an animated tile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileAnimationFrame {
+    /// The tile to show, as an ID within the same tileset.
+    pub tile: u32,
+    /// How long (in seconds) to show the tile for.
+    pub duration: f64,
+}
+
+/// Extra information attached to a single tile in a [`Tileset`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileData {
+    /// The tile's user-defined type (or "class"), if any.
+    pub kind: String,
+    /// Custom properties of the tile.
+    pub properties: Properties,
+    /// If not empty, the frames this tile cycles through.
+    pub animation: Vec<TileAnimationFrame>,
+}
+
+/// A texture divided into a grid of equally sized tiles.
+#[derive(Clone, Debug, Default)]
+pub struct Tileset {
+    /// Name of the tileset.
+    pub name: String,
+    /// Global ID of the first tile in this tileset. Tiles on a map refer to tileset tiles by
+    /// global ID, so that several tilesets can be used by the same map.
+    pub first_gid: u32,
+    /// Width of each tile, in pixels.
+    pub tile_width: u32,
+    /// Height of each tile, in pixels.
+    pub tile_height: u32,
+    /// Pixels between each tile.
+    pub spacing: u32,
+    /// Pixels around the edge of the image.
+    pub margin: u32,
+    /// Number of tiles in the tileset.
+    pub tile_count: u32,
+    /// Number of columns of tiles in the image.
+    pub columns: u32,
+    /// Path of the tileset's image, if it was loaded from a file.
+    pub image: Option<PathBuf>,
+    /// Custom properties of the tileset.
+    pub properties: Properties,
+    /// Per-tile data, keyed by the tile's ID within the tileset.
+    pub tiles: FnvHashMap<u32, TileData>,
+}
+
+impl Tileset {
+    /// Create a tileset with no spacing or margin between tiles.
+    pub fn new(
+        first_gid: u32,
+        tile_width: u32,
+        tile_height: u32,
+        columns: u32,
+        tile_count: u32,
+    ) -> Self {
+        Self {
+            first_gid,
+            tile_width,
+            tile_height,
+            columns,
+            tile_count,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the area of the tileset's texture containing tile `id`.
+    pub fn tile_rect(&self, id: u32) -> Rect {
+        let columns = self.columns.max(1);
+        let x = self.margin + (id % columns) * (self.tile_width + self.spacing);
+        let y = self.margin + (id / columns) * (self.tile_height + self.spacing);
+        Rect::new(x as i32, y as i32, self.tile_width, self.tile_height)
+    }
+
+    /// Returns the tile that should be shown in place of tile `id` at `time` seconds, or `id`
+    /// itself if it isn't animated.
+    pub fn animated_tile(&self, id: u32, time: f64) -> u32 {
+        let frames = match self.tiles.get(&id) {
+            Some(data) if !data.animation.is_empty() => &data.animation,
+            _ => return id,
+        };
+        let total: f64 = frames.iter().map(|f| f.duration).sum();
+        if total <= 0.0 {
+            return frames[0].tile;
+        }
+        let mut time = time.rem_euclid(total);
+        for frame in frames {
+            if time < frame.duration {
+                return frame.tile;
+            }
+            time -= frame.duration;
+        }
+        frames[frames.len() - 1].tile
+    }
+}
@@ -0,0 +1,179 @@
+#!/usr/bin/env python3
+"""Build test-font.ttf, a tiny TrueType font used by the unit tests.
+
+The font only has glyphs for a space, "i" and "W", made of straight lines, which is enough to
+test measuring and rasterising text. Run this from any directory to regenerate the file next to
+this script. Like the font, this script is licensed under the SIL Open Font License 1.1 (see
+OFL.txt).
+"""
+
+import os
+import struct
+
+UNITS_PER_EM = 1000
+ASCENT, DESCENT = 800, -200
+
+
+def rect(x0, y0, x1, y1):
+    """A clockwise rectangular contour."""
+    return [(x0, y0), (x0, y1), (x1, y1), (x1, y0)]
+
+
+# (name, character, advance width, contours)
+GLYPHS = [
+    (".notdef", None, 500, [rect(50, 0, 450, 700)]),
+    ("space", " ", 250, []),
+    ("W", "W", 900, [[
+        (20, 700), (120, 700), (220, 200), (400, 700), (500, 700), (680, 200), (780, 700),
+        (880, 700), (730, 0), (630, 0), (450, 500), (270, 0), (170, 0),
+    ]]),
+    ("i", "i", 220, [rect(60, 0, 160, 500), rect(60, 600, 160, 700)]),
+]
+
+NAMES = {
+    0: "Copyright (c) The simple-game-engine-rs contributors",
+    1: "SGE Test",
+    2: "Regular",
+    3: "SGE Test Regular",
+    4: "SGE Test Regular",
+    5: "Version 1.0",
+    6: "SGETest-Regular",
+    13: "This Font Software is licensed under the SIL Open Font License, Version 1.1.",
+    14: "https://openfontlicense.org",
+}
+
+
+def bounds(contours):
+    points = [p for contour in contours for p in contour]
+    if not points:
+        return (0, 0, 0, 0)
+    xs, ys = [p[0] for p in points], [p[1] for p in points]
+    return (min(xs), min(ys), max(xs), max(ys))
+
+
+def glyf_entry(contours):
+    """Encode a simple glyph with on-curve points and 16-bit coordinate deltas."""
+    if not contours:
+        return b""
+    data = struct.pack(">h4h", len(contours), *bounds(contours))
+    end = -1
+    for contour in contours:
+        end += len(contour)
+        data += struct.pack(">H", end)
+    points = [p for contour in contours for p in contour]
+    data += struct.pack(">H", 0)
+    data += bytes([0x01] * len(points))
+    for axis in (0, 1):
+        previous = 0
+        for point in points:
+            data += struct.pack(">h", point[axis] - previous)
+            previous = point[axis]
+    return data + b"\0" * (-len(data) % 4)
+
+
+def cmap_table():
+    mapping = sorted((ord(c), i) for i, (_, c, _, _) in enumerate(GLYPHS) if c)
+    segments = [(code, code, (glyph - code) % 0x10000) for code, glyph in mapping]
+    segments.append((0xFFFF, 0xFFFF, 1))
+    count = len(segments)
+    search = 2 ** (count.bit_length() - 1)
+    subtable = struct.pack(
+        ">7H", 4, 16 + 8 * count, 0, 2 * count, 2 * search, search.bit_length() - 1,
+        2 * (count - search),
+    )
+    subtable += struct.pack(">%dH" % count, *(end for _, end, _ in segments))
+    subtable += struct.pack(">H", 0)
+    subtable += struct.pack(">%dH" % count, *(start for start, _, _ in segments))
+    subtable += struct.pack(">%dH" % count, *(delta for _, _, delta in segments))
+    subtable += struct.pack(">%dH" % count, *([0] * count))
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+
+def name_table():
+    records, strings = b"", b""
+    for name_id, text in sorted(NAMES.items()):
+        encoded = text.encode("utf-16-be")
+        records += struct.pack(">6H", 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    return struct.pack(">3H", 0, len(NAMES), 6 + len(records)) + records + strings
+
+
+def os2_table():
+    average = sum(advance for _, _, advance, _ in GLYPHS) // len(GLYPHS)
+    codes = [ord(c) for _, c, _, _ in GLYPHS if c]
+    return struct.pack(
+        ">HhHHHhhhhhhhhhhh10s4I4sHHHhhhHHII",
+        4, average, 400, 5, 0,
+        650, 700, 0, 140, 650, 700, 0, 480, 50, 250, 0,
+        b"\0" * 10, 1, 0, 0, 0, b"NONE", 0x40, min(codes), max(codes),
+        ASCENT, DESCENT, 0, ASCENT, -DESCENT, 1, 0,
+    ) + struct.pack(">hhHHH", 500, 700, 0, 0x20, 0)
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def build():
+    glyf, loca = b"", [0]
+    for _, _, _, contours in GLYPHS:
+        glyf += glyf_entry(contours)
+        loca.append(len(glyf))
+    all_contours = [c for _, _, _, contours in GLYPHS for c in contours]
+    x_min, y_min, x_max, y_max = bounds(all_contours)
+    advances = [advance for _, _, advance, _ in GLYPHS]
+    lsbs = [bounds(contours)[0] for _, _, _, contours in GLYPHS]
+    rsbs = [a - bounds(c)[2] for (_, _, a, c) in GLYPHS if c]
+    max_points = max(sum(len(c) for c in contours) for _, _, _, contours in GLYPHS)
+    max_contours = max(len(contours) for _, _, _, contours in GLYPHS)
+
+    tables = {
+        b"OS/2": os2_table(),
+        b"cmap": cmap_table(),
+        b"glyf": glyf,
+        b"head": struct.pack(
+            ">IIIIHHqqhhhhHHhhh", 0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B,
+            UNITS_PER_EM, 0, 0, x_min, y_min, x_max, y_max, 0, 8, 2, 1, 0,
+        ),
+        b"hhea": struct.pack(
+            ">IhhhHhhhhhh4hhH", 0x00010000, ASCENT, DESCENT, 0,
+            max(advances), min(lsbs), min(rsbs), x_max, 1, 0, 0, 0, 0, 0, 0, 0,
+            len(GLYPHS),
+        ),
+        b"hmtx": b"".join(struct.pack(">Hh", a, l) for a, l in zip(advances, lsbs)),
+        b"loca": struct.pack(">%dI" % len(loca), *loca),
+        b"maxp": struct.pack(
+            ">I14H", 0x00010000, len(GLYPHS), max_points, max_contours, 0, 0, 2,
+            0, 0, 0, 0, 0, 0, 0, 0,
+        ),
+        b"name": name_table(),
+        b"post": struct.pack(">IIhhIIIII", 0x00030000, 0, -100, 50, 0, 0, 0, 0, 0),
+    }
+
+    count = len(tables)
+    search = 2 ** (count.bit_length() - 1)
+    header = struct.pack(
+        ">IHHHH", 0x00010000, count, search * 16, search.bit_length() - 1,
+        (count - search) * 16,
+    )
+    offset = len(header) + 16 * count
+    directory, body, head = b"", b"", 0
+    for tag in sorted(tables):
+        data = tables[tag]
+        if tag == b"head":
+            head = offset + len(body)
+        directory += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+    font = bytearray(header + directory + body)
+
+    # The head table's checkSumAdjustment makes the whole font sum to a magic number
+    adjustment = (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF
+    font[head + 8:head + 12] = struct.pack(">I", adjustment)
+    return bytes(font)
+
+
+if __name__ == "__main__":
+    path = os.path.join(os.path.dirname(os.path.abspath(__file__)), "test-font.ttf")
+    with open(path, "wb") as file:
+        file.write(build())